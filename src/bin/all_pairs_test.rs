@@ -1,17 +1,38 @@
 
 use botmarley::binance::DataCollector;
+use botmarley::bot::backtest::PortfolioSummary;
+use botmarley::bot::llm_check::check_llm_endpoint;
+use botmarley::bot::orders::{corrective_message, order_batch, order_problem};
+use botmarley::bot::response_format::{parse_response, schema_instruction, JsonSchemaMode};
 use botmarley::bot::system::get_system_message;
+use botmarley::bot::correlation::{compute_correlation_matrix, format_correlation_matrix, DEFAULT_CORRELATION_PERIOD};
+use botmarley::bot::indicators::volume_indicators::format_volume_analysis;
+use botmarley::bot::indicators::ensemble::{ensemble_from_ohlc, EnsembleWeights};
+use botmarley::bot::klines::{format_raw_ohlc, KlinesOHLC};
 use botmarley::config::Config;
+use botmarley::utils::report_math::{finite_or_zero, rank_by_profit, safe_pct};
 use botmarley::logging::init_logger;
+use botmarley::utils::context_budget::fit_context;
 use botmarley::utils::date_to_timestamp::date_string_to_timestamp;
+use botmarley::utils::decision_cache::DecisionCache;
+use botmarley::utils::holding::holding_duration;
+use botmarley::utils::interval::{KLINE_INTERVAL, interval_candles};
+use botmarley::utils::no_trade::hold_reason;
+use botmarley::utils::partial_exit::{partial_exit_amount, unrealized_pnl_pct};
+use botmarley::utils::allocation::{allocation_after_buy_pct, exceeds_allocation_cap};
+use botmarley::utils::profit_guard::min_sell_price;
+use botmarley::utils::risk::{realized_pnl_since, risk_summary, utc_day_start, RiskSummary};
+use botmarley::utils::slippage::fill_price;
 use color_eyre::eyre::WrapErr;
 use color_eyre::Section;
 use financial_indicators::macd::MACD;
 use financial_indicators::mfi::money_flow_index;
 use financial_indicators::rsi::relative_strength_index;
+use botmarley::bot::indicators::rsi::format_rsi;
 use financial_indicators::ma::simple_moving_average;
 use financial_indicators::ema::exponential_moving_average;
-use financial_indicators::bollinger::bollinger_bands;
+use botmarley::bot::indicators::bollinger::{percent_b_label, BollingerResult};
+use botmarley::bot::indicators::moving_averages::last_defined;
 use serde::{Deserialize, Serialize};
 use reqwest::Client;
 use chrono::{DateTime, Utc};
@@ -28,6 +49,9 @@ struct TradingDecision {
     thinking: Vec<Thought>,
     price_target: Option<f64>,
     stop_loss: Option<f64>,
+    /// Sell below the minimum profit, e.g. for a stop-loss exit
+    #[serde(default)]
+    override_min_profit: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -42,7 +66,8 @@ struct Thought {
 struct LLMRequest {
     model: String,
     messages: Vec<Message>,
-    response_format: ResponseFormat,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<ResponseFormat>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -149,17 +174,19 @@ struct TestReport {
     sell_accuracy_2h: f64,
     
     // Portfolio simulation
-    initial_portfolio_value: f64,
-    final_portfolio_value_30m: f64,
-    final_portfolio_value_1h: f64,
-    final_portfolio_value_2h: f64,
-    portfolio_return_30m: f64,
-    portfolio_return_1h: f64,
-    portfolio_return_2h: f64,
+    portfolio: PortfolioSummary,
     
     results: Vec<DecisionResult>,
 }
 
+impl TestReport {
+    /// Per-pair stats by descending 30m profit, then pair name, so repeated
+    /// runs print the pairs in the same order
+    fn sorted_pair_stats(&self) -> Vec<&PairStatistics> {
+        rank_by_profit(self.pair_stats.values(), |stats| (stats.profit_loss_30m, stats.pair.as_str()))
+    }
+}
+
 #[derive(Debug)]
 struct PairStatistics {
     pair: String,
@@ -182,6 +209,13 @@ struct MultiPairPortfolio {
     cash_amount: f64,
     holdings: HashMap<String, f64>, // pair -> amount
     transactions: Vec<Transaction>,
+    slippage_pct: f64,
+    min_profit_pct: f64,
+    fee_pct: f64,
+    max_asset_allocation_pct: f64,
+    max_holding_hours: f64,
+    partial_exit_drawdown_pct: f64,
+    partial_exit_fraction: f64,
 }
 
 #[derive(Debug, Clone)]
@@ -195,9 +229,20 @@ struct Transaction {
     portfolio_value_after: f64,
 }
 
-async fn make_llm_request(config: &Config, system_message: &str, user_message: &str) -> color_eyre::Result<TradingDecision> {
+/// Batch of orders returned when `max_orders_per_run` is above 1.
+#[derive(Debug, Serialize, Deserialize)]
+struct OrderBatch {
+    orders: Vec<TradingDecision>,
+}
+
+/// `order_problem` of a decision, against the pairs with loaded klines.
+fn decision_problem(decision: &TradingDecision, known_pairs: &HashMap<String, Vec<botmarley::binance::data_collector::KlineData>>) -> Option<String> {
+    order_problem(&decision.action, &decision.pair, decision.amount, decision.confidence, |pair| known_pairs.contains_key(pair))
+}
+
+async fn make_llm_request(config: &Config, system_message: &str, user_message: &str) -> color_eyre::Result<Vec<TradingDecision>> {
     let client = Client::new();
-    let schema = serde_json::json!({
+    let decision_schema = serde_json::json!({
         "type": "object",
         "properties": {
             "thinking": {  
@@ -247,31 +292,56 @@ async fn make_llm_request(config: &Config, system_message: &str, user_message: &
             "stop_loss": {
                 "type": "number"
             },
+            "override_min_profit": {
+                "type": "boolean",
+                "description": "Set to true to sell below the minimum profit, e.g. to cut a loss"
+            },
         },
         "required": ["thinking", "reasoning", "action", "pair", "amount", "confidence" ],  
         "additionalProperties": false
     });
+    let max_orders = config.max_orders_per_run.max(1);
+    let (schema_name, schema) = if max_orders > 1 {
+        ("order_batch", serde_json::json!({
+            "type": "object",
+            "properties": {
+                "orders": {
+                    "type": "array",
+                    "description": format!("Up to {} orders, executed sells first", max_orders),
+                    "items": decision_schema
+                }
+            },
+            "required": ["orders"],
+            "additionalProperties": false
+        }))
+    } else {
+        ("trading_decision", decision_schema)
+    };
 
+    let system_content = match config.json_schema_mode {
+        JsonSchemaMode::None => format!("{}\n\n{}", system_message, schema_instruction(&schema)),
+        JsonSchemaMode::Strict | JsonSchemaMode::Loose => system_message.to_string(),
+    };
     let request = LLMRequest {
         model: config.openai_model.clone(),
         messages: vec![
             Message {
                 role: "system".to_string(),
-                content: system_message.to_string(),
+                content: system_content,
             },
             Message {
                 role: "user".to_string(),
                 content: user_message.to_string(),
             },
         ],
-        response_format: ResponseFormat {
+        response_format: config.json_schema_mode.strict().map(|strict| ResponseFormat {
             format_type: "json_schema".to_string(),
             json_schema: JsonSchema {
-                name: "trading_decision".to_string(),
-                strict: true,
+                name: schema_name.to_string(),
+                strict,
                 schema,
             },
-        },
+        }),
     };
 
     let response = client
@@ -294,18 +364,35 @@ async fn make_llm_request(config: &Config, system_message: &str, user_message: &
         .wrap_err("Failed to parse LLM response")?;
 
     let content = &llm_response.choices[0].message.content;
-    let decision: TradingDecision = serde_json::from_str(content)
-        .wrap_err("Failed to parse trading decision JSON")?;
-    
-    info!("DECISION: {}", serde_json::to_string_pretty(&decision).unwrap());
-    Ok(decision)
+    let decisions = if max_orders > 1 {
+        let batch: OrderBatch = parse_response(content, config.json_schema_mode)
+            .wrap_err("Failed to parse order batch JSON")?;
+        order_batch(batch.orders, max_orders, |order| order.action.as_str())
+    } else {
+        let decision: TradingDecision = parse_response(content, config.json_schema_mode)
+            .wrap_err("Failed to parse trading decision JSON")?;
+        vec![decision]
+    };
+
+    for decision in &decisions {
+        info!("DECISION: {}", serde_json::to_string_pretty(decision).unwrap());
+    }
+    Ok(decisions)
 }
 
+/// Prompt sections dropped first when over `max_context_chars`
+const DROPPABLE_SECTIONS: [&str; 3] = ["Raw OHLCV:", "=== LAST 10 TRANSACTIONS ===", "=== CORRELATION"];
+/// Headers ending a droppable section; portfolio, pair data and instructions always stay
+const KEPT_SECTIONS: [&str; 2] = ["\n--- ", "=== "];
+
 fn generate_multi_pair_user_message(
     all_klines: &HashMap<String, Vec<botmarley::binance::data_collector::KlineData>>, 
     index: usize, 
     portfolio: &MultiPairPortfolio,
-    allowed_pairs: &[String]
+    allowed_pairs: &[String],
+    current_prices: &HashMap<String, f64>,
+    recent_count: usize,
+    include_raw_ohlc: bool,
 ) -> String {
     let mut message = String::new();
     
@@ -327,6 +414,49 @@ fn generate_multi_pair_user_message(
         }
     }
     message.push_str("\n");
+
+    let now_ms = allowed_pairs
+        .iter()
+        .filter_map(|pair| all_klines.get(pair)?.get(index))
+        .map(|k| k.close_time)
+        .max()
+        .unwrap_or_default();
+    let risk = portfolio.risk_summary(current_prices, now_ms);
+    message.push_str("=== RISK SUMMARY ===\n");
+    message.push_str(&format!("Total Value: ${:.2}\n", risk.total_value));
+    message.push_str(&format!("Allocated: {:.1}% | Cash: {:.1}%\n", risk.allocated_pct, risk.cash_pct));
+    match &risk.largest_position {
+        Some((pair, pct)) => message.push_str(&format!("Largest Position: {} ({:.1}% of portfolio)\n", pair, pct)),
+        None => message.push_str("Largest Position: none\n"),
+    }
+    message.push_str(&format!("Realized P&L Today: ${:+.2}\n", risk.realized_pnl_today));
+    if portfolio.max_holding_hours > 0.0 {
+        let mut held: Vec<&String> = portfolio.holdings.iter().filter(|(_, amount)| **amount > 0.0).map(|(pair, _)| pair).collect();
+        held.sort();
+        for pair in held {
+            if let Some(duration) = portfolio.holding_duration(pair, now_ms)
+                && duration.as_secs_f64() / 3600.0 > portfolio.max_holding_hours
+            {
+                message.push_str(&format!(
+                    "⚠️ MAX HOLDING PERIOD EXCEEDED: {} has been held for {:.1}h (limit {}h), strongly consider exiting\n",
+                    pair,
+                    duration.as_secs_f64() / 3600.0,
+                    portfolio.max_holding_hours
+                ));
+            }
+        }
+    }
+    for (pair, pnl_pct, amount) in portfolio.partial_exits(current_prices) {
+        message.push_str(&format!(
+            "⚠️ DRAWDOWN LIMIT EXCEEDED: {} is at {:+.1}% unrealized (limit -{}%), strongly consider selling {:.0}% of it ({:.6}) with override_min_profit to cut risk\n",
+            pair,
+            pnl_pct,
+            portfolio.partial_exit_drawdown_pct,
+            portfolio.partial_exit_fraction.clamp(0.0, 1.0) * 100.0,
+            amount
+        ));
+    }
+    message.push('\n');
     
     // Add last 10 transactions
     message.push_str("=== LAST 10 TRANSACTIONS ===\n");
@@ -352,6 +482,22 @@ fn generate_multi_pair_user_message(
     }
     message.push_str("\n");
     
+    // Correlation over the candles up to this index only
+    let closes: HashMap<String, Vec<f64>> = allowed_pairs
+        .iter()
+        .filter_map(|pair| {
+            let klines = all_klines.get(pair)?;
+            let end = (index + 1).min(klines.len());
+            Some((pair.clone(), klines[..end].iter().map(|k| k.close).collect()))
+        })
+        .collect();
+    let matrix = compute_correlation_matrix(&closes, DEFAULT_CORRELATION_PERIOD);
+    if !matrix.is_empty() {
+        message.push_str(&format!("=== CORRELATION (last {} returns) ===\n", DEFAULT_CORRELATION_PERIOD));
+        message.push_str(&format_correlation_matrix(allowed_pairs, &matrix));
+        message.push('\n');
+    }
+
     // Add market data for all allowed pairs
     message.push_str("=== MARKET DATA FOR ALL PAIRS ===\n");
     
@@ -359,7 +505,7 @@ fn generate_multi_pair_user_message(
         if let Some(klines) = all_klines.get(pair) {
             if !klines.is_empty() && index < klines.len() {
                 message.push_str(&format!("\n--- {} ---\n", pair));
-                message.push_str(&generate_pair_analysis(klines, index));
+                message.push_str(&generate_pair_analysis(klines, index, recent_count, include_raw_ohlc));
             }
         }
     }
@@ -377,7 +523,7 @@ fn generate_multi_pair_user_message(
     message
 }
 
-fn generate_pair_analysis(klines: &[botmarley::binance::data_collector::KlineData], index: usize) -> String {
+fn generate_pair_analysis(klines: &[botmarley::binance::data_collector::KlineData], index: usize, recent_count: usize, include_raw_ohlc: bool) -> String {
     if klines.is_empty() || index >= klines.len() {
         return "No kline data available".to_string();
     }
@@ -404,8 +550,8 @@ fn generate_pair_analysis(klines: &[botmarley::binance::data_collector::KlineDat
     // Add technical indicators if we have enough data
     if closes.len() >= 14 {
         let rsi = relative_strength_index(&closes, 14);
-        if let Some(current_rsi) = rsi.last().and_then(|r| *r) {
-            analysis.push_str(&format!("RSI(14): {:.2}\n", current_rsi));
+        if let Some(rsi_text) = format_rsi(&closes, &rsi, 14) {
+            analysis.push_str(&format!("{}\n", rsi_text));
         }
 
         let mfi = money_flow_index(&highs, &lows, &closes, &volumes, 14);
@@ -413,6 +559,7 @@ fn generate_pair_analysis(klines: &[botmarley::binance::data_collector::KlineDat
             analysis.push_str(&format!("MFI(14): {:.2}\n", current_mfi));
         }
     }
+    analysis.push_str(&format!("{}\n", format_volume_analysis(context_klines)));
 
     // Moving averages
     if closes.len() >= 20 {
@@ -426,16 +573,25 @@ fn generate_pair_analysis(klines: &[botmarley::binance::data_collector::KlineDat
             analysis.push_str(&format!("EMA(20): ${:.4}\n", current_ema_20));
         }
 
-        let bb = bollinger_bands(&closes, 20, 2.0);
+        let bb = BollingerResult::from_close(&closes, 20, 2.0);
         if let (Some(upper), Some(middle), Some(lower)) = (
-            bb.0.last().and_then(|u| *u),
-            bb.1.last().and_then(|m| *m),
-            bb.2.last().and_then(|l| *l),
+            bb.upper.last().and_then(|u| *u),
+            bb.middle.last().and_then(|m| *m),
+            bb.lower.last().and_then(|l| *l),
         ) {
             analysis.push_str(&format!(
                 "BB: U:${:.4} M:${:.4} L:${:.4}\n",
                 upper, middle, lower
             ));
+            if let (Some(percent_b), Some(bandwidth)) = (last_defined(&bb.percent_b), last_defined(&bb.bandwidth)) {
+                analysis.push_str(&format!(
+                    "BB %B: {:.2} ({}) BW: {:.4}{}\n",
+                    percent_b,
+                    percent_b_label(percent_b),
+                    bandwidth,
+                    if bb.squeeze { " SQUEEZE" } else { "" }
+                ));
+            }
         }
     }
 
@@ -450,9 +606,13 @@ fn generate_pair_analysis(klines: &[botmarley::binance::data_collector::KlineDat
         }
     }
 
+    let history = KlinesOHLC::from(&klines[..=index]);
+    let ensemble = ensemble_from_ohlc(current.symbol.to_string(), &history, &EnsembleWeights::default());
+    analysis.push_str(&format!("{}\n", ensemble.summary()));
+
     // Recent price trend
-    analysis.push_str("Recent 5 candles:\n");
-    let recent_start = if index >= 4 { index - 4 } else { 0 };
+    let recent_start = index.saturating_sub(recent_count.max(1) - 1);
+    analysis.push_str(&format!("Recent {} candles:\n", index - recent_start + 1));
     for i in recent_start..=index {
         let k = &klines[i];
         let change = if i > 0 {
@@ -472,17 +632,29 @@ fn generate_pair_analysis(klines: &[botmarley::binance::data_collector::KlineDat
         ));
     }
 
+    if include_raw_ohlc {
+        analysis.push_str("Raw OHLCV:\n");
+        analysis.push_str(&format_raw_ohlc(&klines[recent_start..=index]));
+    }
+
     analysis
 }
 
 impl MultiPairPortfolio {
-    fn new(initial_value: f64) -> Self {
+    fn new(initial_value: f64, config: &Config) -> Self {
         Self {
             initial_value,
             current_value: initial_value,
             cash_amount: initial_value,
             holdings: HashMap::new(),
             transactions: Vec::new(),
+            slippage_pct: config.slippage_pct,
+            min_profit_pct: config.min_profit_pct,
+            fee_pct: config.fee_pct,
+            max_asset_allocation_pct: config.max_asset_allocation_pct,
+            max_holding_hours: config.max_holding_hours,
+            partial_exit_drawdown_pct: config.partial_exit_drawdown_pct,
+            partial_exit_fraction: config.partial_exit_fraction,
         }
     }
 
@@ -498,7 +670,17 @@ impl MultiPairPortfolio {
 
         match decision.action.as_str() {
             "buy" => {
+                let price = fill_price(price, true, self.slippage_pct);
                 let trade_value = decision.amount * price;
+                let asset_value = self.holdings.get(&decision.pair).unwrap_or(&0.0) * price;
+                let portfolio_value = self.calculate_portfolio_value(current_prices);
+                if exceeds_allocation_cap(asset_value, trade_value, portfolio_value, self.max_asset_allocation_pct) {
+                    println!("🛑 Buy of {} rejected: {} would be {:.1}% of the portfolio, above the {:.1}% cap",
+                             decision.pair, decision.pair,
+                             allocation_after_buy_pct(asset_value, trade_value, portfolio_value),
+                             self.max_asset_allocation_pct);
+                    return;
+                }
                 if self.cash_amount >= trade_value {
                     self.cash_amount -= trade_value;
                     *self.holdings.entry(decision.pair.clone()).or_insert(0.0) += decision.amount;
@@ -520,6 +702,19 @@ impl MultiPairPortfolio {
                 let sell_amount = decision.amount.min(current_holding);
                 
                 if sell_amount > 0.0 {
+                    let price = fill_price(price, false, self.slippage_pct);
+                    if let Some(avg_buy) = self.average_buy_price(&decision.pair) {
+                        let min_price = min_sell_price(avg_buy, self.min_profit_pct, self.fee_pct);
+                        if price < min_price {
+                            if !decision.override_min_profit {
+                                println!("🛑 Sell of {} rejected: ${:.4} is below the minimum profit price ${:.4}",
+                                         decision.pair, price, min_price);
+                                return;
+                            }
+                            println!("⚠️  Selling {} below the minimum profit price ${:.4} (override)",
+                                     decision.pair, min_price);
+                        }
+                    }
                     let trade_value = sell_amount * price;
                     self.cash_amount += trade_value;
                     *self.holdings.entry(decision.pair.clone()).or_insert(0.0) -= sell_amount;
@@ -542,6 +737,67 @@ impl MultiPairPortfolio {
         self.current_value = self.calculate_portfolio_value(current_prices);
     }
 
+    /// Aggregate risk picture: allocation, concentration and today's realized P&L.
+    fn risk_summary(&self, current_prices: &HashMap<String, f64>, now_ms: i64) -> RiskSummary {
+        let position_values: Vec<(String, f64)> = self
+            .holdings
+            .iter()
+            .filter(|(_, amount)| **amount > 0.0)
+            .filter_map(|(pair, amount)| current_prices.get(pair).map(|price| (pair.clone(), amount * price)))
+            .collect();
+        let trades = self
+            .transactions
+            .iter()
+            .map(|t| (t.timestamp, t.pair.as_str(), t.action.as_str(), t.amount, t.value));
+        risk_summary(self.cash_amount, position_values, realized_pnl_since(trades, utc_day_start(now_ms)))
+    }
+
+    /// How long the current position in `pair` has been open at `now_ms`.
+    fn holding_duration(&self, pair: &str, now_ms: i64) -> Option<std::time::Duration> {
+        let trades = self
+            .transactions
+            .iter()
+            .filter(|t| t.pair == pair)
+            .map(|t| (t.timestamp, t.action.as_str(), t.amount));
+        holding_duration(trades, now_ms)
+    }
+
+    /// Held pairs past the partial exit drawdown at `current_prices`, sorted,
+    /// with their unrealized P&L in percent and the amount to sell.
+    fn partial_exits(&self, current_prices: &HashMap<String, f64>) -> Vec<(String, f64, f64)> {
+        let mut exits: Vec<(String, f64, f64)> = self
+            .holdings
+            .iter()
+            .filter_map(|(pair, held)| {
+                let price = *current_prices.get(pair)?;
+                let avg_buy = self.average_buy_price(pair)?;
+                let amount = partial_exit_amount(*held, avg_buy, price, self.partial_exit_drawdown_pct, self.partial_exit_fraction)?;
+                Some((pair.clone(), unrealized_pnl_pct(avg_buy, price), amount))
+            })
+            .collect();
+        exits.sort_by(|a, b| a.0.cmp(&b.0));
+        exits
+    }
+
+    /// Average cost of the currently held amount of `pair`, replaying the transactions.
+    fn average_buy_price(&self, pair: &str) -> Option<f64> {
+        let (mut amount, mut cost) = (0.0, 0.0);
+        for transaction in self.transactions.iter().filter(|t| t.pair == pair) {
+            match transaction.action.as_str() {
+                "buy" => {
+                    amount += transaction.amount;
+                    cost += transaction.value;
+                }
+                "sell" if amount > 0.0 => {
+                    cost -= cost / amount * transaction.amount;
+                    amount -= transaction.amount;
+                }
+                _ => {}
+            }
+        }
+        (amount > 0.0).then(|| cost / amount)
+    }
+
     fn calculate_portfolio_value(&self, current_prices: &HashMap<String, f64>) -> f64 {
         let mut total_value = self.cash_amount;
         
@@ -555,25 +811,6 @@ impl MultiPairPortfolio {
     }
 }
 
-async fn filter_klines_by_date_range(
-    klines: Vec<botmarley::binance::data_collector::KlineData>,
-    start_date: &str,
-    end_date: &str,
-) -> color_eyre::Result<Vec<botmarley::binance::data_collector::KlineData>> {
-    let start_timestamp = date_string_to_timestamp(start_date)? * 1000;
-    let end_timestamp = date_string_to_timestamp(end_date)? * 1000;
-
-    let filtered: Vec<_> = klines
-        .into_iter()
-        .filter(|kline| {
-            kline.open_time >= start_timestamp && kline.open_time <= end_timestamp
-        })
-        .collect();
-
-    println!("Filtered {} klines for date range {} to {}", filtered.len(), start_date, end_date);
-    Ok(filtered)
-}
-
 fn evaluate_decision(
     decision: &TradingDecision,
     current_price: f64,
@@ -601,7 +838,7 @@ fn evaluate_decision(
     }
 }
 
-fn generate_test_report(results: Vec<DecisionResult>, config: Config, pairs: Vec<String>, test_period_from: String, test_period_to: String) -> TestReport {
+fn generate_test_report(results: Vec<DecisionResult>, config: Config, pairs: Vec<String>, test_period_from: String, test_period_to: String, final_portfolio_value: f64) -> TestReport {
     let total_decisions = results.len();
     let buy_decisions = results.iter().filter(|r| r.decision.action == "buy").count();
     let sell_decisions = results.iter().filter(|r| r.decision.action == "sell").count();
@@ -616,9 +853,9 @@ fn generate_test_report(results: Vec<DecisionResult>, config: Config, pairs: Vec
     let total_with_1h = results.iter().filter(|r| r.was_correct_1h.is_some()).count();
     let total_with_2h = results.iter().filter(|r| r.was_correct_2h.is_some()).count();
 
-    let accuracy_30m = if total_with_30m > 0 { (correct_30m as f64 / total_with_30m as f64) * 100.0 } else { 0.0 };
-    let accuracy_1h = if total_with_1h > 0 { (correct_1h as f64 / total_with_1h as f64) * 100.0 } else { 0.0 };
-    let accuracy_2h = if total_with_2h > 0 { (correct_2h as f64 / total_with_2h as f64) * 100.0 } else { 0.0 };
+    let accuracy_30m = safe_pct(correct_30m as f64, total_with_30m as f64);
+    let accuracy_1h = safe_pct(correct_1h as f64, total_with_1h as f64);
+    let accuracy_2h = safe_pct(correct_2h as f64, total_with_2h as f64);
 
     // Calculate profit/loss metrics
     let total_profit_30m: f64 = results.iter().filter_map(|r| r.profit_loss_30m).sum();
@@ -629,13 +866,13 @@ fn generate_test_report(results: Vec<DecisionResult>, config: Config, pairs: Vec
     let avg_profit_1h = if total_with_1h > 0 { total_profit_1h / total_with_1h as f64 } else { 0.0 };
     let avg_profit_2h = if total_with_2h > 0 { total_profit_2h / total_with_2h as f64 } else { 0.0 };
 
-    let best_profit_30m = results.iter().filter_map(|r| r.profit_loss_30m).fold(f64::NEG_INFINITY, f64::max);
-    let best_profit_1h = results.iter().filter_map(|r| r.profit_loss_1h).fold(f64::NEG_INFINITY, f64::max);
-    let best_profit_2h = results.iter().filter_map(|r| r.profit_loss_2h).fold(f64::NEG_INFINITY, f64::max);
+    let best_profit_30m = finite_or_zero(results.iter().filter_map(|r| r.profit_loss_30m).fold(f64::NEG_INFINITY, f64::max));
+    let best_profit_1h = finite_or_zero(results.iter().filter_map(|r| r.profit_loss_1h).fold(f64::NEG_INFINITY, f64::max));
+    let best_profit_2h = finite_or_zero(results.iter().filter_map(|r| r.profit_loss_2h).fold(f64::NEG_INFINITY, f64::max));
 
-    let worst_loss_30m = results.iter().filter_map(|r| r.profit_loss_30m).fold(f64::INFINITY, f64::min);
-    let worst_loss_1h = results.iter().filter_map(|r| r.profit_loss_1h).fold(f64::INFINITY, f64::min);
-    let worst_loss_2h = results.iter().filter_map(|r| r.profit_loss_2h).fold(f64::INFINITY, f64::min);
+    let worst_loss_30m = finite_or_zero(results.iter().filter_map(|r| r.profit_loss_30m).fold(f64::INFINITY, f64::min));
+    let worst_loss_1h = finite_or_zero(results.iter().filter_map(|r| r.profit_loss_1h).fold(f64::INFINITY, f64::min));
+    let worst_loss_2h = finite_or_zero(results.iter().filter_map(|r| r.profit_loss_2h).fold(f64::INFINITY, f64::min));
 
     // Generate per-pair statistics
     let mut pair_stats = HashMap::new();
@@ -649,7 +886,7 @@ fn generate_test_report(results: Vec<DecisionResult>, config: Config, pairs: Vec
 
             let pair_correct_30m = pair_results.iter().filter(|r| r.was_correct_30m == Some(true)).count();
             let pair_total_30m = pair_results.iter().filter(|r| r.was_correct_30m.is_some()).count();
-            let pair_accuracy_30m = if pair_total_30m > 0 { (pair_correct_30m as f64 / pair_total_30m as f64) * 100.0 } else { 0.0 };
+            let pair_accuracy_30m = safe_pct(pair_correct_30m as f64, pair_total_30m as f64);
 
             let pair_profit_30m: f64 = pair_results.iter().filter_map(|r| r.profit_loss_30m).sum();
 
@@ -669,6 +906,7 @@ fn generate_test_report(results: Vec<DecisionResult>, config: Config, pairs: Vec
         }
     }
 
+    let portfolio = PortfolioSummary::new(&config, [final_portfolio_value; 3]);
     TestReport {
         pairs,
         model: config.openai_model.clone(),
@@ -707,13 +945,8 @@ fn generate_test_report(results: Vec<DecisionResult>, config: Config, pairs: Vec
         sell_accuracy_30m: 0.0,
         sell_accuracy_1h: 0.0,
         sell_accuracy_2h: 0.0,
-        initial_portfolio_value: 10000.0,
-        final_portfolio_value_30m: 10000.0,
-        final_portfolio_value_1h: 10000.0,
-        final_portfolio_value_2h: 10000.0,
-        portfolio_return_30m: 0.0,
-        portfolio_return_1h: 0.0,
-        portfolio_return_2h: 0.0,
+        // A single portfolio is traded at decision time, so every window shares it
+        portfolio,
         results,
     }
 }
@@ -728,9 +961,9 @@ fn print_test_report(report: &TestReport) {
     println!("   Total Decisions: {}", report.total_decisions);
     
     println!("\n📈 Decision Breakdown:");
-    println!("   🟢 Buy:  {} ({:.1}%)", report.buy_decisions, (report.buy_decisions as f64 / report.total_decisions as f64) * 100.0);
-    println!("   🔴 Sell: {} ({:.1}%)", report.sell_decisions, (report.sell_decisions as f64 / report.total_decisions as f64) * 100.0);
-    println!("   ⚪ Hold: {} ({:.1}%)", report.hold_decisions, (report.hold_decisions as f64 / report.total_decisions as f64) * 100.0);
+    println!("   🟢 Buy:  {} ({:.1}%)", report.buy_decisions, safe_pct(report.buy_decisions as f64, report.total_decisions as f64));
+    println!("   🔴 Sell: {} ({:.1}%)", report.sell_decisions, safe_pct(report.sell_decisions as f64, report.total_decisions as f64));
+    println!("   ⚪ Hold: {} ({:.1}%)", report.hold_decisions, safe_pct(report.hold_decisions as f64, report.total_decisions as f64));
     
     println!("\n🎯 Overall Accuracy:");
     println!("   30-minute: {:.1}%", report.accuracy_30m);
@@ -742,10 +975,14 @@ fn print_test_report(report: &TestReport) {
     println!("   1-hour:    {:+.2}% (avg: {:+.2}%)", report.total_profit_loss_1h, report.average_profit_per_decision_1h);
     println!("   2-hour:    {:+.2}% (avg: {:+.2}%)", report.total_profit_loss_2h, report.average_profit_per_decision_2h);
     
+    println!("\n💼 Portfolio:");
+    println!("   Initial: {:.2} {}", report.portfolio.initial_portfolio_value, report.portfolio.portfolio_currency);
+    println!("   Final:   {:.2} {} ({:+.2}%)", report.portfolio.final_portfolio_value_1h, report.portfolio.portfolio_currency, report.portfolio.portfolio_return_1h);
+
     println!("\n📊 Per-Pair Performance:");
-    for (pair, stats) in &report.pair_stats {
+    for stats in report.sorted_pair_stats() {
         println!("   {}: {} decisions, {:.1}% accuracy, {:+.2}% profit",
-                 pair, stats.decisions, stats.accuracy_30m, stats.profit_loss_30m);
+                 stats.pair, stats.decisions, stats.accuracy_30m, stats.profit_loss_30m);
     }
     
     println!("\n⚖️  Risk/Reward:");
@@ -763,13 +1000,16 @@ async fn main() -> color_eyre::Result<()> {
         .with_suggestion(|| "Make sure .env file exists and is readable")?;
     
     let config = (&*botmarley::config::CONFIG).clone();
+    if config.startup_llm_check {
+        check_llm_endpoint(&config).await?;
+    }
     let allowed_pairs = if config.allowed_pairs.is_empty() {
         vec!["BTCUSDC".to_string()]
     } else {
         config.pairs()
     };
     
-    let system_message = get_system_message();
+    let system_message = get_system_message(config.strategy_profile);
     let test_period_from = "2024-10-01";
     let test_period_to = "2024-10-02";
     
@@ -785,12 +1025,14 @@ async fn main() -> color_eyre::Result<()> {
     // Create single data collector
     let collector = DataCollector::new(config.clone())?;
     
+    let start_ms = date_string_to_timestamp(test_period_from)? * 1000;
+    let end_ms = date_string_to_timestamp(test_period_to)? * 1000;
+
     println!("\n📊 Loading klines data for all pairs...");
     for pair in &allowed_pairs {
         info!("Loading data for pair: {}", pair);
-        match collector.get_klines_for_symbol(pair.clone()).await {
-            Ok(klines) => {
-                let filtered_klines = filter_klines_by_date_range(klines, test_period_from, test_period_to).await?;
+        match collector.get_klines_for_symbol_range(pair.clone(), start_ms, end_ms).await {
+            Ok(filtered_klines) => {
                 if !filtered_klines.is_empty() {
                     min_length = min_length.min(filtered_klines.len());
                     all_klines.insert(pair.clone(), filtered_klines);
@@ -811,11 +1053,16 @@ async fn main() -> color_eyre::Result<()> {
     
     println!("📈 Processing {} pairs with minimum {} klines", all_klines.len(), min_length);
     
-    let mut portfolio = MultiPairPortfolio::new(10000.0);
+    let mut portfolio = MultiPairPortfolio::new(config.initial_portfolio_value, &config);
     let mut results = Vec::new();
+    let mut cache = DecisionCache::load(&config.decision_cache_path)?;
     
-    // Process each time point (skip the last 24 to ensure we have future data for evaluation)
-    let process_count = if min_length > 24 { min_length - 24 } else { 0 };
+    // Process each time point (skip the last 2h to ensure we have future data for evaluation)
+    let candles_30m = interval_candles(KLINE_INTERVAL, std::time::Duration::from_secs(30 * 60));
+    let candles_1h = interval_candles(KLINE_INTERVAL, std::time::Duration::from_secs(60 * 60));
+    let candles_2h = interval_candles(KLINE_INTERVAL, std::time::Duration::from_secs(2 * 60 * 60));
+    let process_count = min_length.saturating_sub(candles_2h);
+    let no_trade_windows = config.no_trade_time_windows()?;
     
     for i in 20..process_count.min(50) { // Limit to 30 decisions for demo
         println!("\n🔄 Processing decision {}/{}", i - 19, process_count.min(50) - 20);
@@ -828,91 +1075,152 @@ async fn main() -> color_eyre::Result<()> {
             }
         }
         
+        if config.partial_exit_auto {
+            for (pair, pnl_pct, amount) in portfolio.partial_exits(&current_prices) {
+                let Some(timestamp) = all_klines.get(&pair).and_then(|klines| klines.get(i)).map(|k| k.open_time) else {
+                    continue;
+                };
+                println!("✂️  Partial exit: selling {:.6} {} at {:+.1}% unrealized", amount, pair, pnl_pct);
+                let decision = TradingDecision {
+                    action: "sell".to_string(),
+                    pair,
+                    amount,
+                    confidence: 1.0,
+                    reasoning: format!("Drawdown of {:.1}% exceeded the partial exit limit", -pnl_pct),
+                    thinking: Vec::new(),
+                    price_target: None,
+                    stop_loss: None,
+                    override_min_profit: true,
+                };
+                portfolio.execute_trade(&decision, &current_prices, timestamp);
+            }
+        }
+
         // Generate user message with all pairs data
-        let user_message = generate_multi_pair_user_message(&all_klines, i, &portfolio, &allowed_pairs);
+        let mut user_message = fit_context(
+            &generate_multi_pair_user_message(&all_klines, i, &portfolio, &allowed_pairs, &current_prices, config.context_candle_count, config.include_raw_ohlc),
+            config.max_context_chars,
+            &DROPPABLE_SECTIONS,
+            &KEPT_SECTIONS,
+        );
         
-        // Make LLM request
-        match make_llm_request(&config, &system_message, &user_message).await {
-            Ok(decision) => {
-                // Validate that the decision pair exists in our data
-                if !all_klines.contains_key(&decision.pair) {
-                    println!("❌ LLM returned invalid pair '{}', skipping decision", decision.pair);
-                    continue;
-                }
-                
-                let current_price = *current_prices.get(&decision.pair).unwrap_or(&0.0);
-                if current_price == 0.0 {
-                    println!("❌ No price data for pair '{}', skipping decision", decision.pair);
-                    continue;
-                }
+        if config.max_orders_per_run > 1 {
+            user_message.push_str(&format!(
+                "\nYou may return up to {} orders (e.g. sell one pair and buy another to rebalance).\n",
+                config.max_orders_per_run
+            ));
+        }
+
+        // Make LLM request, unless the same prompt was answered in an earlier run
+        let misses_before = cache.misses;
+        let key = DecisionCache::key(&config.openai_model, &system_message, &user_message);
+        let mut response = cache.get_or_fetch(&key, || make_llm_request(&config, &system_message, &user_message)).await;
+
+        // Missing or zero fields are sent back once, naming the field, instead of recording a degenerate trade
+        if let Ok(decisions) = &response {
+            let problems: Vec<String> = decisions
+                .iter()
+                .filter_map(|d| decision_problem(d, &all_klines).map(|problem| format!("{} {}: {}", d.action, d.pair, problem)))
+                .collect();
+            if !problems.is_empty() {
+                println!("⚠️  Rejected order(s), asking the model to correct them: {}", problems.join("; "));
+                let corrective_message = corrective_message(&user_message, &problems);
+                let key = DecisionCache::key(&config.openai_model, &system_message, &corrective_message);
+                response = cache.get_or_fetch(&key, || make_llm_request(&config, &system_message, &corrective_message)).await;
+            }
+        }
+
+        match response {
+            Ok(decisions) => {
+                // Every order of a batch is validated and executed on its own
+                for mut decision in decisions {
+                    if let Some(problem) = decision_problem(&decision, &all_klines) {
+                        println!("❌ Skipping {} {}: {}", decision.action, decision.pair, problem);
+                        continue;
+                    }
                 
-                println!("🤖 Decision: {} {} {:.6} @ ${:.4} (confidence: {:.0}%)",
-                         decision.action.to_uppercase(),
-                         decision.pair,
-                         decision.amount,
-                         current_price,
-                         decision.confidence * 100.0);
-                println!("💭 Reasoning: {}", decision.reasoning);
+                    let current_price = *current_prices.get(&decision.pair).unwrap_or(&0.0);
+                    if current_price == 0.0 {
+                        println!("❌ No price data for pair '{}', skipping decision", decision.pair);
+                        continue;
+                    }
                 
-                let pair_klines = all_klines.get(&decision.pair).unwrap();
-                let timestamp = pair_klines[i].open_time;
+                    println!("🤖 Decision: {} {} {:.6} @ ${:.4} (confidence: {:.0}%)",
+                             decision.action.to_uppercase(),
+                             decision.pair,
+                             decision.amount,
+                             current_price,
+                             decision.confidence * 100.0);
+                    println!("💭 Reasoning: {}", decision.reasoning);
                 
-                // Execute the trade in portfolio
-                portfolio.execute_trade(&decision, &current_prices, timestamp);
+                    let pair_klines = all_klines.get(&decision.pair).unwrap();
+                    let timestamp = pair_klines[i].open_time;
+                    if decision.action != "hold"
+                        && let Some(reason) = DateTime::<Utc>::from_timestamp_millis(timestamp).and_then(|t| hold_reason(&no_trade_windows, t))
+                    {
+                        println!("⏸️  Forcing HOLD instead of {} {}: {}", decision.action, decision.pair, reason);
+                        decision.action = "hold".to_string();
+                    }
                 
-                // Get future prices for evaluation
-                let future_30m_idx = i + 6; // 30 minutes = 6 * 5-minute candles
-                let future_1h_idx = i + 12; // 1 hour = 12 * 5-minute candles
-                let future_2h_idx = i + 24; // 2 hours = 24 * 5-minute candles
+                    // Execute the trade in portfolio
+                    portfolio.execute_trade(&decision, &current_prices, timestamp);
                 
-                let actual_price_30m = if future_30m_idx < pair_klines.len() {
-                    Some(pair_klines[future_30m_idx].close)
-                } else { None };
+                    // Get future prices for evaluation
+                    let future_30m_idx = i + candles_30m;
+                    let future_1h_idx = i + candles_1h;
+                    let future_2h_idx = i + candles_2h;
                 
-                let actual_price_1h = if future_1h_idx < pair_klines.len() {
-                    Some(pair_klines[future_1h_idx].close)
-                } else { None };
+                    let actual_price_30m = if future_30m_idx < pair_klines.len() {
+                        Some(pair_klines[future_30m_idx].close)
+                    } else { None };
                 
-                let actual_price_2h = if future_2h_idx < pair_klines.len() {
-                    Some(pair_klines[future_2h_idx].close)
-                } else { None };
+                    let actual_price_1h = if future_1h_idx < pair_klines.len() {
+                        Some(pair_klines[future_1h_idx].close)
+                    } else { None };
                 
-                // Evaluate decisions
-                let (was_correct_30m, profit_loss_30m) = if let Some(future_price) = actual_price_30m {
-                    let (correct, profit, _) = evaluate_decision(&decision, current_price, future_price);
-                    (Some(correct), Some(profit))
-                } else { (None, None) };
+                    let actual_price_2h = if future_2h_idx < pair_klines.len() {
+                        Some(pair_klines[future_2h_idx].close)
+                    } else { None };
                 
-                let (was_correct_1h, profit_loss_1h) = if let Some(future_price) = actual_price_1h {
-                    let (correct, profit, _) = evaluate_decision(&decision, current_price, future_price);
-                    (Some(correct), Some(profit))
-                } else { (None, None) };
+                    // Evaluate decisions
+                    let (was_correct_30m, profit_loss_30m) = if let Some(future_price) = actual_price_30m {
+                        let (correct, profit, _) = evaluate_decision(&decision, current_price, future_price);
+                        (Some(correct), Some(profit))
+                    } else { (None, None) };
                 
-                let (was_correct_2h, profit_loss_2h) = if let Some(future_price) = actual_price_2h {
-                    let (correct, profit, _) = evaluate_decision(&decision, current_price, future_price);
-                    (Some(correct), Some(profit))
-                } else { (None, None) };
+                    let (was_correct_1h, profit_loss_1h) = if let Some(future_price) = actual_price_1h {
+                        let (correct, profit, _) = evaluate_decision(&decision, current_price, future_price);
+                        (Some(correct), Some(profit))
+                    } else { (None, None) };
                 
-                let result = DecisionResult {
-                    timestamp,
-                    pair: decision.pair.clone(),
-                    price: current_price,
-                    decision,
-                    actual_price_30m,
-                    actual_price_1h,
-                    actual_price_2h,
-                    was_correct_30m,
-                    was_correct_1h,
-                    was_correct_2h,
-                    profit_loss_30m,
-                    profit_loss_1h,
-                    profit_loss_2h,
-                };
+                    let (was_correct_2h, profit_loss_2h) = if let Some(future_price) = actual_price_2h {
+                        let (correct, profit, _) = evaluate_decision(&decision, current_price, future_price);
+                        (Some(correct), Some(profit))
+                    } else { (None, None) };
                 
-                results.push(result);
+                    let result = DecisionResult {
+                        timestamp,
+                        pair: decision.pair.clone(),
+                        price: current_price,
+                        decision,
+                        actual_price_30m,
+                        actual_price_1h,
+                        actual_price_2h,
+                        was_correct_30m,
+                        was_correct_1h,
+                        was_correct_2h,
+                        profit_loss_30m,
+                        profit_loss_1h,
+                        profit_loss_2h,
+                    };
                 
+                    results.push(result);
+                }
+
                 // Small delay to avoid rate limiting
-                tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+                if cache.misses > misses_before {
+                    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+                }
             }
             Err(e) => {
                 println!("❌ Failed to get decision for index {}: {}", i, e);
@@ -921,7 +1229,8 @@ async fn main() -> color_eyre::Result<()> {
     }
     
     // Generate and print report
-    let report = generate_test_report(results, config, allowed_pairs, test_period_from.to_string(), test_period_to.to_string());
+    cache.flush()?;
+    let report = generate_test_report(results, (*config).clone(), allowed_pairs, test_period_from.to_string(), test_period_to.to_string(), portfolio.current_value);
     print_test_report(&report);
     
     println!("\n✅ Multi-pair trading test completed!");
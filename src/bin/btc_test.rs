@@ -1,45 +1,50 @@
 use botmarley::binance::DataCollector;
+use botmarley::bot::backtest::{BacktestRun, DecisionResult, PortfolioSimulation, TestReport, TradingDecision, Transaction};
+use botmarley::bot::dataset::{indicator_performance, DatasetRecord, DatasetRecorder};
+use botmarley::bot::indicators::{agreement_summary, series_until, tally_signals, DirectionalSeries, IndicatorResult};
+use botmarley::bot::indicators::patterns::PatternsResult;
+use botmarley::bot::indicators::lin_reg::LinRegResult;
+use botmarley::bot::indicators::mass_index::MassIndexResult;
+use botmarley::bot::indicators::frame::IndicatorColumns;
+use botmarley::bot::indicators::ema_extension::{extension_at, extension_summary};
+use botmarley::bot::indicators::volume_indicators::format_volume_analysis;
+use botmarley::bot::indicators::moving_averages::{ema, ribbon_label, ribbon_score_at, RIBBON_PERIODS};
+use botmarley::bot::llm_check::check_llm_endpoint;
+use botmarley::bot::response_format::{parse_response, schema_instruction, JsonSchemaMode};
 use botmarley::bot::system::get_system_message;
+use botmarley::bot::prompt::LastDecision;
+use botmarley::bot::klines::{format_raw_ohlc, KlinesOHLC};
 use botmarley::config::Config;
 use botmarley::logging::init_logger;
+use botmarley::utils::context_budget::fit_context;
+use botmarley::utils::confidence::calibrate_confidence;
+use botmarley::utils::interval::{KLINE_INTERVAL, candles_to_duration, interval_candles};
 use botmarley::utils::date_to_timestamp::date_string_to_timestamp;
+use botmarley::utils::decision_cache::DecisionCache;
+use botmarley::utils::session::{session_at, session_volatility_series};
+use botmarley::utils::walk_forward::sample_ranges;
 use color_eyre::eyre::WrapErr;
 use color_eyre::Section;
 use financial_indicators::macd::MACD;
 use financial_indicators::mfi::money_flow_index;
 use financial_indicators::rsi::relative_strength_index;
+use botmarley::bot::indicators::rsi::format_rsi;
 use financial_indicators::ma::simple_moving_average;
 use financial_indicators::ema::exponential_moving_average;
-use financial_indicators::bollinger::bollinger_bands;
+use botmarley::bot::indicators::bollinger::BollingerResult;
 use serde::{Deserialize, Serialize};
 use reqwest::Client;
+use std::sync::Arc;
 use chrono::{DateTime, Utc};
 use tracing::{info, instrument};
 
-#[derive(Debug, Serialize, Deserialize)]
-struct TradingDecision {
-    action: String, // "buy", "sell", "hold"
-    confidence: f64, // 0.0 to 1.0
-    reasoning: String,
-    thinking:Vec<Thought>,
-    price_target: Option<f64>,
-    stop_loss: Option<f64>,
-}
-#[derive(Debug, Serialize, Deserialize)]
-struct Thought {
-    step: f64,  
-    thought: String,
-    require_next_step: bool,
-    conclusion: String,
-
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 struct LLMRequest {
     model: String,
     messages: Vec<Message>,
     // temperature: f64,
-    response_format: ResponseFormat,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<ResponseFormat>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -77,132 +82,6 @@ struct ResponseMessage {
     content: String,
 }
 
-#[derive(Debug)]
-struct DecisionResult {
-    timestamp: i64,
-    price: f64,
-    decision: TradingDecision,
-    actual_price_30m: Option<f64>,
-    actual_price_1h: Option<f64>,
-    actual_price_2h: Option<f64>,
-    was_correct_30m: Option<bool>,
-    was_correct_1h: Option<bool>,
-    was_correct_2h: Option<bool>,
-    profit_loss_30m: Option<f64>,
-    profit_loss_1h: Option<f64>,
-    profit_loss_2h: Option<f64>,
-}
-
-#[derive(Debug)]
-struct TestReport {
-    // Test configuration
-    symbol: String,
-    model: String,
-    test_period_from: String,
-    test_period_to: String,
-    config: Config,
-    
-    // Basic statistics
-    total_decisions: usize,
-    buy_decisions: usize,
-    sell_decisions: usize,
-    hold_decisions: usize,
-    
-    // Accuracy metrics
-    correct_predictions_30m: usize,
-    correct_predictions_1h: usize,
-    correct_predictions_2h: usize,
-    wrong_predictions_30m: usize,
-    wrong_predictions_1h: usize,
-    wrong_predictions_2h: usize,
-    
-    // Decision-specific performance
-    good_buy_decisions_30m: usize,
-    good_buy_decisions_1h: usize,
-    good_buy_decisions_2h: usize,
-    bad_buy_decisions_30m: usize,
-    bad_buy_decisions_1h: usize,
-    bad_buy_decisions_2h: usize,
-    good_sell_decisions_30m: usize,
-    good_sell_decisions_1h: usize,
-    good_sell_decisions_2h: usize,
-    bad_sell_decisions_30m: usize,
-    bad_sell_decisions_1h: usize,
-    bad_sell_decisions_2h: usize,
-    
-    // Fatal decisions (buy when price drops significantly)
-    fatal_buy_decisions_30m: usize,
-    fatal_buy_decisions_1h: usize,
-    fatal_buy_decisions_2h: usize,
-    fatal_sell_decisions_30m: usize,
-    fatal_sell_decisions_1h: usize,
-    fatal_sell_decisions_2h: usize,
-    
-    // Profitability
-    profitable_decisions_30m: usize,
-    profitable_decisions_1h: usize,
-    profitable_decisions_2h: usize,
-    losing_decisions_30m: usize,
-    losing_decisions_1h: usize,
-    losing_decisions_2h: usize,
-    
-    // Financial metrics
-    total_profit_loss_30m: f64,
-    total_profit_loss_1h: f64,
-    total_profit_loss_2h: f64,
-    average_profit_per_decision_30m: f64,
-    average_profit_per_decision_1h: f64,
-    average_profit_per_decision_2h: f64,
-    best_decision_profit_30m: f64,
-    best_decision_profit_1h: f64,
-    best_decision_profit_2h: f64,
-    worst_decision_loss_30m: f64,
-    worst_decision_loss_1h: f64,
-    worst_decision_loss_2h: f64,
-    
-    // Accuracy percentages
-    accuracy_30m: f64,
-    accuracy_1h: f64,
-    accuracy_2h: f64,
-    buy_accuracy_30m: f64,
-    buy_accuracy_1h: f64,
-    buy_accuracy_2h: f64,
-    sell_accuracy_30m: f64,
-    sell_accuracy_1h: f64,
-    sell_accuracy_2h: f64,
-    
-    // Portfolio simulation
-    initial_portfolio_value: f64,
-    final_portfolio_value_30m: f64,
-    final_portfolio_value_1h: f64,
-    final_portfolio_value_2h: f64,
-    portfolio_return_30m: f64,
-    portfolio_return_1h: f64,
-    portfolio_return_2h: f64,
-    
-    results: Vec<DecisionResult>,
-}
-
-#[derive(Debug)]
-struct PortfolioSimulation {
-    initial_value: f64,
-    current_value: f64,
-    current_position: String, // "cash", "crypto"
-    crypto_amount: f64,
-    cash_amount: f64,
-    transactions: Vec<Transaction>,
-}
-
-#[derive(Debug, Clone)]
-struct Transaction {
-    timestamp: i64,
-    action: String, // "buy", "sell"
-    price: f64,
-    amount: f64,
-    value: f64,
-    portfolio_value_after: f64,
-}
-
 async fn make_llm_request(config: &Config, system_message: &str, user_message: &str) -> color_eyre::Result<TradingDecision> {
     let client = Client::new();
    let schema = serde_json::json!({
@@ -255,12 +134,16 @@ async fn make_llm_request(config: &Config, system_message: &str, user_message: &
     "required": ["thinking", "reasoning", "action", "confidence" ],  
     "additionalProperties": false
 });
+    let system_content = match config.json_schema_mode {
+        JsonSchemaMode::None => format!("{}\n\n{}", system_message, schema_instruction(&schema)),
+        JsonSchemaMode::Strict | JsonSchemaMode::Loose => system_message.to_string(),
+    };
     let request = LLMRequest {
         model: config.openai_model.clone(),
         messages: vec![
             Message {
                 role: "system".to_string(),
-                content: system_message.to_string(),
+                content: system_content,
             },
             Message {
                 role: "user".to_string(),
@@ -268,14 +151,14 @@ async fn make_llm_request(config: &Config, system_message: &str, user_message: &
             },
         ],
         // temperature: 0.1,
-        response_format: ResponseFormat {
+        response_format: config.json_schema_mode.strict().map(|strict| ResponseFormat {
             format_type: "json_schema".to_string(),
             json_schema: JsonSchema {
                 name: "trading_decision".to_string(),
-                strict: true,
+                strict,
                 schema,
             },
-        },
+        }),
     };
 
     let response = client
@@ -298,13 +181,43 @@ async fn make_llm_request(config: &Config, system_message: &str, user_message: &
         .wrap_err("Failed to parse LLM response")?;
 
     let content = &llm_response.choices[0].message.content;
-    let decision: TradingDecision = serde_json::from_str(content)
+    let decision: TradingDecision = parse_response(content, config.json_schema_mode)
         .wrap_err("Failed to parse trading decision JSON")?;
     info!("DECISION: {}", serde_json::to_string_pretty(&decision).unwrap());
     Ok(decision)
 }
-#[instrument(ret)]
-fn generate_user_message(klines: &[botmarley::binance::data_collector::KlineData], index: usize) -> String {
+/// Prompt sections dropped first when over `max_context_chars`
+const DROPPABLE_SECTIONS: [&str; 4] = ["\nRaw OHLCV", "\nRecent MA", "\nRecent MACD", "\nRecent Price"];
+/// Instructions that always stay in the prompt
+const KEPT_SECTIONS: [&str; 1] = ["\nPlease analyze"];
+
+/// Indicator series of the loaded klines, computed once per run and read
+/// candle by candle for the prompts and dataset records
+struct RunIndicators {
+    history: KlinesOHLC,
+    signals: DirectionalSeries,
+    mass_index: MassIndexResult,
+    ribbon: Vec<Vec<Option<f64>>>,
+    session_range: Vec<Option<f64>>,
+}
+
+impl RunIndicators {
+    fn new(klines: &[botmarley::binance::data_collector::KlineData]) -> Self {
+        let history = KlinesOHLC::from(klines);
+        RunIndicators {
+            signals: DirectionalSeries::from_ohlc(&history),
+            mass_index: MassIndexResult::from_ohlc(&history),
+            ribbon: RIBBON_PERIODS.iter().map(|p| ema(&history.close, *p)).collect(),
+            session_range: session_volatility_series(&history.time, &history.high, &history.low, &history.close),
+            history,
+        }
+    }
+}
+
+#[instrument(ret, skip(indicators))]
+/// `recent_count` candles are listed in the recent price, MACD and MA sections,
+/// capped by the 21 candle indicator window.
+fn generate_user_message(klines: &[botmarley::binance::data_collector::KlineData], columns: &IndicatorColumns, indicators: &RunIndicators, index: usize, recent_count: usize, include_raw_ohlc: bool, last_decision: Option<&LastDecision>) -> String {
     if klines.is_empty() || index >= klines.len() {
         return "No kline data available".to_string();
     }
@@ -350,8 +263,8 @@ fn generate_user_message(klines: &[botmarley::binance::data_collector::KlineData
     // Add technical indicators if we have enough data
     if closes.len() >= 14 {
         let rsi = relative_strength_index(&closes, 14);
-        if let Some(current_rsi) = rsi.last().and_then(|r| *r) {
-            message.push_str(&format!("RSI (14): {:.2}\n", current_rsi));
+        if let Some(rsi_text) = format_rsi(&closes, &rsi, 14) {
+            message.push_str(&format!("{}\n", rsi_text));
         }
 
         let mfi = money_flow_index(&highs, &lows, &closes, &volumes, 14);
@@ -359,53 +272,36 @@ fn generate_user_message(klines: &[botmarley::binance::data_collector::KlineData
             message.push_str(&format!("MFI (14): {:.2}\n", current_mfi));
         }
     }
+    message.push_str(&format!("{}\n", format_volume_analysis(context_klines)));
 
-    // Calculate multiple SMA and EMA periods
-    if closes.len() >= 6 {
-        let sma_6 = simple_moving_average(&closes, 6);
-        if let Some(current_sma_6) = sma_6.last().and_then(|s| *s) {
-            message.push_str(&format!("SMA (6): ${:.2}\n", current_sma_6));
+    // SMA/EMA columns are evaluated once over the loaded history
+    for period in [6, 14, 20] {
+        if let Some(sma) = columns.value(&format!("sma_{period}"), index) {
+            message.push_str(&format!("SMA ({}): ${:.2}\n", period, sma));
         }
-
-        let ema_6 = exponential_moving_average(&closes, 6);
-        if let Some(current_ema_6) = ema_6.last().and_then(|e| *e) {
-            message.push_str(&format!("EMA (6): ${:.2}\n", current_ema_6));
-        }
-    }
-
-    if closes.len() >= 14 {
-        let sma_14 = simple_moving_average(&closes, 14);
-        if let Some(current_sma_14) = sma_14.last().and_then(|s| *s) {
-            message.push_str(&format!("SMA (14): ${:.2}\n", current_sma_14));
-        }
-
-        let ema_14 = exponential_moving_average(&closes, 14);
-        if let Some(current_ema_14) = ema_14.last().and_then(|e| *e) {
-            message.push_str(&format!("EMA (14): ${:.2}\n", current_ema_14));
+        if let Some(ema) = columns.value(&format!("ema_{period}"), index) {
+            message.push_str(&format!("EMA ({}): ${:.2}\n", period, ema));
         }
     }
 
     if closes.len() >= 20 {
-        let sma_20 = simple_moving_average(&closes, 20);
-        if let Some(current_sma_20) = sma_20.last().and_then(|s| *s) {
-            message.push_str(&format!("SMA (20): ${:.2}\n", current_sma_20));
+        // The ribbon needs EMA50, so it looks past the 20 candle context window
+        if index + 1 >= RIBBON_PERIODS[RIBBON_PERIODS.len() - 1] {
+            let score = ribbon_score_at(&indicators.history.close, &indicators.ribbon, index);
+            message.push_str(&format!("EMA Ribbon (9/21/50) Score: {:+} ({})\n", score, ribbon_label(score)));
         }
 
-        let ema_20 = exponential_moving_average(&closes, 20);
-        if let Some(current_ema_20) = ema_20.last().and_then(|e| *e) {
-            message.push_str(&format!("EMA (20): ${:.2}\n", current_ema_20));
-        }
-
-        let bb = bollinger_bands(&closes, 20, 2.0);
+        let bb = BollingerResult::from_close(&closes, 20, 2.0);
         if let (Some(upper), Some(middle), Some(lower)) = (
-            bb.0.last().and_then(|u| *u),
-            bb.1.last().and_then(|m| *m),
-            bb.2.last().and_then(|l| *l),
+            bb.upper.last().and_then(|u| *u),
+            bb.middle.last().and_then(|m| *m),
+            bb.lower.last().and_then(|l| *l),
         ) {
             message.push_str(&format!(
                 "Bollinger Bands: Upper: ${:.2}, Middle: ${:.2}, Lower: ${:.2}\n",
                 upper, middle, lower
             ));
+            message.push_str(&format!("{}\n", bb.summary()));
         }
     }
 
@@ -420,9 +316,37 @@ fn generate_user_message(klines: &[botmarley::binance::data_collector::KlineData
         }
     }
 
+    // Consensus over the full history up to this candle, never past it
+    let candle_time = DateTime::<Utc>::from_timestamp_millis(current.open_time).unwrap_or_default();
+    let session = session_at(candle_time);
+    message.push_str(&format!("Session: {} (UTC {})", session, candle_time.format("%H:%M")));
+    if let Some(range) = indicators.session_range.get(index).copied().flatten() {
+        message.push_str(&format!(", typical {} candle range: {:.2}%", session, range));
+    }
+    message.push('\n');
+    let series = &indicators.signals;
+    let signals = series.signals_at(&current.symbol.to_string(), index);
+    message.push_str(&format!("{}\n", agreement_summary(tally_signals(&signals))));
+    message.push_str(&format!("{}\n", series.regime.at(index).summary()));
+    message.push_str(&format!("{}\n", LinRegResult::from_close(series_until(&series.close, index), 50, 2.0).summary()));
+    message.push_str(&format!("{}\n", series.disparity.summary_at(index)));
+    message.push_str(&format!("{}\n", series.stc.summary_at(index)));
+    message.push_str(&format!("{}\n", series.gann_hilo.summary_at(index)));
+    message.push_str(&format!("{}\n", series.qstick.summary_at(index)));
+    message.push_str(&format!("{}\n", series.fisher.summary_at(index)));
+    message.push_str(&format!("{}\n", indicators.mass_index.summary_at(index)));
+    message.push_str(&format!("{}\n", series.rvi.summary_at(index)));
+    message.push_str(&format!("{}\n", series.bop.summary_at(index)));
+    message.push_str(&format!("{}\n", extension_summary(&extension_at(&series.ema_extension, index))));
+    let patterns = PatternsResult::at(&series.patterns, index);
+    if !patterns.patterns.is_empty() {
+        message.push_str(&format!("{}\n", patterns.summary()));
+    }
+
     // Add recent price context
-    message.push_str("\nRecent Price (last 5 candles):\n");
-    let recent_start = if index >= 4 { index - 4 } else { 0 };
+    let recent_start = index.saturating_sub(recent_count.max(1) - 1).max(start_idx);
+    let shown = index - recent_start + 1;
+    message.push_str(&format!("\nRecent Price (last {} candles):\n", shown));
     for i in recent_start..=index {
         let k = &klines[i];
         let change = if i > 0 {
@@ -444,7 +368,7 @@ fn generate_user_message(klines: &[botmarley::binance::data_collector::KlineData
 
     // Add recent MACD context
     if closes.len() >= 26 {
-        message.push_str("\nRecent MACD (last 5 candles):\n");
+        message.push_str(&format!("\nRecent MACD (last {} candles):\n", shown));
         let macd_values = MACD::new(&closes, 12, 26, 9);
         
         // Calculate the correct indices for recent MACD values
@@ -470,7 +394,7 @@ fn generate_user_message(klines: &[botmarley::binance::data_collector::KlineData
     }
 
     // Add recent MA context
-    message.push_str("\nRecent MA (last 5 candles):\n");
+    message.push_str(&format!("\nRecent MA (last {} candles):\n", shown));
     let sma_6 = if closes.len() >= 6 { simple_moving_average(&closes, 6) } else { vec![] };
     let sma_14 = if closes.len() >= 14 { simple_moving_average(&closes, 14) } else { vec![] };
     let sma_20 = if closes.len() >= 20 { simple_moving_average(&closes, 20) } else { vec![] };
@@ -516,28 +440,26 @@ fn generate_user_message(klines: &[botmarley::binance::data_collector::KlineData
         ));
     }
 
+    if let Some(last) = last_decision {
+        message.push_str(&format!("\n{}\n", last.summary(current.close, current.open_time)));
+    }
+
+    if include_raw_ohlc {
+        message.push_str(&format!("\nRaw OHLCV (last {} candles):\n", shown));
+        message.push_str(&format_raw_ohlc(&klines[recent_start..=index]));
+    }
+
     message.push_str("\nPlease analyze this data and provide a trading decision (buy, sell, or hold) with your confidence level and reasoning. Consider the technical indicators, recent price, and overall market context.");
     dbg!(&message);
     message
 }
 
-async fn filter_klines_by_date_range(
-    klines: Vec<botmarley::binance::data_collector::KlineData>,
-    start_date: &str,
-    end_date: &str,
-) -> color_eyre::Result<Vec<botmarley::binance::data_collector::KlineData>> {
-    let start_timestamp = date_string_to_timestamp(start_date)? * 1000; // Convert to milliseconds
-    let end_timestamp = date_string_to_timestamp(end_date)? * 1000; // Convert to milliseconds
-
-    let filtered: Vec<_> = klines
-        .into_iter()
-        .filter(|kline| {
-            kline.open_time >= start_timestamp && kline.open_time <= end_timestamp
-        })
-        .collect();
-
-    println!("Filtered {} klines for date range {} to {}", filtered.len(), start_date, end_date);
-    Ok(filtered)
+/// Structured values of the indicators summarized in the prompt, for dataset records
+fn dataset_features(klines: &[botmarley::binance::data_collector::KlineData], indicators: &RunIndicators, index: usize) -> Vec<IndicatorResult> {
+    let symbol = klines[index].symbol.to_string();
+    let mut features = indicators.signals.signals_at(&symbol, index);
+    features.push(indicators.mass_index.indicator_result_at(&symbol, index));
+    features
 }
 
 fn evaluate_decision(
@@ -571,256 +493,295 @@ fn evaluate_decision(
     }
 }
 
-fn simulate_portfolio(results: &[DecisionResult], initial_value: f64) -> (PortfolioSimulation, PortfolioSimulation, PortfolioSimulation) {
-    let mut sim_30m = PortfolioSimulation {
-        initial_value,
-        current_value: initial_value,
-        current_position: "cash".to_string(),
-        crypto_amount: 0.0,
-        cash_amount: initial_value,
-        transactions: Vec::new(),
-    };
-    
-    let mut sim_1h = sim_30m.clone();
-    let mut sim_2h = sim_30m.clone();
+fn print_report(report: &TestReport) {
+    print!("{}", report.summary());
+}
+
+struct Backtester {
+    config: Arc<Config>,
+    collector: DataCollector,
+    symbol: String,
+    system_message: String,
+}
+
+/// In-sample and out-of-sample reports of a walk-forward run.
+/// Degradation is `in_sample - out_of_sample`, so positive values mean the
+/// strategy did worse on the held-out range.
+struct WalkForwardReport {
+    in_sample: TestReport,
+    out_of_sample: TestReport,
+    accuracy_degradation_1h: f64,
+    return_degradation_1h: f64,
+}
+
+impl Backtester {
+    /// Run the backtest over `test_period_from..=test_period_to` (YYYY-MM-DD).
+    async fn run(&self, test_period_from: &str, test_period_to: &str) -> color_eyre::Result<BacktestRun> {
+        let start_ms = date_string_to_timestamp(test_period_from)? * 1000; // Convert to milliseconds
+        let end_ms = date_string_to_timestamp(test_period_to)? * 1000;
+        self.run_range(test_period_from, test_period_to, start_ms, end_ms).await
+    }
+
+    /// Run on an in-sample range, then report separately on a held-out
+    /// out-of-sample range. The ranges never share a kline.
+    async fn run_walk_forward(&self, in_sample: (&str, &str), out_sample: (&str, &str)) -> color_eyre::Result<WalkForwardReport> {
+        let (in_range, out_range) = sample_ranges(in_sample, out_sample)?;
+
+        println!("\n🧪 In-sample period: {} to {}", in_sample.0, in_sample.1);
+        let in_run = self.run_range(in_sample.0, in_sample.1, *in_range.start(), *in_range.end()).await?;
+        println!("\n🧪 Out-of-sample period: {} to {}", out_sample.0, out_sample.1);
+        let out_run = self.run_range(out_sample.0, out_sample.1, *out_range.start(), *out_range.end()).await?;
+
+        Ok(WalkForwardReport {
+            accuracy_degradation_1h: in_run.report.accuracy_1h - out_run.report.accuracy_1h,
+            return_degradation_1h: in_run.report.portfolio.portfolio_return_1h - out_run.report.portfolio.portfolio_return_1h,
+            in_sample: in_run.report,
+            out_of_sample: out_run.report,
+        })
+    }
+
+    async fn run_range(&self, test_period_from: &str, test_period_to: &str, start_ms: i64, end_ms: i64) -> color_eyre::Result<BacktestRun> {
+        let config = self.config.as_ref();
+        let system_message = self.system_message.as_str();
+        let symbol = self.symbol.as_str();
+
+        // Load klines for the date range
+        println!("\n📊 Loading klines data...");
+        let klines = self.collector.get_klines_for_symbol_range(symbol.to_string(), start_ms, end_ms).await?;
+        println!("Loaded {} klines for date range {} to {}", klines.len(), test_period_from, test_period_to);
+        let indicator_columns = IndicatorColumns::from_klines(&klines)?;
+        let indicators = RunIndicators::new(&klines);
     
-    for result in results {
-        // 30-minute simulation
-        if let Some(future_price_30m) = result.actual_price_30m {
-            simulate_decision(&mut sim_30m, result, future_price_30m, result.timestamp);
-        }
-        
-        // 1-hour simulation
-        if let Some(future_price_1h) = result.actual_price_1h {
-            simulate_decision(&mut sim_1h, result, future_price_1h, result.timestamp);
+        if klines.is_empty() {
+            return Err(color_eyre::eyre::eyre!(
+                "No klines found for the specified date range {} to {}",
+                test_period_from,
+                test_period_to
+            ));
         }
-        
-        // 2-hour simulation
-        if let Some(future_price_2h) = result.actual_price_2h {
-            simulate_decision(&mut sim_2h, result, future_price_2h, result.timestamp);
+    
+        println!("📈 Processing {} klines in date range", klines.len());
+    
+        let mut results = Vec::new();
+        let mut _total_requests = 0;
+        let dataset = DatasetRecorder::new(&config.dataset_dir);
+        let mut indicator_snapshots: Vec<(Vec<IndicatorResult>, f64)> = Vec::new();
+        let mut cache = DecisionCache::load(&config.decision_cache_path)?;
+        if !cache.is_empty() {
+            println!("🗄️  Loaded {} cached decisions from {}", cache.len(), config.decision_cache_path);
         }
-    }
     
-    (sim_30m, sim_1h, sim_2h)
-}
+        let candles_30m = interval_candles(KLINE_INTERVAL, std::time::Duration::from_secs(30 * 60));
+        let candles_1h = interval_candles(KLINE_INTERVAL, std::time::Duration::from_secs(60 * 60));
+        let candles_2h = interval_candles(KLINE_INTERVAL, std::time::Duration::from_secs(2 * 60 * 60));
+        let scored_after_ms = candles_to_duration(KLINE_INTERVAL, candles_1h).as_millis() as i64;
 
-fn simulate_decision(portfolio: &mut PortfolioSimulation, result: &DecisionResult, future_price: f64, timestamp: i64) {
-    match result.decision.action.as_str() {
-        "buy" => {
-            if portfolio.current_position == "cash" && portfolio.cash_amount > 0.0 {
-                // Buy crypto with all cash
-                let crypto_bought = portfolio.cash_amount / result.price;
-                portfolio.crypto_amount = crypto_bought;
-                portfolio.cash_amount = 0.0;
-                portfolio.current_position = "crypto".to_string();
+        // Process each kline (skip the last 2h to ensure we have future data for evaluation)
+        let process_count = klines.len().saturating_sub(candles_2h);
+    
+        for i in 20..process_count { // Start from 20 to have enough historical data for indicators
+            _total_requests += 1;
+            println!("\n🔄 Processing kline {}/{} ({})", 
+                     i + 1, process_count, 
+                     DateTime::<Utc>::from_timestamp_millis(klines[i].open_time)
+                         .unwrap_or_default()
+                         .format("%Y-%m-%d %H:%M:%S UTC"));
+        
+            let last_decision = results.last().map(|r: &DecisionResult| LastDecision {
+                action: r.decision.action.clone(),
+                price_at_decision: r.price,
+                timestamp: r.timestamp,
+            });
+            let user_message = fit_context(
+                &generate_user_message(&klines, &indicator_columns, &indicators, i, config.context_candle_count, config.include_raw_ohlc, last_decision.as_ref()),
+                config.max_context_chars,
+                &DROPPABLE_SECTIONS,
+                &KEPT_SECTIONS,
+            );
+        
+            // Make LLM request, unless the same prompt was answered in an earlier run
+            let misses_before = cache.misses;
+            let key = DecisionCache::key(&config.openai_model, system_message, &user_message);
+            match cache.get_or_fetch(&key, || make_llm_request(config, system_message, &user_message)).await {
+                Ok(mut decision) => {
+                    println!("🤖 Decision: {} (confidence: {:.0}%)", 
+                             decision.action.to_uppercase(), decision.confidence * 100.0);
+                    if config.calibrate_confidence {
+                        // only decisions whose 1h outcome is already known at this candle
+                        let history: Vec<(f64, bool)> = results
+                            .iter()
+                            .filter(|r: &&DecisionResult| klines[i].open_time - r.timestamp >= scored_after_ms)
+                            .filter_map(|r| Some((r.decision.confidence, r.was_correct_1h?)))
+                            .collect();
+                        let calibrated = calibrate_confidence(decision.confidence, &history);
+                        println!("🎯 Calibrated confidence: {:.0}% ({} scored decisions)", calibrated * 100.0, history.len());
+                        decision.calibrated_confidence = Some(calibrated);
+                    }
+                    println!("💭 Reasoning: {}", decision.reasoning);
+                    println!("{}",serde_json::to_string_pretty(&decision).unwrap());
+                    println!("----------------------");
+                    let current_price = klines[i].close;
                 
-                // Calculate portfolio value at future price
-                portfolio.current_value = portfolio.crypto_amount * future_price;
+                    // Get future prices for evaluation
+                    let future_30m_idx = i + candles_30m;
+                    let future_1h_idx = i + candles_1h;
+                    let future_2h_idx = i + candles_2h;
                 
-                portfolio.transactions.push(Transaction {
-                    timestamp,
-                    action: "buy".to_string(),
-                    price: result.price,
-                    amount: crypto_bought,
-                    value: portfolio.cash_amount,
-                    portfolio_value_after: portfolio.current_value,
-                });
-            }
-        }
-        "sell" => {
-            if portfolio.current_position == "crypto" && portfolio.crypto_amount > 0.0 {
-                // Sell all crypto for cash
-                portfolio.cash_amount = portfolio.crypto_amount * result.price;
-                portfolio.crypto_amount = 0.0;
-                portfolio.current_position = "cash".to_string();
-                portfolio.current_value = portfolio.cash_amount;
+                    let mut result = DecisionResult {
+                        timestamp: klines[i].open_time,
+                        price: current_price,
+                        decision,
+                        actual_price_30m: None,
+                        actual_price_1h: None,
+                        actual_price_2h: None,
+                        was_correct_30m: None,
+                        was_correct_1h: None,
+                        was_correct_2h: None,
+                        profit_loss_30m: None,
+                        profit_loss_1h: None,
+                        profit_loss_2h: None,
+                    };
+                
+                    // Evaluate 30-minute prediction
+                    if future_30m_idx < klines.len() {
+                        let best_price = match result.decision.action.as_str() {
+                            "buy" => {
+                                // For buy decisions, find the maximum high price in the 30m window
+                                klines.iter()
+                                    .skip(i + 1)
+                                    .take(candles_30m)
+                                    .map(|k| k.high)
+                                    .fold(f64::NEG_INFINITY, f64::max)
+                            }
+                            "sell" => {
+                                // For sell decisions, find the minimum low price in the 30m window
+                                klines.iter()
+                                    .skip(i + 1)
+                                    .take(candles_30m)
+                                    .map(|k| k.low)
+                                    .fold(f64::INFINITY, f64::min)
+                            }
+                            _ => klines[future_30m_idx].close, // For hold, use closing price
+                        };
+                        result.actual_price_30m = Some(best_price);
+                        let (correct, pl, _fatal) = evaluate_decision(&result.decision, current_price, best_price);
+                        result.was_correct_30m = Some(correct);
+                        result.profit_loss_30m = Some(pl);
+                    }
+                
+                    // Evaluate 1-hour prediction
+                    if future_1h_idx < klines.len() {
+                        let best_price = match result.decision.action.as_str() {
+                            "buy" => {
+                                // For buy decisions, find the maximum high price in the 1h window
+                                klines.iter()
+                                    .skip(i + 1)
+                                    .take(candles_1h)
+                                    .map(|k| k.high)
+                                    .fold(f64::NEG_INFINITY, f64::max)
+                            }
+                            "sell" => {
+                                // For sell decisions, find the minimum low price in the 1h window
+                                klines.iter()
+                                    .skip(i + 1)
+                                    .take(candles_1h)
+                                    .map(|k| k.low)
+                                    .fold(f64::INFINITY, f64::min)
+                            }
+                            _ => klines[future_1h_idx].close, // For hold, use closing price
+                        };
+                        result.actual_price_1h = Some(best_price);
+                        let (correct, pl, _fatal) = evaluate_decision(&result.decision, current_price, best_price);
+                        result.was_correct_1h = Some(correct);
+                        result.profit_loss_1h = Some(pl);
+                    }
+                
+                    // Evaluate 2-hour prediction
+                    if future_2h_idx < klines.len() {
+                        let best_price = match result.decision.action.as_str() {
+                            "buy" => {
+                                // For buy decisions, find the maximum high price in the 2h window
+                                klines.iter()
+                                    .skip(i + 1)
+                                    .take(candles_2h)
+                                    .map(|k| k.high)
+                                    .fold(f64::NEG_INFINITY, f64::max)
+                            }
+                            "sell" => {
+                                // For sell decisions, find the minimum low price in the 2h window
+                                klines.iter()
+                                    .skip(i + 1)
+                                    .take(candles_2h)
+                                    .map(|k| k.low)
+                                    .fold(f64::INFINITY, f64::min)
+                            }
+                            _ => klines[future_2h_idx].close, // For hold, use closing price
+                        };
+                        result.actual_price_2h = Some(best_price);
+                        let (correct, pl, _fatal) = evaluate_decision(&result.decision, current_price, best_price);
+                        result.was_correct_2h = Some(correct);
+                        result.profit_loss_2h = Some(pl);
+                    }
+
+                    let features = dataset_features(&klines, &indicators, i);
+                    if future_1h_idx < klines.len() {
+                        let change_1h = (klines[future_1h_idx].close - current_price) / current_price * 100.0;
+                        indicator_snapshots.push((features.clone(), change_1h));
+                    }
+
+                    if dataset.is_enabled() {
+                        let outcome = serde_json::json!({
+                            "price": result.price,
+                            "actual_price_30m": result.actual_price_30m,
+                            "actual_price_1h": result.actual_price_1h,
+                            "actual_price_2h": result.actual_price_2h,
+                            "was_correct_30m": result.was_correct_30m,
+                            "was_correct_1h": result.was_correct_1h,
+                            "was_correct_2h": result.was_correct_2h,
+                            "profit_loss_30m": result.profit_loss_30m,
+                            "profit_loss_1h": result.profit_loss_1h,
+                            "profit_loss_2h": result.profit_loss_2h,
+                        });
+                        let record = DatasetRecord::new(symbol, result.timestamp, &user_message, features, &result.decision, &outcome)?;
+                        dataset.record(&record)?;
+                    }
                 
-                portfolio.transactions.push(Transaction {
-                    timestamp,
-                    action: "sell".to_string(),
-                    price: result.price,
-                    amount: portfolio.crypto_amount,
-                    value: portfolio.cash_amount,
-                    portfolio_value_after: portfolio.current_value,
-                });
+                    results.push(result);
+                }
+                Err(e) => {
+                    println!("❌ Failed to get LLM decision: {}", e);
+                    continue;
+                }
             }
-        }
-        "hold" => {
-            // Update portfolio value based on current position
-            if portfolio.current_position == "crypto" {
-                portfolio.current_value = portfolio.crypto_amount * future_price;
-            } else {
-                portfolio.current_value = portfolio.cash_amount;
+        
+            // Add a small delay to avoid overwhelming the API
+            if cache.misses > misses_before {
+                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
             }
         }
-        _ => {}
-    }
-}
-
-impl Clone for PortfolioSimulation {
-    fn clone(&self) -> Self {
-        PortfolioSimulation {
-            initial_value: self.initial_value,
-            current_value: self.current_value,
-            current_position: self.current_position.clone(),
-            crypto_amount: self.crypto_amount,
-            cash_amount: self.cash_amount,
-            transactions: self.transactions.clone(),
+        cache.flush()?;
+        if !config.decision_cache_path.is_empty() {
+            println!("🗄️  Decision cache: {} hits, {} LLM requests", cache.hits, cache.misses);
         }
+    
+        let indicator_performance = indicator_performance(indicator_snapshots.iter().map(|(features, change)| (features.as_slice(), *change)));
+        Ok(BacktestRun::new(symbol, test_period_from, test_period_to, config, results, indicator_performance))
     }
 }
 
-fn print_report(report: &TestReport) {
-    println!("\n{}", "=".repeat(80));
-    println!("                           TRADING DECISION ANALYSIS REPORT");
-    println!("{}", "=".repeat(80));
-    
-    println!("\n📊 SUMMARY STATISTICS:");
-    println!("├─ Total Decisions: {}", report.total_decisions);
-    println!("├─ Buy Decisions: {} ({:.1}%)", report.buy_decisions,
-             (report.buy_decisions as f64 / report.total_decisions as f64) * 100.0);
-    println!("├─ Sell Decisions: {} ({:.1}%)", report.sell_decisions,
-             (report.sell_decisions as f64 / report.total_decisions as f64) * 100.0);
-    println!("└─ Hold Decisions: {} ({:.1}%)", report.hold_decisions,
-             (report.hold_decisions as f64 / report.total_decisions as f64) * 100.0);
-
-    println!("\n🎯 OVERALL ACCURACY METRICS:");
-    println!("├─ 30-minute: {:.1}% (✅{} / ❌{})", report.accuracy_30m,
-             report.correct_predictions_30m, report.wrong_predictions_30m);
-    println!("├─ 1-hour: {:.1}% (✅{} / ❌{})", report.accuracy_1h,
-             report.correct_predictions_1h, report.wrong_predictions_1h);
-    println!("└─ 2-hour: {:.1}% (✅{} / ❌{})", report.accuracy_2h,
-             report.correct_predictions_2h, report.wrong_predictions_2h);
-
-    println!("\n📈 BUY DECISION PERFORMANCE:");
-    println!("├─ 30-minute: {:.1}% (✅{} / ❌{})", report.buy_accuracy_30m,
-             report.good_buy_decisions_30m, report.bad_buy_decisions_30m);
-    println!("├─ 1-hour: {:.1}% (✅{} / ❌{})", report.buy_accuracy_1h,
-             report.good_buy_decisions_1h, report.bad_buy_decisions_1h);
-    println!("└─ 2-hour: {:.1}% (✅{} / ❌{})", report.buy_accuracy_2h,
-             report.good_buy_decisions_2h, report.bad_buy_decisions_2h);
-
-    println!("\n📉 SELL DECISION PERFORMANCE:");
-    println!("├─ 30-minute: {:.1}% (✅{} / ❌{})", report.sell_accuracy_30m,
-             report.good_sell_decisions_30m, report.bad_sell_decisions_30m);
-    println!("├─ 1-hour: {:.1}% (✅{} / ❌{})", report.sell_accuracy_1h,
-             report.good_sell_decisions_1h, report.bad_sell_decisions_1h);
-    println!("└─ 2-hour: {:.1}% (✅{} / ❌{})", report.sell_accuracy_2h,
-             report.good_sell_decisions_2h, report.bad_sell_decisions_2h);
-
-    println!("\n💰 PROFIT/LOSS ANALYSIS:");
-    println!("├─ 30-minute: Total: {:+.2}% | Avg: {:+.2}% | Best: {:+.2}% | Worst: {:+.2}%",
-             report.total_profit_loss_30m, report.average_profit_per_decision_30m,
-             report.best_decision_profit_30m, report.worst_decision_loss_30m);
-    println!("├─ 1-hour: Total: {:+.2}% | Avg: {:+.2}% | Best: {:+.2}% | Worst: {:+.2}%",
-             report.total_profit_loss_1h, report.average_profit_per_decision_1h,
-             report.best_decision_profit_1h, report.worst_decision_loss_1h);
-    println!("└─ 2-hour: Total: {:+.2}% | Avg: {:+.2}% | Best: {:+.2}% | Worst: {:+.2}%",
-             report.total_profit_loss_2h, report.average_profit_per_decision_2h,
-             report.best_decision_profit_2h, report.worst_decision_loss_2h);
-
-    println!("\n💸 PROFITABILITY BREAKDOWN:");
-    println!("├─ 30-minute: 💚{} profitable | 💔{} losing",
-             report.profitable_decisions_30m, report.losing_decisions_30m);
-    println!("├─ 1-hour: 💚{} profitable | 💔{} losing",
-             report.profitable_decisions_1h, report.losing_decisions_1h);
-    println!("└─ 2-hour: 💚{} profitable | 💔{} losing",
-             report.profitable_decisions_2h, report.losing_decisions_2h);
-
-    println!("\n⚠️ FATAL DECISIONS (>3% Loss):");
-    println!("├─ Fatal Buy Decisions: 30m: {} | 1h: {} | 2h: {}",
-             report.fatal_buy_decisions_30m, report.fatal_buy_decisions_1h, report.fatal_buy_decisions_2h);
-    println!("└─ Fatal Sell Decisions: 30m: {} | 1h: {} | 2h: {}",
-             report.fatal_sell_decisions_30m, report.fatal_sell_decisions_1h, report.fatal_sell_decisions_2h);
-
-    println!("\n💰 PORTFOLIO SIMULATION (${:.2} initial):", report.initial_portfolio_value);
-    println!("├─ 30-minute: ${:.2} ({:+.2}%)",
-             report.final_portfolio_value_30m, report.portfolio_return_30m);
-    println!("├─ 1-hour: ${:.2} ({:+.2}%)",
-             report.final_portfolio_value_1h, report.portfolio_return_1h);
-    println!("└─ 2-hour: ${:.2} ({:+.2}%)",
-             report.final_portfolio_value_2h, report.portfolio_return_2h);
-
-    println!("\n📈 DETAILED RESULTS TABLE:");
-    println!("{:<20} {:<8} {:<8} {:<6} {:<10} {:<10} {:<10} {:<8} {:<8} {:<8}",
-             "Timestamp", "Price", "Action", "Conf", "30m P&L", "1h P&L", "2h P&L", "30m ✓", "1h ✓", "2h ✓");
-    println!("{}", "-".repeat(100));
-
-    for result in &report.results {
-        let timestamp = DateTime::<Utc>::from_timestamp_millis(result.timestamp)
-            .unwrap_or_default()
-            .format("%m-%d %H:%M");
-        
-        println!("{:<20} ${:<7.2} {:<8} {:<5.0}% {:>+9.2}% {:>+9.2}% {:>+9.2}% {:<8} {:<8} {:<8}",
-                 timestamp,
-                 result.price,
-                 result.decision.action.to_uppercase(),
-                 result.decision.confidence * 100.0,
-                 result.profit_loss_30m.unwrap_or(0.0),
-                 result.profit_loss_1h.unwrap_or(0.0),
-                 result.profit_loss_2h.unwrap_or(0.0),
-                 if result.was_correct_30m.unwrap_or(false) { "✅" } else { "❌" },
-                 if result.was_correct_1h.unwrap_or(false) { "✅" } else { "❌" },
-                 if result.was_correct_2h.unwrap_or(false) { "✅" } else { "❌" },
-        );
-    }
+fn print_walk_forward_report(walk_forward: &WalkForwardReport) {
+    println!("\n📘 IN-SAMPLE");
+    print_report(&walk_forward.in_sample);
+    println!("\n📙 OUT-OF-SAMPLE");
+    print_report(&walk_forward.out_of_sample);
 
     println!("\n{}", "=".repeat(80));
-    
-    // Enhanced performance insights
-    println!("\n🔍 PERFORMANCE INSIGHTS:");
-    
-    // Overall performance
-    if report.accuracy_30m > 60.0 {
-        println!("✅ Strong short-term prediction accuracy (30m: {:.1}%)", report.accuracy_30m);
-    } else {
-        println!("⚠️  Room for improvement in short-term predictions (30m: {:.1}%)", report.accuracy_30m);
-    }
-    
-    // Profitability insights
-    if report.total_profit_loss_2h > 0.0 {
-        println!("✅ Positive overall returns over 2-hour periods ({:+.2}%)", report.total_profit_loss_2h);
-    } else {
-        println!("⚠️  Negative overall returns - strategy needs refinement ({:+.2}%)", report.total_profit_loss_2h);
-    }
-    
-    // Best timeframe
-    let best_timeframe = if report.accuracy_2h >= report.accuracy_1h && report.accuracy_2h >= report.accuracy_30m {
-        "2-hour"
-    } else if report.accuracy_1h >= report.accuracy_30m {
-        "1-hour"
-    } else {
-        "30-minute"
-    };
-    println!("📊 Best performing timeframe: {}", best_timeframe);
-    
-    // Action-specific insights
-    if report.buy_decisions > 0 {
-        let best_buy_timeframe = if report.buy_accuracy_2h >= report.buy_accuracy_1h && report.buy_accuracy_2h >= report.buy_accuracy_30m {
-            ("2-hour", report.buy_accuracy_2h)
-        } else if report.buy_accuracy_1h >= report.buy_accuracy_30m {
-            ("1-hour", report.buy_accuracy_1h)
-        } else {
-            ("30-minute", report.buy_accuracy_30m)
-        };
-        println!("🟢 Buy decisions perform best at {} timeframe ({:.1}% accuracy)", best_buy_timeframe.0, best_buy_timeframe.1);
-    }
-    
-    if report.sell_decisions > 0 {
-        let best_sell_timeframe = if report.sell_accuracy_2h >= report.sell_accuracy_1h && report.sell_accuracy_2h >= report.sell_accuracy_30m {
-            ("2-hour", report.sell_accuracy_2h)
-        } else if report.sell_accuracy_1h >= report.sell_accuracy_30m {
-            ("1-hour", report.sell_accuracy_1h)
-        } else {
-            ("30-minute", report.sell_accuracy_30m)
-        };
-        println!("🔴 Sell decisions perform best at {} timeframe ({:.1}% accuracy)", best_sell_timeframe.0, best_sell_timeframe.1);
-    }
-    
-    // Risk assessment
-    let max_loss = report.worst_decision_loss_30m.min(report.worst_decision_loss_1h).min(report.worst_decision_loss_2h);
-    let max_gain = report.best_decision_profit_30m.max(report.best_decision_profit_1h).max(report.best_decision_profit_2h);
-    println!("⚖️  Risk/Reward: Max gain {:+.2}% | Max loss {:+.2}%", max_gain, max_loss);
+    println!("                           WALK-FORWARD DEGRADATION");
+    println!("{}", "=".repeat(80));
+    println!("├─ 1h Accuracy: {:.1}% in-sample vs {:.1}% out-of-sample ({:+.1} pts degradation)",
+             walk_forward.in_sample.accuracy_1h, walk_forward.out_of_sample.accuracy_1h, walk_forward.accuracy_degradation_1h);
+    println!("└─ 1h Portfolio Return: {:+.2}% in-sample vs {:+.2}% out-of-sample ({:+.2} pts degradation)",
+             walk_forward.in_sample.portfolio.portfolio_return_1h, walk_forward.out_of_sample.portfolio.portfolio_return_1h, walk_forward.return_degradation_1h);
 }
 
 #[tokio::main]
@@ -836,379 +797,64 @@ async fn main() -> color_eyre::Result<()> {
         .with_suggestion(|| "Make sure .env file exists and is readable")?;
     
     let config = (&*botmarley::config::CONFIG).clone();
+    if config.startup_llm_check {
+        check_llm_endpoint(&config).await?;
+    }
 
     // Create data collector
     let collector = DataCollector::new(config.clone())?;
 
-    let system_message = get_system_message();
-    let symbol = "BTCUSDC";
+    let backtester = Backtester {
+        system_message: get_system_message(config.strategy_profile),
+        config,
+        collector,
+        symbol: "BTCUSDC".to_string(),
+    };
     // let test_period_from = "2024-10-04";
     let test_period_from = "2024-10-01";
 
     let test_period_to = "2024-10-02";
+    // Held-out range used with --walk-forward
+    let out_of_sample_from = "2024-10-02";
+    let out_of_sample_to = "2024-10-03";
     
     println!("🚀 Starting BTC Trading Decision Analysis");
-    println!("Symbol: {}", symbol);
-    println!("Period: {} to {}", test_period_from, test_period_to);
-    println!("System Message: {}", system_message);
-    
-    // Get all klines for the symbol
-    println!("\n📊 Loading klines data...");
-    let all_klines = collector.get_klines_for_symbol(symbol.to_string()).await?;
-    println!("Loaded {} total klines", all_klines.len());
-    
-    // Filter klines by date range
-    let klines = filter_klines_by_date_range(all_klines, test_period_from, test_period_to).await?;
-    
-    if klines.is_empty() {
-        println!("❌ No klines found for the specified date range");
-        return Ok(());
-    }
-    
-    println!("📈 Processing {} klines in date range", klines.len());
-    
-    let mut results = Vec::new();
-    let mut _total_requests = 0;
-    
-    // Process each kline (skip the last 24 to ensure we have future data for evaluation)
-    let process_count = if klines.len() > 24 { klines.len() - 24 } else { 0 };
-    
-    for i in 20..process_count { // Start from 20 to have enough historical data for indicators
-        _total_requests += 1;
-        println!("\n🔄 Processing kline {}/{} ({})", 
-                 i + 1, process_count, 
-                 DateTime::<Utc>::from_timestamp_millis(klines[i].open_time)
-                     .unwrap_or_default()
-                     .format("%Y-%m-%d %H:%M:%S UTC"));
-        
-        let user_message = generate_user_message(&klines, i);
-        
-        // Make LLM request
-        match make_llm_request(&config, &system_message, &user_message).await {
-            Ok(decision) => {
-                println!("🤖 Decision: {} (confidence: {:.0}%)", 
-                         decision.action.to_uppercase(), decision.confidence * 100.0);
-                println!("💭 Reasoning: {}", decision.reasoning);
-                println!("{}",serde_json::to_string_pretty(&decision).unwrap());
-                println!("----------------------");
-                let current_price = klines[i].close;
-                
-                // Get future prices for evaluation
-                let future_30m_idx = i + 6; // 30 minutes = 6 * 5-minute candles
-                let future_1h_idx = i + 12; // 1 hour = 12 * 5-minute candles
-                let future_2h_idx = i + 24; // 2 hours = 24 * 5-minute candles
-                
-                let mut result = DecisionResult {
-                    timestamp: klines[i].open_time,
-                    price: current_price,
-                    decision,
-                    actual_price_30m: None,
-                    actual_price_1h: None,
-                    actual_price_2h: None,
-                    was_correct_30m: None,
-                    was_correct_1h: None,
-                    was_correct_2h: None,
-                    profit_loss_30m: None,
-                    profit_loss_1h: None,
-                    profit_loss_2h: None,
-                };
-                
-                // Evaluate 30-minute prediction
-                if future_30m_idx < klines.len() {
-                    let best_price = match result.decision.action.as_str() {
-                        "buy" => {
-                            // For buy decisions, find the maximum high price in the 30m window
-                            klines.iter()
-                                .skip(i + 1)
-                                .take(6) // 30 minutes = 6 * 5-minute candles
-                                .map(|k| k.high)
-                                .fold(f64::NEG_INFINITY, f64::max)
-                        }
-                        "sell" => {
-                            // For sell decisions, find the minimum low price in the 30m window
-                            klines.iter()
-                                .skip(i + 1)
-                                .take(6)
-                                .map(|k| k.low)
-                                .fold(f64::INFINITY, f64::min)
-                        }
-                        _ => klines[future_30m_idx].close, // For hold, use closing price
-                    };
-                    result.actual_price_30m = Some(best_price);
-                    let (correct, pl, _fatal) = evaluate_decision(&result.decision, current_price, best_price);
-                    result.was_correct_30m = Some(correct);
-                    result.profit_loss_30m = Some(pl);
-                }
-                
-                // Evaluate 1-hour prediction
-                if future_1h_idx < klines.len() {
-                    let best_price = match result.decision.action.as_str() {
-                        "buy" => {
-                            // For buy decisions, find the maximum high price in the 1h window
-                            klines.iter()
-                                .skip(i + 1)
-                                .take(12) // 1 hour = 12 * 5-minute candles
-                                .map(|k| k.high)
-                                .fold(f64::NEG_INFINITY, f64::max)
-                        }
-                        "sell" => {
-                            // For sell decisions, find the minimum low price in the 1h window
-                            klines.iter()
-                                .skip(i + 1)
-                                .take(12)
-                                .map(|k| k.low)
-                                .fold(f64::INFINITY, f64::min)
-                        }
-                        _ => klines[future_1h_idx].close, // For hold, use closing price
-                    };
-                    result.actual_price_1h = Some(best_price);
-                    let (correct, pl, _fatal) = evaluate_decision(&result.decision, current_price, best_price);
-                    result.was_correct_1h = Some(correct);
-                    result.profit_loss_1h = Some(pl);
-                }
-                
-                // Evaluate 2-hour prediction
-                if future_2h_idx < klines.len() {
-                    let best_price = match result.decision.action.as_str() {
-                        "buy" => {
-                            // For buy decisions, find the maximum high price in the 2h window
-                            klines.iter()
-                                .skip(i + 1)
-                                .take(24) // 2 hours = 24 * 5-minute candles
-                                .map(|k| k.high)
-                                .fold(f64::NEG_INFINITY, f64::max)
-                        }
-                        "sell" => {
-                            // For sell decisions, find the minimum low price in the 2h window
-                            klines.iter()
-                                .skip(i + 1)
-                                .take(24)
-                                .map(|k| k.low)
-                                .fold(f64::INFINITY, f64::min)
-                        }
-                        _ => klines[future_2h_idx].close, // For hold, use closing price
-                    };
-                    result.actual_price_2h = Some(best_price);
-                    let (correct, pl, _fatal) = evaluate_decision(&result.decision, current_price, best_price);
-                    result.was_correct_2h = Some(correct);
-                    result.profit_loss_2h = Some(pl);
-                }
-                
-                results.push(result);
-            }
-            Err(e) => {
-                println!("❌ Failed to get LLM decision: {}", e);
-                continue;
-            }
-        }
-        
-        // Add a small delay to avoid overwhelming the API
-        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-    }
-    
-    // Run portfolio simulation
-    let initial_portfolio = 1000.0;
-    let (sim_30m, sim_1h, sim_2h) = simulate_portfolio(&results, initial_portfolio);
-    
-    // Generate comprehensive report statistics
-    let mut buy_decisions = 0;
-    let mut sell_decisions = 0;
-    let mut hold_decisions = 0;
-    let mut correct_30m = 0;
-    let mut correct_1h = 0;
-    let mut correct_2h = 0;
-    let mut wrong_30m = 0;
-    let mut wrong_1h = 0;
-    let mut wrong_2h = 0;
-    let mut good_buy_30m = 0;
-    let mut good_buy_1h = 0;
-    let mut good_buy_2h = 0;
-    let mut bad_buy_30m = 0;
-    let mut bad_buy_1h = 0;
-    let mut bad_buy_2h = 0;
-    let mut good_sell_30m = 0;
-    let mut good_sell_1h = 0;
-    let mut good_sell_2h = 0;
-    let mut bad_sell_30m = 0;
-    let mut bad_sell_1h = 0;
-    let mut bad_sell_2h = 0;
-    let mut fatal_buy_30m = 0;
-    let mut fatal_buy_1h = 0;
-    let mut fatal_buy_2h = 0;
-    let mut fatal_sell_30m = 0;
-    let mut fatal_sell_1h = 0;
-    let mut fatal_sell_2h = 0;
-    let mut profitable_30m = 0;
-    let mut profitable_1h = 0;
-    let mut profitable_2h = 0;
-    let mut losing_30m = 0;
-    let mut losing_1h = 0;
-    let mut losing_2h = 0;
-    let mut total_pl_30m = 0.0;
-    let mut total_pl_1h = 0.0;
-    let mut total_pl_2h = 0.0;
-    let mut best_profit_30m = f64::NEG_INFINITY;
-    let mut best_profit_1h = f64::NEG_INFINITY;
-    let mut best_profit_2h = f64::NEG_INFINITY;
-    let mut worst_loss_30m = f64::INFINITY;
-    let mut worst_loss_1h = f64::INFINITY;
-    let mut worst_loss_2h = f64::INFINITY;
-    
-    for result in &results {
-        match result.decision.action.as_str() {
-            "buy" => {
-                buy_decisions += 1;
-                if result.was_correct_30m.unwrap_or(false) { good_buy_30m += 1; } else { bad_buy_30m += 1; }
-                if result.was_correct_1h.unwrap_or(false) { good_buy_1h += 1; } else { bad_buy_1h += 1; }
-                if result.was_correct_2h.unwrap_or(false) { good_buy_2h += 1; } else { bad_buy_2h += 1; }
-                
-                // Check for fatal buy decisions
-                if let Some(pl) = result.profit_loss_30m {
-                    if pl < -3.0 { fatal_buy_30m += 1; }
-                }
-                if let Some(pl) = result.profit_loss_1h {
-                    if pl < -3.0 { fatal_buy_1h += 1; }
-                }
-                if let Some(pl) = result.profit_loss_2h {
-                    if pl < -3.0 { fatal_buy_2h += 1; }
-                }
-            },
-            "sell" => {
-                sell_decisions += 1;
-                if result.was_correct_30m.unwrap_or(false) { good_sell_30m += 1; } else { bad_sell_30m += 1; }
-                if result.was_correct_1h.unwrap_or(false) { good_sell_1h += 1; } else { bad_sell_1h += 1; }
-                if result.was_correct_2h.unwrap_or(false) { good_sell_2h += 1; } else { bad_sell_2h += 1; }
-                
-                // Check for fatal sell decisions
-                if let Some(pl) = result.profit_loss_30m {
-                    if pl < -3.0 { fatal_sell_30m += 1; }
-                }
-                if let Some(pl) = result.profit_loss_1h {
-                    if pl < -3.0 { fatal_sell_1h += 1; }
-                }
-                if let Some(pl) = result.profit_loss_2h {
-                    if pl < -3.0 { fatal_sell_2h += 1; }
-                }
-            },
-            "hold" => hold_decisions += 1,
-            _ => {}
-        }
-        
-        // Overall accuracy
-        if result.was_correct_30m.unwrap_or(false) { correct_30m += 1; } else { wrong_30m += 1; }
-        if result.was_correct_1h.unwrap_or(false) { correct_1h += 1; } else { wrong_1h += 1; }
-        if result.was_correct_2h.unwrap_or(false) { correct_2h += 1; } else { wrong_2h += 1; }
-        
-        // Profit/Loss tracking
-        if let Some(pl) = result.profit_loss_30m {
-            total_pl_30m += pl;
-            if pl > 0.0 { profitable_30m += 1; } else if pl < 0.0 { losing_30m += 1; }
-            if pl > best_profit_30m { best_profit_30m = pl; }
-            if pl < worst_loss_30m { worst_loss_30m = pl; }
-        }
-        if let Some(pl) = result.profit_loss_1h {
-            total_pl_1h += pl;
-            if pl > 0.0 { profitable_1h += 1; } else if pl < 0.0 { losing_1h += 1; }
-            if pl > best_profit_1h { best_profit_1h = pl; }
-            if pl < worst_loss_1h { worst_loss_1h = pl; }
+    println!("Symbol: {}", backtester.symbol);
+    println!("System Message: {}", backtester.system_message);
+
+    if std::env::args().any(|arg| arg == "--walk-forward") {
+        let walk_forward = backtester
+            .run_walk_forward((test_period_from, test_period_to), (out_of_sample_from, out_of_sample_to))
+            .await?;
+        print_walk_forward_report(&walk_forward);
+    } else {
+        println!("Period: {} to {}", test_period_from, test_period_to);
+        let run = backtester.run(test_period_from, test_period_to).await?;
+        print_report(&run.report);
+
+        // Generate HTML report
+        let reports_dir = backtester.config.reports_dir.as_str();
+        if let Err(e) = generate_html_report(reports_dir, &run.report, &run.sim_30m, &run.sim_1h, &run.sim_2h) {
+            println!("⚠️  Failed to generate HTML report: {}", e);
         }
-        if let Some(pl) = result.profit_loss_2h {
-            total_pl_2h += pl;
-            if pl > 0.0 { profitable_2h += 1; } else if pl < 0.0 { losing_2h += 1; }
-            if pl > best_profit_2h { best_profit_2h = pl; }
-            if pl < worst_loss_2h { worst_loss_2h = pl; }
+
+        let json_path = format!("{}/btc_analysis_{}.json", reports_dir, chrono::Utc::now().format("%Y%m%d_%H%M%S"));
+        match run.report.to_json_file(&json_path) {
+            Ok(()) => println!("📄 JSON report generated: {}", json_path),
+            Err(e) => println!("⚠️  Failed to write JSON report: {}", e),
         }
     }
     
-    let total_decisions = results.len();
-    let report = TestReport {
-        symbol: symbol.to_string(),
-        model: config.openai_model.clone(),
-        test_period_from: test_period_from.to_string(),
-        test_period_to: test_period_to.to_string(),
-        config: config.clone(),
-        total_decisions,
-        buy_decisions,
-        sell_decisions,
-        hold_decisions,
-        correct_predictions_30m: correct_30m,
-        correct_predictions_1h: correct_1h,
-        correct_predictions_2h: correct_2h,
-        wrong_predictions_30m: wrong_30m,
-        wrong_predictions_1h: wrong_1h,
-        wrong_predictions_2h: wrong_2h,
-        good_buy_decisions_30m: good_buy_30m,
-        good_buy_decisions_1h: good_buy_1h,
-        good_buy_decisions_2h: good_buy_2h,
-        bad_buy_decisions_30m: bad_buy_30m,
-        bad_buy_decisions_1h: bad_buy_1h,
-        bad_buy_decisions_2h: bad_buy_2h,
-        good_sell_decisions_30m: good_sell_30m,
-        good_sell_decisions_1h: good_sell_1h,
-        good_sell_decisions_2h: good_sell_2h,
-        bad_sell_decisions_30m: bad_sell_30m,
-        bad_sell_decisions_1h: bad_sell_1h,
-        bad_sell_decisions_2h: bad_sell_2h,
-        fatal_buy_decisions_30m: fatal_buy_30m,
-        fatal_buy_decisions_1h: fatal_buy_1h,
-        fatal_buy_decisions_2h: fatal_buy_2h,
-        fatal_sell_decisions_30m: fatal_sell_30m,
-        fatal_sell_decisions_1h: fatal_sell_1h,
-        fatal_sell_decisions_2h: fatal_sell_2h,
-        profitable_decisions_30m: profitable_30m,
-        profitable_decisions_1h: profitable_1h,
-        profitable_decisions_2h: profitable_2h,
-        losing_decisions_30m: losing_30m,
-        losing_decisions_1h: losing_1h,
-        losing_decisions_2h: losing_2h,
-        total_profit_loss_30m: total_pl_30m,
-        total_profit_loss_1h: total_pl_1h,
-        total_profit_loss_2h: total_pl_2h,
-        average_profit_per_decision_30m: if total_decisions > 0 { total_pl_30m / total_decisions as f64 } else { 0.0 },
-        average_profit_per_decision_1h: if total_decisions > 0 { total_pl_1h / total_decisions as f64 } else { 0.0 },
-        average_profit_per_decision_2h: if total_decisions > 0 { total_pl_2h / total_decisions as f64 } else { 0.0 },
-        best_decision_profit_30m: if best_profit_30m == f64::NEG_INFINITY { 0.0 } else { best_profit_30m },
-        best_decision_profit_1h: if best_profit_1h == f64::NEG_INFINITY { 0.0 } else { best_profit_1h },
-        best_decision_profit_2h: if best_profit_2h == f64::NEG_INFINITY { 0.0 } else { best_profit_2h },
-        worst_decision_loss_30m: if worst_loss_30m == f64::INFINITY { 0.0 } else { worst_loss_30m },
-        worst_decision_loss_1h: if worst_loss_1h == f64::INFINITY { 0.0 } else { worst_loss_1h },
-        worst_decision_loss_2h: if worst_loss_2h == f64::INFINITY { 0.0 } else { worst_loss_2h },
-        accuracy_30m: if total_decisions > 0 { (correct_30m as f64 / total_decisions as f64) * 100.0 } else { 0.0 },
-        accuracy_1h: if total_decisions > 0 { (correct_1h as f64 / total_decisions as f64) * 100.0 } else { 0.0 },
-        accuracy_2h: if total_decisions > 0 { (correct_2h as f64 / total_decisions as f64) * 100.0 } else { 0.0 },
-        buy_accuracy_30m: if buy_decisions > 0 { (good_buy_30m as f64 / buy_decisions as f64) * 100.0 } else { 0.0 },
-        buy_accuracy_1h: if buy_decisions > 0 { (good_buy_1h as f64 / buy_decisions as f64) * 100.0 } else { 0.0 },
-        buy_accuracy_2h: if buy_decisions > 0 { (good_buy_2h as f64 / buy_decisions as f64) * 100.0 } else { 0.0 },
-        sell_accuracy_30m: if sell_decisions > 0 { (good_sell_30m as f64 / sell_decisions as f64) * 100.0 } else { 0.0 },
-        sell_accuracy_1h: if sell_decisions > 0 { (good_sell_1h as f64 / sell_decisions as f64) * 100.0 } else { 0.0 },
-        sell_accuracy_2h: if sell_decisions > 0 { (good_sell_2h as f64 / sell_decisions as f64) * 100.0 } else { 0.0 },
-        initial_portfolio_value: initial_portfolio,
-        final_portfolio_value_30m: sim_30m.current_value,
-        final_portfolio_value_1h: sim_1h.current_value,
-        final_portfolio_value_2h: sim_2h.current_value,
-        portfolio_return_30m: ((sim_30m.current_value - initial_portfolio) / initial_portfolio) * 100.0,
-        portfolio_return_1h: ((sim_1h.current_value - initial_portfolio) / initial_portfolio) * 100.0,
-        portfolio_return_2h: ((sim_2h.current_value - initial_portfolio) / initial_portfolio) * 100.0,
-        results,
-    };
-    
-    print_report(&report);
-    
-    // Generate HTML report
-    if let Err(e) = generate_html_report(&report, &sim_30m, &sim_1h, &sim_2h) {
-        println!("⚠️  Failed to generate HTML report: {}", e);
-    }
-    
     println!("\n✅ BotMarley trading decision analysis completed successfully!");
     Ok(())
 }
 
-fn generate_html_report(report: &TestReport, sim_30m: &PortfolioSimulation, sim_1h: &PortfolioSimulation, sim_2h: &PortfolioSimulation) -> color_eyre::Result<()> {
+fn generate_html_report(reports_dir: &str, report: &TestReport, sim_30m: &PortfolioSimulation, sim_1h: &PortfolioSimulation, sim_2h: &PortfolioSimulation) -> color_eyre::Result<()> {
     let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
-    let filename = format!("reports/btc_analysis_{}.html", timestamp);
+    let filename = format!("{}/btc_analysis_{}.html", reports_dir, timestamp);
     
     // Create reports directory if it doesn't exist
-    std::fs::create_dir_all("reports")?;
+    std::fs::create_dir_all(reports_dir)?;
     
     let html_content = format!(r#"
 <!DOCTYPE html>
@@ -1261,7 +907,7 @@ fn generate_html_report(report: &TestReport, sim_30m: &PortfolioSimulation, sim_
                 <div><strong>Period:</strong> {} to {}</div>
                 <div><strong>OpenAI Base URL:</strong> {}</div>
                 <div><strong>Total Decisions:</strong> {}</div>
-                <div><strong>Initial Portfolio:</strong> ${:.2}</div>
+                <div><strong>Initial Portfolio:</strong> {:.2} {}</div>
             </div>
         </div>
 
@@ -1410,27 +1056,28 @@ fn generate_html_report(report: &TestReport, sim_30m: &PortfolioSimulation, sim_
         report.test_period_to,
         report.config.openai_base_url,
         report.total_decisions,
-        report.initial_portfolio_value,
+        report.portfolio.initial_portfolio_value,
+        report.portfolio.portfolio_currency,
         
         // 30m portfolio
-        if report.portfolio_return_30m >= 0.0 { "positive" } else { "negative" },
-        report.final_portfolio_value_30m,
-        if report.portfolio_return_30m >= 0.0 { "positive" } else { "negative" },
-        report.portfolio_return_30m,
+        if report.portfolio.portfolio_return_30m >= 0.0 { "positive" } else { "negative" },
+        report.portfolio.final_portfolio_value_30m,
+        if report.portfolio.portfolio_return_30m >= 0.0 { "positive" } else { "negative" },
+        report.portfolio.portfolio_return_30m,
         sim_30m.transactions.len(),
         
         // 1h portfolio
-        if report.portfolio_return_1h >= 0.0 { "positive" } else { "negative" },
-        report.final_portfolio_value_1h,
-        if report.portfolio_return_1h >= 0.0 { "positive" } else { "negative" },
-        report.portfolio_return_1h,
+        if report.portfolio.portfolio_return_1h >= 0.0 { "positive" } else { "negative" },
+        report.portfolio.final_portfolio_value_1h,
+        if report.portfolio.portfolio_return_1h >= 0.0 { "positive" } else { "negative" },
+        report.portfolio.portfolio_return_1h,
         sim_1h.transactions.len(),
         
         // 2h portfolio
-        if report.portfolio_return_2h >= 0.0 { "positive" } else { "negative" },
-        report.final_portfolio_value_2h,
-        if report.portfolio_return_2h >= 0.0 { "positive" } else { "negative" },
-        report.portfolio_return_2h,
+        if report.portfolio.portfolio_return_2h >= 0.0 { "positive" } else { "negative" },
+        report.portfolio.final_portfolio_value_2h,
+        if report.portfolio.portfolio_return_2h >= 0.0 { "positive" } else { "negative" },
+        report.portfolio.portfolio_return_2h,
         sim_2h.transactions.len(),
         
         // Fatal decisions
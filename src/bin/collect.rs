@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use botmarley::binance::scheduler::collect_pairs;
+use botmarley::binance::DataCollector;
+use botmarley::config::Config;
+use botmarley::logging::init_logger;
+use tokio::time::{interval, Duration, MissedTickBehavior};
+
+/// Collect every configured pair once, printing a line per symbol.
+/// Returns the symbols that failed.
+async fn collect_once(collector: &DataCollector, pairs: &[String]) -> Vec<String> {
+    let outcome = collect_pairs(pairs, async |symbol: &str| collector.collect_symbol_data(symbol).await).await;
+    for symbol in &outcome.collected {
+        println!("✅ {}", symbol);
+    }
+    for (symbol, error) in &outcome.failed {
+        println!("❌ {}: {}", symbol, error);
+    }
+    let failed: Vec<String> = outcome.failed.into_iter().map(|(symbol, _)| symbol).collect();
+
+    match collector.get_data_stats().await {
+        Ok(stats) => {
+            println!("\n📊 Summary: {} collected, {} failed", pairs.len() - failed.len(), failed.len());
+            let mut stats: Vec<_> = stats.into_iter().collect();
+            stats.sort_by(|a, b| a.0.cmp(&b.0));
+            for (symbol, (count, _, max_time)) in stats {
+                let last = max_time
+                    .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+                    .unwrap_or_else(|| "N/A".to_string());
+                println!("  {}: {} klines, last {}", symbol, count, last);
+            }
+        }
+        Err(e) => println!("⚠️  Failed to read data statistics: {}", e),
+    }
+    failed
+}
+
+/// Run data collection once (for cron) or keep collecting with `--watch`,
+/// every `COLLECTION_INTERVAL_SECS`. `--rebuild` deletes the stored data of
+/// every pair first, e.g. after an upgrade changed the data file layout.
+#[tokio::main]
+async fn main() -> color_eyre::Result<()> {
+    color_eyre::install()?;
+    init_logger();
+
+    // without a .env file the settings come from the environment and defaults
+    let _ = dotenv::dotenv();
+
+    let config = Arc::new(Config::load());
+    let watch = std::env::args().any(|arg| arg == "--watch");
+    let rebuild = std::env::args().any(|arg| arg == "--rebuild");
+    let collector = DataCollector::new(config.clone())?;
+    let pairs = collector.get_listed_pairs().await?;
+
+    if rebuild {
+        for symbol in &pairs {
+            if collector.remove_symbol_data(symbol)? {
+                println!("🗑️  Removed stored data of {}", symbol);
+            }
+        }
+    }
+
+    println!("📥 Collecting {} pairs: {}", pairs.len(), pairs.join(", "));
+
+    if !watch {
+        let failed = collect_once(&collector, &pairs).await;
+        if !failed.is_empty() {
+            return Err(color_eyre::eyre::eyre!("Data collection failed for {}", failed.join(", ")));
+        }
+        return Ok(());
+    }
+
+    let every = Duration::from_secs(config.collection_interval_secs.max(1));
+    println!("👀 Watching, collecting every {}s (Ctrl+C to stop)", every.as_secs());
+    let mut ticker = interval(every);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                println!("\n🔄 {}", chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC"));
+                collect_once(&collector, &pairs).await;
+            }
+            _ = tokio::signal::ctrl_c() => {
+                println!("\n👋 Stopping collection");
+                break;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use botmarley::utils::report_math::{compare_reports, SavedReport, Winner};
+use color_eyre::eyre::eyre;
+
+fn main() -> color_eyre::Result<()> {
+    color_eyre::install()?;
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [path_a, path_b] = args.as_slice() else {
+        return Err(eyre!("Usage: compare_runs <report_a.json> <report_b.json>"));
+    };
+    let a = SavedReport::load(path_a)?;
+    let b = SavedReport::load(path_b)?;
+
+    println!("\n{}", "=".repeat(80));
+    println!("                           BACKTEST RUN COMPARISON");
+    println!("{}", "=".repeat(80));
+    println!("A: {} | {} | {} to {} | {} decisions", a.model, a.symbol, a.test_period_from, a.test_period_to, a.total_decisions);
+    println!("B: {} | {} | {} to {} | {} decisions", b.model, b.symbol, b.test_period_from, b.test_period_to, b.total_decisions);
+    if a.symbol != b.symbol || a.test_period_from != b.test_period_from || a.test_period_to != b.test_period_to {
+        println!("⚠️  The runs cover different symbols or periods, the comparison may not be fair");
+    }
+
+    let metrics = compare_reports(&a, &b);
+
+    println!("\n{:<28} {:>14} {:>14}  Winner", "Metric", "A", "B");
+    println!("{}", "-".repeat(66));
+    let (mut wins_a, mut wins_b) = (0, 0);
+    for row in &metrics {
+        let label = match row.winner {
+            Winner::A => {
+                wins_a += 1;
+                "🏆 A"
+            }
+            Winner::B => {
+                wins_b += 1;
+                "🏆 B"
+            }
+            Winner::Tie => "tie",
+        };
+        println!("{:<28} {:>14.2} {:>14.2}  {}", row.name, row.a, row.b, label);
+    }
+
+    println!("{}", "-".repeat(66));
+    match wins_a.cmp(&wins_b) {
+        std::cmp::Ordering::Greater => println!("Overall: A ({}) wins {} of {} metrics", a.model, wins_a, metrics.len()),
+        std::cmp::Ordering::Less => println!("Overall: B ({}) wins {} of {} metrics", b.model, wins_b, metrics.len()),
+        std::cmp::Ordering::Equal => println!("Overall: tie, {} metrics each", wins_a),
+    }
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use std::path::Path;
+
+use botmarley::utils::atomic_write::write_atomic;
+use botmarley::utils::journal::{journal_markdown, JournalEntry};
+use color_eyre::eyre::{eyre, WrapErr};
+use serde::Deserialize;
+
+/// Journals go where the markdown viewer picks them up
+const JOURNAL_DIR: &str = "logs_md";
+
+/// The parts of a `btc_test` JSON report the journal is built from.
+#[derive(Debug, Deserialize)]
+struct SavedReport {
+    symbol: String,
+    model: String,
+    test_period_from: String,
+    test_period_to: String,
+    results: Vec<SavedResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SavedResult {
+    timestamp: i64,
+    price: f64,
+    decision: SavedDecision,
+    actual_price_1h: Option<f64>,
+    profit_loss_1h: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SavedDecision {
+    action: String,
+    confidence: f64,
+    reasoning: String,
+}
+
+fn main() -> color_eyre::Result<()> {
+    color_eyre::install()?;
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [path] = args.as_slice() else {
+        return Err(eyre!("Usage: journal <report.json>"));
+    };
+    let json = std::fs::read_to_string(path).wrap_err_with(|| format!("Failed to read {}", path))?;
+    let report: SavedReport =
+        serde_json::from_str(&json).wrap_err_with(|| format!("{} is not a btc_test JSON report", path))?;
+
+    let entries: Vec<JournalEntry> = report
+        .results
+        .into_iter()
+        .map(|result| JournalEntry {
+            timestamp: result.timestamp,
+            action: result.decision.action,
+            price: result.price,
+            exit_price: result.actual_price_1h,
+            profit_loss_pct: result.profit_loss_1h,
+            confidence: result.decision.confidence,
+            reasoning: result.decision.reasoning,
+        })
+        .collect();
+    let title = format!(
+        "{} trade journal, {} to {} ({}, outcomes after 1h)",
+        report.symbol, report.test_period_from, report.test_period_to, report.model
+    );
+
+    let mut name = Path::new(path)
+        .file_stem()
+        .ok_or_else(|| eyre!("{} has no file name", path))?
+        .to_os_string();
+    name.push(".md");
+    let output = Path::new(JOURNAL_DIR).join(name);
+    write_atomic(&output, journal_markdown(&title, &entries))?;
+    println!("📓 Journal of {} decisions written to {}", entries.len(), output.display());
+    Ok(())
+}
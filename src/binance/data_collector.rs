@@ -1,19 +1,24 @@
 use chrono::{DateTime, Utc};
 use color_eyre::eyre::{Result, eyre, WrapErr};
 use polars::prelude::*;
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::time::{sleep, Duration};
 use tracing::{Level, debug, error, info, instrument, trace, warn};
 
 use crate::config::Config;
 use crate::symbol::Symbol;
+use crate::utils::atomic_write::write_atomic;
 use crate::utils::date_to_timestamp::date_string_to_timestamp;
+use crate::utils::file_lock::FileLock;
+use crate::utils::interval::KLINE_INTERVAL;
+use crate::utils::slippage::fill_price;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KlineData {
@@ -31,6 +36,74 @@ pub struct KlineData {
     pub taker_buy_quote_asset_volume: f64,
 }
 
+/// Columns (and types) of the stored kline Arrow files
+fn kline_columns() -> [(&'static str, DataType); 12] {
+    [
+        ("open_time", DataType::Int64),
+        ("close_time", DataType::Int64),
+        ("symbol", DataType::String),
+        ("open", DataType::Float64),
+        ("high", DataType::Float64),
+        ("low", DataType::Float64),
+        ("close", DataType::Float64),
+        ("volume", DataType::Float64),
+        ("quote_asset_volume", DataType::Float64),
+        ("number_of_trades", DataType::Int64),
+        ("taker_buy_base_asset_volume", DataType::Float64),
+        ("taker_buy_quote_asset_volume", DataType::Float64),
+    ]
+}
+
+/// Check that a stored kline file has every column this version reads.
+///
+/// Files written by an older version can miss newer columns; instead of a
+/// column error deep inside a run, this names the file, what is wrong with
+/// it and how to re-fetch it.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::binance::data_collector::check_kline_schema;
+/// use polars::prelude::*;
+/// let old = df![
+///     "open_time" => [0i64], "close_time" => [299_999i64], "symbol" => ["BTCUSDT"],
+///     "open" => [1.0], "high" => [1.0], "low" => [1.0], "close" => [1.0], "volume" => [1.0],
+/// ].unwrap();
+/// let err = check_kline_schema(old.schema(), std::path::Path::new("data/btcusdt_5m.arrow")).unwrap_err();
+/// let message = err.to_string();
+/// assert!(message.contains("missing quote_asset_volume"));
+/// assert!(message.contains("--rebuild"));
+/// ```
+pub fn check_kline_schema(schema: &Schema, file: &Path) -> Result<()> {
+    let problems: Vec<String> = kline_columns()
+        .iter()
+        .filter_map(|(name, dtype)| match schema.get(name) {
+            None => Some(format!("missing {}", name)),
+            Some(found) if found != dtype => Some(format!("{} is {} instead of {}", name, found, dtype)),
+            Some(_) => None,
+        })
+        .collect();
+    if problems.is_empty() {
+        return Ok(());
+    }
+    Err(eyre!(
+        "Incompatible data file {} ({}), it was probably written by an older version. Run `cargo run --bin collect -- --rebuild` to re-fetch it",
+        file.display(),
+        problems.join(", ")
+    ))
+}
+
+/// Lazily scan a stored kline file after checking its schema
+fn scan_arrow_file(file: &Path) -> Result<LazyFrame> {
+    let mut lazy = LazyFrame::scan_ipc(PlPath::Local(Arc::from(file)), Default::default())
+        .wrap_err_with(|| format!("Failed to scan Arrow file {}", file.display()))?;
+    let schema = lazy
+        .collect_schema()
+        .wrap_err_with(|| format!("Failed to read the schema of {}", file.display()))?;
+    check_kline_schema(&schema, file)?;
+    Ok(lazy)
+}
+
 #[derive(Debug, Deserialize)]
 struct BinanceKline {
     #[serde(rename = "0")]
@@ -59,19 +132,253 @@ struct BinanceKline {
     _ignore: String,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceTicker24h {
+    symbol: String,
+    last_price: String,
+    high_price: String,
+    low_price: String,
+    volume: String,
+    quote_volume: String,
+    price_change_percent: String,
+    close_time: i64,
+}
+
+/// Rolling 24h statistics with the live last price, fresher than the last closed candle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ticker24h {
+    pub symbol: String,
+    pub last_price: f64,
+    pub high_price: f64,
+    pub low_price: f64,
+    pub volume: f64,
+    pub quote_volume: f64,
+    pub price_change_percent: f64,
+    pub close_time: i64,
+}
+
+impl TryFrom<BinanceTicker24h> for Ticker24h {
+    type Error = color_eyre::Report;
+
+    fn try_from(raw: BinanceTicker24h) -> Result<Self> {
+        Ok(Ticker24h {
+            last_price: raw.last_price.parse()?,
+            high_price: raw.high_price.parse()?,
+            low_price: raw.low_price.parse()?,
+            volume: raw.volume.parse()?,
+            quote_volume: raw.quote_volume.parse()?,
+            price_change_percent: raw.price_change_percent.parse()?,
+            close_time: raw.close_time,
+            symbol: raw.symbol,
+        })
+    }
+}
+
+impl Ticker24h {
+    /// Lines for the LLM context
+    pub fn context_summary(&self) -> String {
+        format!(
+            "Live price: {:.4} ({:+.2}% 24h)\n24h high/low: {:.4} / {:.4}\n24h volume: {:.2} ({:.2} quote)",
+            self.last_price,
+            self.price_change_percent,
+            self.high_price,
+            self.low_price,
+            self.volume,
+            self.quote_volume
+        )
+    }
+}
+
+/// Parse a `/api/v3/ticker/24hr` response body
+pub fn parse_24h_ticker(body: &str) -> Result<Ticker24h> {
+    let raw: BinanceTicker24h = serde_json::from_str(body).wrap_err("Failed to parse 24h ticker")?;
+    raw.try_into()
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceBookTicker {
+    symbol: String,
+    bid_price: String,
+    bid_qty: String,
+    ask_price: String,
+    ask_qty: String,
+}
+
+/// Best bid and ask of the order book. Market buys fill around the ask and
+/// market sells around the bid, unlike the last trade price.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BookTicker {
+    pub symbol: String,
+    pub bid_price: f64,
+    pub bid_qty: f64,
+    pub ask_price: f64,
+    pub ask_qty: f64,
+}
+
+impl TryFrom<BinanceBookTicker> for BookTicker {
+    type Error = color_eyre::Report;
+
+    fn try_from(raw: BinanceBookTicker) -> Result<Self> {
+        Ok(BookTicker {
+            bid_price: raw.bid_price.parse()?,
+            bid_qty: raw.bid_qty.parse()?,
+            ask_price: raw.ask_price.parse()?,
+            ask_qty: raw.ask_qty.parse()?,
+            symbol: raw.symbol,
+        })
+    }
+}
+
+impl BookTicker {
+    /// Side of the book a market order takes: the ask for buys, the bid for sells
+    pub fn price_for(&self, is_buy: bool) -> f64 {
+        if is_buy { self.ask_price } else { self.bid_price }
+    }
+
+    /// Expected fill of a market order, spread and slippage included
+    pub fn fill_price(&self, is_buy: bool, slippage_pct: f64) -> f64 {
+        fill_price(self.price_for(is_buy), is_buy, slippage_pct)
+    }
+
+    /// Spread in percent of the mid price
+    pub fn spread_pct(&self) -> f64 {
+        let mid = (self.bid_price + self.ask_price) / 2.0;
+        if mid > 0.0 { (self.ask_price - self.bid_price) / mid * 100.0 } else { 0.0 }
+    }
+}
+
+/// Parse a `/api/v3/ticker/bookTicker` response body
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::binance::data_collector::parse_book_ticker;
+/// let body = r#"{"symbol":"BTCUSDC","bidPrice":"99.50","bidQty":"1.2","askPrice":"100.50","askQty":"0.8"}"#;
+/// let book = parse_book_ticker(body).unwrap();
+/// assert_eq!((book.bid_price, book.ask_price), (99.5, 100.5));
+/// assert_eq!(book.price_for(true), 100.5);
+/// assert_eq!(book.price_for(false), 99.5);
+/// assert_eq!(book.spread_pct(), 1.0);
+/// ```
+pub fn parse_book_ticker(body: &str) -> Result<BookTicker> {
+    let raw: BinanceBookTicker = serde_json::from_str(body).wrap_err("Failed to parse book ticker")?;
+    raw.try_into()
+}
+
+/// Error object Binance returns instead of data, e.g. `{"code":-1121,"msg":"Invalid symbol."}`
+#[derive(Debug, Clone, Deserialize)]
+pub struct BinanceError {
+    pub code: i64,
+    pub msg: String,
+}
+
+impl std::fmt::Display for BinanceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Binance error {}: {}", self.code, self.msg)
+    }
+}
+
+/// Parse a response body as a Binance error object, if it is one
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::binance::data_collector::parse_binance_error;
+/// let error = parse_binance_error(r#"{"code":-1121,"msg":"Invalid symbol."}"#).unwrap();
+/// assert_eq!((error.code, error.msg.as_str()), (-1121, "Invalid symbol."));
+/// assert_eq!(error.to_string(), "Binance error -1121: Invalid symbol.");
+/// // an HTML error page from a proxy, or regular kline data
+/// assert!(parse_binance_error("<html>502 Bad Gateway</html>").is_none());
+/// assert!(parse_binance_error("[[1499040000000,\"0.01634790\"]]").is_none());
+/// ```
+pub fn parse_binance_error(body: &str) -> Option<BinanceError> {
+    serde_json::from_str::<BinanceError>(body).ok()
+}
+
+#[derive(Debug, Deserialize)]
+struct BinanceExchangeInfo {
+    symbols: Vec<BinanceSymbolInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BinanceSymbolInfo {
+    symbol: String,
+    status: String,
+}
+
+impl BinanceExchangeInfo {
+    fn trading_symbols(self) -> HashSet<String> {
+        self.symbols
+            .into_iter()
+            .filter(|symbol| symbol.status == "TRADING")
+            .map(|symbol| symbol.symbol)
+            .collect()
+    }
+}
+
+/// Symbols currently trading, from an `/api/v3/exchangeInfo` response body
+pub fn parse_trading_symbols(body: &str) -> Result<HashSet<String>> {
+    let info: BinanceExchangeInfo = serde_json::from_str(body).wrap_err("Failed to parse exchange info")?;
+    Ok(info.trading_symbols())
+}
+
+/// Configured pairs the collector should use: every `allowed_pairs` entry,
+/// then the `base_assets`/`quote_asset` combinations found in `trading`.
+/// Expanded pairs Binance doesn't list are skipped with a warning.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::binance::data_collector::{listed_pairs, parse_trading_symbols};
+/// use botmarley::config::Config;
+/// let body = r#"{"timezone":"UTC","symbols":[
+///     {"symbol":"BTCUSDC","status":"TRADING","baseAsset":"BTC","quoteAsset":"USDC"},
+///     {"symbol":"ETHUSDC","status":"TRADING","baseAsset":"ETH","quoteAsset":"USDC"},
+///     {"symbol":"LUNAUSDC","status":"BREAK","baseAsset":"LUNA","quoteAsset":"USDC"}]}"#;
+/// let trading = parse_trading_symbols(body).unwrap();
+/// let config = Config {
+///     allowed_pairs: "DOGE_USDT".to_string(),
+///     base_assets: "BTC,ETH,LUNA,NOPE".to_string(),
+///     quote_asset: "USDC".to_string(),
+///     ..Default::default()
+/// };
+/// assert_eq!(listed_pairs(&config, &trading), ["DOGEUSDT", "BTCUSDC", "ETHUSDC"]);
+/// ```
+pub fn listed_pairs(config: &Config, trading: &HashSet<String>) -> Vec<String> {
+    let mut pairs: Vec<String> = config.explicit_pairs_parts().into_iter().map(|(l, r)| format!("{l}{r}")).collect();
+    for (base, quote) in config.expanded_pairs_parts() {
+        let pair = format!("{base}{quote}");
+        if pairs.contains(&pair) {
+            continue;
+        }
+        if trading.contains(&pair) {
+            pairs.push(pair);
+        } else {
+            warn!("{} is not a trading pair on Binance, skipping {} from BASE_ASSETS", pair, base);
+        }
+    }
+    pairs
+}
+
+/// How many times a request is attempted while Binance answers 503 (maintenance)
+const MAX_UNAVAILABLE_ATTEMPTS: u32 = 5;
+
 #[derive(Debug,Clone)]
 
 pub struct DataCollector {
     client: Client,
     data_dir: PathBuf,
-    config: Arc<Config>,
+    pub(crate) config: Arc<Config>,
 }
 
 impl DataCollector {
     pub fn new(config: Arc<Config>) -> color_eyre::Result<Self> {
         let client = Client::new();
         
-        let data_dir = PathBuf::from("data");
+        // Testnet klines are synthetic, keep them away from the real history
+        let data_dir = if config.use_testnet { PathBuf::from(&config.data_dir).join("testnet") } else { PathBuf::from(&config.data_dir) };
         if !data_dir.exists() {
             fs::create_dir_all(&data_dir)
                 .wrap_err("Failed to create data directory")?;
@@ -96,8 +403,7 @@ impl DataCollector {
         let symbol_clone = symbol.to_string();
         
         let df = tokio::task::spawn_blocking(move || {
-            LazyFrame::scan_ipc(PlPath::Local(Arc::from(file_path_clone.as_path())), Default::default())
-                .wrap_err("Failed to scan IPC file")?
+            scan_arrow_file(&file_path_clone)?
                 .select([col("close_time")])
                 .sort(["close_time"], SortMultipleOptions::default())
                 .tail(1)
@@ -123,9 +429,68 @@ impl DataCollector {
         Ok(Some(last_timestamp))
     }
 
+    /// Latest stored candle of a symbol, `None` without stored data.
+    /// Only the last row is materialized, not the whole history.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use botmarley::binance::DataCollector;
+    /// use botmarley::config::Config;
+    /// use polars::prelude::*;
+    /// let dir = std::env::temp_dir().join(format!("botmarley_latest_{}", std::process::id()));
+    /// let config = Config { data_dir: dir.to_str().unwrap().to_string(), ..Default::default() };
+    /// let collector = DataCollector::new(Arc::new(config)).unwrap();
+    /// let rt = tokio::runtime::Runtime::new().unwrap();
+    /// assert!(rt.block_on(collector.latest_candle("BTCUSDC")).unwrap().is_none());
+    ///
+    /// let df = df![
+    ///     "open_time" => [0i64, 300_000], "close_time" => [299_999i64, 599_999], "symbol" => ["BTCUSDC", "BTCUSDC"],
+    ///     "open" => [1.0, 2.0], "high" => [1.5, 2.5], "low" => [0.5, 1.5], "close" => [2.0, 2.2], "volume" => [10.0, 20.0],
+    ///     "quote_asset_volume" => [15.0, 42.0], "number_of_trades" => [3i64, 4],
+    ///     "taker_buy_base_asset_volume" => [5.0, 8.0], "taker_buy_quote_asset_volume" => [7.5, 17.0],
+    /// ].unwrap();
+    /// collector.save_to_arrow(df, "BTCUSDC").unwrap();
+    /// let latest = rt.block_on(collector.latest_candle("BTCUSDC")).unwrap().unwrap();
+    /// assert_eq!((latest.open_time, latest.close), (300_000, 2.2));
+    /// std::fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    pub async fn latest_candle(&self, symbol: &str) -> color_eyre::Result<Option<KlineData>> {
+        let file_path = self.get_arrow_file_path(symbol);
+        if !file_path.exists() {
+            return Ok(None);
+        }
+
+        let symbol_clone = symbol.to_string();
+        let df = tokio::task::spawn_blocking(move || {
+            scan_arrow_file(&file_path)?
+                .sort(["open_time"], SortMultipleOptions::default())
+                .tail(1)
+                .collect()
+                .wrap_err("Failed to collect DataFrame")
+        })
+        .await
+        .wrap_err("Failed to execute blocking task")?
+        .wrap_err_with(|| format!("Failed to load the latest candle of {}", symbol_clone))?;
+
+        Ok(self.dataframe_to_klines(df)?.pop())
+    }
+
     /// Get the Arrow file path for a symbol
-    fn get_arrow_file_path(&self, symbol: &str) -> PathBuf {
-        self.data_dir.join(format!("{}_5m.arrow", symbol.to_lowercase()))
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use botmarley::binance::DataCollector;
+    /// use botmarley::config::Config;
+    /// let dir = std::env::temp_dir().join(format!("botmarley_data_{}", std::process::id()));
+    /// let config = Config { data_dir: dir.to_str().unwrap().to_string(), ..Default::default() };
+    /// let collector = DataCollector::new(Arc::new(config)).unwrap();
+    /// assert_eq!(collector.get_arrow_file_path("BTCUSDC"), dir.join("btcusdc_5m.arrow"));
+    /// std::fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    pub fn get_arrow_file_path(&self, symbol: &str) -> PathBuf {
+        self.data_dir.join(format!("{}_{}.arrow", symbol.to_lowercase(), KLINE_INTERVAL))
     }
 
     /// Fetch klines data from Binance API using reqwest
@@ -135,6 +500,19 @@ impl DataCollector {
         start_time: Option<i64>,
         end_time: Option<i64>,
     ) -> color_eyre::Result<Vec<KlineData>> {
+        let (klines, _) = self.fetch_kline_batches(symbol, start_time, end_time, None).await?;
+        Ok(klines)
+    }
+
+    /// Fetch at most `max_batches` requests worth of klines (all of them when `None`).
+    /// The flag is `true` once the end of the available data was reached.
+    pub async fn fetch_kline_batches(
+        &self,
+        symbol: &str,
+        start_time: Option<i64>,
+        end_time: Option<i64>,
+        max_batches: Option<usize>,
+    ) -> color_eyre::Result<(Vec<KlineData>, bool)> {
         let start_str = start_time
             .map(|t| DateTime::<Utc>::from_timestamp_millis(t).unwrap().to_rfc3339())
             .unwrap_or_else(|| "recent data".to_string());
@@ -147,15 +525,16 @@ impl DataCollector {
         let mut all_klines = Vec::new();
         let mut current_start = start_time;
         let limit = 1000; // Maximum allowed by Binance
+        let mut batches = 0;
 
-        loop {
+        let done = loop {
             // Add rate limiting to avoid hitting API limits
             sleep(Duration::from_millis(100)).await;
             
             // Build the URL for Binance API
             let mut url = format!(
-                "https://api.binance.com/api/v3/klines?symbol={}&interval=5m&limit={}",
-                symbol, limit
+                "{}/api/v3/klines?symbol={}&interval={}&limit={}",
+                self.config.binance_api_url(), symbol, KLINE_INTERVAL, limit
             );
             
             if let Some(start) = current_start {
@@ -168,27 +547,10 @@ impl DataCollector {
             
             trace!("Requesting: {}", url);
             
-            let response = self.client
-                .get(&url)
-                .send()
-                .await
-                .wrap_err_with(|| format!("Failed to send request to Binance API for {}", symbol))?;
-            
-            if !response.status().is_success() {
-                return Err(eyre!(
-                    "Binance API returned error status {} for symbol {}",
-                    response.status(),
-                    symbol
-                ));
-            }
-            
-            let klines: Vec<BinanceKline> = response
-                .json()
-                .await
-                .wrap_err_with(|| format!("Failed to parse JSON response for {}", symbol))?;
+            let klines: Vec<BinanceKline> = self.get_binance_json(&url, symbol).await?;
 
             if klines.is_empty() {
-                break;
+                break true;
             }
 
             let mut batch_klines = Vec::new();
@@ -217,22 +579,103 @@ impl DataCollector {
             }
 
             all_klines.extend(batch_klines);
+            batches += 1;
 
             // If we got less than the limit, we've reached the end
             if klines_len < limit as usize {
-                break;
+                break true;
             }
 
             // If we have an end_time and we've passed it, break
             if let Some(end_time) = end_time {
                 if current_start.unwrap_or(0) >= end_time {
-                    break;
+                    break true;
                 }
             }
-        }
+
+            if max_batches.is_some_and(|max| batches >= max) {
+                break false;
+            }
+        };
 
         debug!("Fetched {} klines for {}", all_klines.len(), symbol);
-        Ok(all_klines)
+        Ok((all_klines, done))
+    }
+
+    /// Fetch the rolling 24h ticker, including the live last price
+    pub async fn get_24h_ticker(&self, symbol: &str) -> color_eyre::Result<Ticker24h> {
+        let url = format!("{}/api/v3/ticker/24hr?symbol={}", self.config.binance_api_url(), symbol);
+        let raw: BinanceTicker24h = self.get_binance_json(&url, symbol).await?;
+        raw.try_into()
+            .wrap_err_with(|| format!("Invalid 24h ticker values for {}", symbol))
+    }
+
+    /// Fetch the best bid and ask
+    pub async fn get_book_ticker(&self, symbol: &str) -> color_eyre::Result<BookTicker> {
+        let url = format!("{}/api/v3/ticker/bookTicker?symbol={}", self.config.binance_api_url(), symbol);
+        let raw: BinanceBookTicker = self.get_binance_json(&url, symbol).await?;
+        raw.try_into()
+            .wrap_err_with(|| format!("Invalid book ticker values for {}", symbol))
+    }
+
+    /// Configured pairs, with the `base_assets` expansion checked against `exchangeInfo`
+    pub async fn get_listed_pairs(&self) -> color_eyre::Result<Vec<String>> {
+        if self.config.expanded_pairs_parts().is_empty() {
+            return Ok(self.config.pairs());
+        }
+        let url = format!("{}/api/v3/exchangeInfo?permissions=SPOT", self.config.binance_api_url());
+        let info: BinanceExchangeInfo = self.get_binance_json(&url, "exchangeInfo").await?;
+        Ok(listed_pairs(&self.config, &info.trading_symbols()))
+    }
+
+    /// `(bid, ask)`: size buys against the ask and sells against the bid
+    pub async fn get_bid_ask(&self, symbol: &str) -> color_eyre::Result<(f64, f64)> {
+        let book = self.get_book_ticker(symbol).await?;
+        Ok((book.bid_price, book.ask_price))
+    }
+
+    /// GET a Binance endpoint and decode the JSON body.
+    /// Binance error objects are turned into descriptive errors and 503 responses
+    /// (maintenance / overload) are retried with exponential backoff.
+    pub(crate) async fn get_binance_json<T: DeserializeOwned>(&self, url: &str, symbol: &str) -> Result<T> {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+
+            let response = self.client
+                .get(url)
+                .send()
+                .await
+                .wrap_err_with(|| format!("Failed to send request to Binance API for {}", symbol))?;
+            let status = response.status();
+            let body = response
+                .text()
+                .await
+                .wrap_err_with(|| format!("Failed to read Binance response body for {}", symbol))?;
+
+            if status == StatusCode::SERVICE_UNAVAILABLE && attempt < MAX_UNAVAILABLE_ATTEMPTS {
+                let delay = Duration::from_secs(2u64.pow(attempt));
+                warn!("Binance unavailable (503) for {}, retrying in {:?} (attempt {}/{})",
+                      symbol, delay, attempt, MAX_UNAVAILABLE_ATTEMPTS);
+                sleep(delay).await;
+                continue;
+            }
+
+            if let Some(error) = parse_binance_error(&body) {
+                return Err(eyre!("{} for symbol {} (HTTP {})", error, symbol, status));
+            }
+
+            if !status.is_success() {
+                return Err(eyre!(
+                    "Binance API returned error status {} for symbol {}",
+                    status,
+                    symbol
+                ));
+            }
+
+            return serde_json::from_str(&body)
+                .wrap_err_with(|| format!("Failed to parse JSON response for {}", symbol));
+        }
     }
 
     /// Convert klines data to Polars DataFrame
@@ -272,14 +715,51 @@ impl DataCollector {
         Ok(df)
     }
 
-    /// Save DataFrame to Arrow file
+    /// Save DataFrame to Arrow file. The file is replaced atomically, so a
+    /// collector killed mid-flush leaves the previous data in place and the
+    /// next run resumes after its last close_time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use botmarley::binance::DataCollector;
+    /// use botmarley::config::Config;
+    /// use polars::prelude::*;
+    /// let dir = std::env::temp_dir().join(format!("botmarley_flush_{}", std::process::id()));
+    /// let config = Config { data_dir: dir.to_str().unwrap().to_string(), ..Default::default() };
+    /// let collector = DataCollector::new(Arc::new(config)).unwrap();
+    /// let klines = |open_times: &[i64]| df![
+    ///     "open_time" => open_times, "close_time" => open_times.iter().map(|t| t + 299_999).collect::<Vec<_>>(),
+    ///     "symbol" => vec!["BTCUSDC"; open_times.len()], "open" => vec![1.0; open_times.len()],
+    ///     "high" => vec![1.5; open_times.len()], "low" => vec![0.5; open_times.len()], "close" => vec![1.2; open_times.len()],
+    ///     "volume" => vec![10.0; open_times.len()], "quote_asset_volume" => vec![12.0; open_times.len()],
+    ///     "number_of_trades" => vec![3i64; open_times.len()], "taker_buy_base_asset_volume" => vec![5.0; open_times.len()],
+    ///     "taker_buy_quote_asset_volume" => vec![6.0; open_times.len()],
+    /// ].unwrap();
+    /// let rt = tokio::runtime::Runtime::new().unwrap();
+    /// collector.save_to_arrow(klines(&[0, 300_000]), "BTCUSDC").unwrap();
+    ///
+    /// // a flush killed half way only leaves a partial temp file behind
+    /// let path = collector.get_arrow_file_path("BTCUSDC");
+    /// let bytes = std::fs::read(&path).unwrap();
+    /// std::fs::write(path.with_extension("arrow.tmp"), &bytes[..bytes.len() / 2]).unwrap();
+    /// assert_eq!(rt.block_on(collector.get_last_timestamp("BTCUSDC")).unwrap(), Some(599_999));
+    ///
+    /// // the resumed backfill appends after the last stored candle
+    /// rt.block_on(collector.append_to_arrow(klines(&[600_000]), "BTCUSDC")).unwrap();
+    /// assert_eq!(rt.block_on(collector.get_last_timestamp("BTCUSDC")).unwrap(), Some(899_999));
+    /// assert!(!path.with_extension("arrow.tmp").exists());
+    /// std::fs::remove_dir_all(&dir).unwrap();
+    /// ```
     pub fn save_to_arrow(&self, df: DataFrame, symbol: &str) -> Result<()> {
         let file_path = self.get_arrow_file_path(symbol);
         
-        let mut file = std::fs::File::create(&file_path)
-            .wrap_err_with(|| format!("Failed to create Arrow file: {}", file_path.display()))?;
-        IpcWriter::new(&mut file).finish(&mut df.clone())
+        let mut buffer = Vec::new();
+        IpcWriter::new(&mut buffer).finish(&mut df.clone())
             .wrap_err("Failed to write DataFrame to Arrow file")?;
+        write_atomic(&file_path, buffer)
+            .wrap_err_with(|| format!("Failed to save Arrow file: {}", file_path.display()))?;
         
         debug!("Saved {} rows to {}", df.height(), file_path.display());
         Ok(())
@@ -298,8 +778,7 @@ impl DataCollector {
         
         let combined_df = tokio::task::spawn_blocking(move || {
             // Read existing data
-            let existing_df = LazyFrame::scan_ipc(PlPath::Local(Arc::from(file_path_clone.as_path())), Default::default())
-                .wrap_err("Failed to scan existing Arrow file")?
+            let existing_df = scan_arrow_file(&file_path_clone)?
                 .collect()
                 .wrap_err("Failed to collect existing DataFrame")?;
 
@@ -319,6 +798,20 @@ impl DataCollector {
         self.save_to_arrow(combined_df, symbol)
     }
 
+    /// Delete the stored klines of a symbol so the next collection re-fetches
+    /// them from scratch. Returns whether there was a file to delete.
+    pub fn remove_symbol_data(&self, symbol: &str) -> Result<bool> {
+        let file_path = self.get_arrow_file_path(symbol);
+        let _lock = FileLock::acquire(&file_path)?;
+        if !file_path.exists() {
+            return Ok(false);
+        }
+        fs::remove_file(&file_path)
+            .wrap_err_with(|| format!("Failed to remove {}", file_path.display()))?;
+        info!("Removed {} to rebuild it", file_path.display());
+        Ok(true)
+    }
+
     #[instrument]
     /// Collect data for a single symbol
     pub async fn collect_symbol_data(&self, symbol: &str) -> Result<()> {
@@ -326,10 +819,14 @@ impl DataCollector {
         let _enter = span.enter();
         // info!("Starting data collection for {}", symbol);
 
+        // Held until the Arrow file is written, so a scheduler and a manual
+        // collector run can't interleave their read-modify-write cycles
+        let _lock = FileLock::acquire(&self.get_arrow_file_path(symbol))?;
+
         // Get the last timestamp from existing data
         let last_timestamp = self.get_last_timestamp(symbol).await?;
         
-        let start_time = match last_timestamp {
+        let mut start_time = match last_timestamp {
             Some(ts) => {
                 debug!("Found existing data .last timestamp: {}", 
                       DateTime::<Utc>::from_timestamp_millis(ts).unwrap().to_rfc3339());
@@ -359,22 +856,41 @@ impl DataCollector {
             }
         };
 
-        // Fetch new klines data
-        let klines = self.fetch_klines(symbol, start_time, None).await?;
-        
-        if klines.is_empty() {
-            info!("No new data available for {}", symbol);
-            return Ok(());
+        // Fetch new klines, flushing every `backfill_flush_batches` requests so an
+        // interrupted backfill resumes from the last written close_time
+        let flush_every = Some(self.config.backfill_flush_batches.max(1));
+        let mut has_file = last_timestamp.is_some();
+        let mut fetched = 0;
+        loop {
+            let (klines, done) = self.fetch_kline_batches(symbol, start_time, None, flush_every).await?;
+
+            if let Some(last_kline) = klines.last() {
+                start_time = Some(last_kline.close_time + 1);
+                fetched += klines.len();
+
+                // Convert to DataFrame
+                let df = self.klines_to_dataframe(klines)?;
+
+                // Save or append to Arrow file
+                if has_file {
+                    self.append_to_arrow(df, symbol).await?;
+                } else {
+                    self.save_to_arrow(df, symbol)?;
+                    has_file = true;
+                }
+                if !done {
+                    debug!("Flushed {} klines for {}, continuing backfill", fetched, symbol);
+                }
+            }
+
+            if done {
+                break;
+            }
         }
 
-        // Convert to DataFrame
-        let df = self.klines_to_dataframe(klines)?;
-        
-        // Save or append to Arrow file
-        if last_timestamp.is_some() {
-            self.append_to_arrow(df, symbol).await?;
-        } else {
-            self.save_to_arrow(df, symbol)?;
+        if fetched == 0 {
+            info!("No new data available for {}", symbol);
+            return Ok(());
         }
 
         debug!("Successfully updated data for {}", symbol);
@@ -385,7 +901,7 @@ impl DataCollector {
     pub async fn collect_all_data(&self) -> Result<()> {
         debug!("Starting data collection for all pairs");
         
-        let pairs = self.config.pairs();
+        let pairs = self.get_listed_pairs().await?;
         let mut errors = Vec::new();
 
         for symbol in pairs {
@@ -428,8 +944,7 @@ impl DataCollector {
         let symbol_clone = symbol.clone();
         
         let df = tokio::task::spawn_blocking(move || {
-            LazyFrame::scan_ipc(PlPath::Local(Arc::from(file_path_clone.as_path())), Default::default())
-                .wrap_err("Failed to scan Arrow file")?
+            scan_arrow_file(&file_path_clone)?
                 .sort(["open_time"], SortMultipleOptions::default())
                 .collect()
                 .wrap_err("Failed to collect DataFrame")
@@ -443,6 +958,83 @@ impl DataCollector {
         // Convert DataFrame back to Vec<KlineData>
         self.dataframe_to_klines(df)
     }
+    /// Lazily scan the stored klines of a symbol, sorted by `open_time`.
+    /// Nothing is read until the frame is collected.
+    pub fn scan_klines(&self, symbol: &str) -> color_eyre::Result<LazyFrame> {
+        let file_path = self.get_arrow_file_path(symbol);
+        if !file_path.exists() {
+            return Err(eyre!("No data file found for symbol: {}", symbol));
+        }
+        Ok(scan_arrow_file(&file_path)?
+            .sort(["open_time"], SortMultipleOptions::default()))
+    }
+
+#[instrument(level="debug")]
+    /// Get klines for a symbol with `start_ms <= open_time <= end_ms`.
+    /// The range filter is pushed into the lazy scan so only matching rows are materialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use botmarley::binance::DataCollector;
+    /// use botmarley::config::Config;
+    /// use polars::prelude::*;
+    /// let dir = std::env::temp_dir().join(format!("botmarley_range_{}", std::process::id()));
+    /// let config = Config { data_dir: dir.to_str().unwrap().to_string(), ..Default::default() };
+    /// let collector = DataCollector::new(Arc::new(config)).unwrap();
+    /// let df = df![
+    ///     "open_time" => [0i64, 300_000, 600_000], "close_time" => [299_999i64, 599_999, 899_999],
+    ///     "symbol" => ["BTCUSDC", "BTCUSDC", "BTCUSDC"],
+    ///     "open" => [1.0, 2.0, 3.0], "high" => [1.5, 2.5, 3.5], "low" => [0.5, 1.5, 2.5], "close" => [2.0, 3.0, 4.0],
+    ///     "volume" => [10.0, 20.0, 30.0], "quote_asset_volume" => [15.0, 42.0, 99.0], "number_of_trades" => [3i64, 4, 5],
+    ///     "taker_buy_base_asset_volume" => [5.0, 8.0, 9.0], "taker_buy_quote_asset_volume" => [7.5, 17.0, 30.0],
+    /// ].unwrap();
+    /// collector.save_to_arrow(df, "BTCUSDC").unwrap();
+    /// let rt = tokio::runtime::Runtime::new().unwrap();
+    /// let open_times = |start, end| {
+    ///     let klines = rt.block_on(collector.get_klines_for_symbol_range("BTCUSDC".to_string(), start, end)).unwrap();
+    ///     klines.iter().map(|k| k.open_time).collect::<Vec<_>>()
+    /// };
+    /// // both bounds are inclusive
+    /// assert_eq!(open_times(300_000, 600_000), vec![300_000, 600_000]);
+    /// assert_eq!(open_times(0, 0), vec![0]);
+    /// // a range between two candles, and one that ends before it starts
+    /// assert!(open_times(300_001, 599_999).is_empty());
+    /// assert!(open_times(600_000, 0).is_empty());
+    /// std::fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    pub async fn get_klines_for_symbol_range(&self, symbol: String, start_ms: i64, end_ms: i64) -> color_eyre::Result<Vec<KlineData>> {
+        let file_path = self.get_arrow_file_path(&symbol);
+
+        if !file_path.exists() {
+            return Err(eyre!("No data file found for symbol: {}", symbol));
+        }
+
+        debug!("Loading klines data for {} between {} and {}", symbol, start_ms, end_ms);
+
+        let file_path_clone = file_path.clone();
+        let symbol_clone = symbol.clone();
+
+        let df = tokio::task::spawn_blocking(move || {
+            scan_arrow_file(&file_path_clone)?
+                .filter(
+                    col("open_time")
+                        .gt_eq(lit(start_ms))
+                        .and(col("open_time").lt_eq(lit(end_ms))),
+                )
+                .sort(["open_time"], SortMultipleOptions::default())
+                .collect()
+                .wrap_err("Failed to collect DataFrame")
+        })
+        .await
+        .wrap_err("Failed to execute blocking task")?
+        .wrap_err_with(|| format!("Failed to load DataFrame for {}", symbol_clone))?;
+
+        debug!("Loaded {} klines in range for {}", df.height(), symbol);
+
+        self.dataframe_to_klines(df)
+    }
 #[instrument(level="debug")]
     /// Convert DataFrame back to Vec<KlineData>
     fn dataframe_to_klines(&self, df: DataFrame) -> Result<Vec<KlineData>> {
@@ -0,0 +1,138 @@
+use color_eyre::eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize};
+
+use crate::binance::DataCollector;
+
+/// Funding per 8h above this (0.05%) means longs pay heavily to stay in
+pub const EXTREME_FUNDING_RATE: f64 = 0.0005;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinancePremiumIndex {
+    symbol: String,
+    mark_price: String,
+    last_funding_rate: String,
+    next_funding_time: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceOpenInterest {
+    open_interest: String,
+}
+
+/// Latest funding of a perpetual contract
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingRate {
+    pub symbol: String,
+    pub mark_price: f64,
+    /// Fraction paid every funding interval, positive when longs pay shorts
+    pub funding_rate: f64,
+    pub next_funding_time: i64,
+}
+
+impl TryFrom<BinancePremiumIndex> for FundingRate {
+    type Error = color_eyre::Report;
+
+    fn try_from(raw: BinancePremiumIndex) -> Result<Self> {
+        Ok(FundingRate {
+            mark_price: raw.mark_price.parse()?,
+            funding_rate: raw.last_funding_rate.parse()?,
+            next_funding_time: raw.next_funding_time,
+            symbol: raw.symbol,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FundingSentiment {
+    CrowdedLongs,
+    CrowdedShorts,
+    Balanced,
+}
+
+impl std::fmt::Display for FundingSentiment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            FundingSentiment::CrowdedLongs => "crowded longs",
+            FundingSentiment::CrowdedShorts => "crowded shorts",
+            FundingSentiment::Balanced => "balanced",
+        };
+        f.write_str(label)
+    }
+}
+
+/// Sentiment of a funding rate; extreme funding often precedes squeezes
+pub fn classify_funding(funding_rate: f64) -> FundingSentiment {
+    if funding_rate >= EXTREME_FUNDING_RATE {
+        FundingSentiment::CrowdedLongs
+    } else if funding_rate <= -EXTREME_FUNDING_RATE {
+        FundingSentiment::CrowdedShorts
+    } else {
+        FundingSentiment::Balanced
+    }
+}
+
+/// Parse a futures `/fapi/v1/premiumIndex` response body
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::binance::futures::{classify_funding, parse_funding_rate, FundingSentiment};
+/// let body = r#"{"symbol":"BTCUSDT","markPrice":"64000.10","indexPrice":"63990.00","estimatedSettlePrice":"63995.00","lastFundingRate":"0.00075000","interestRate":"0.00010000","nextFundingTime":1597392000000,"time":1597370495002}"#;
+/// let funding = parse_funding_rate(body).unwrap();
+/// assert_eq!(funding.funding_rate, 0.00075);
+/// assert_eq!(classify_funding(funding.funding_rate), FundingSentiment::CrowdedLongs);
+/// assert_eq!(classify_funding(0.0001), FundingSentiment::Balanced);
+/// ```
+pub fn parse_funding_rate(body: &str) -> Result<FundingRate> {
+    let raw: BinancePremiumIndex = serde_json::from_str(body).wrap_err("Failed to parse premium index")?;
+    raw.try_into()
+}
+
+/// Funding and open interest of a perpetual, for the LLM context
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FuturesContext {
+    pub funding: FundingRate,
+    /// Open contracts, in the base asset
+    pub open_interest: f64,
+}
+
+impl FuturesContext {
+    pub fn sentiment(&self) -> FundingSentiment {
+        classify_funding(self.funding.funding_rate)
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "Futures {}: funding {:+.4}% ({}), open interest {:.2} (~${:.0})",
+            self.funding.symbol,
+            self.funding.funding_rate * 100.0,
+            self.sentiment(),
+            self.open_interest,
+            self.open_interest * self.funding.mark_price
+        )
+    }
+}
+
+impl DataCollector {
+    /// Latest funding rate and open interest of the perpetual `symbol`
+    pub async fn get_futures_context(&self, symbol: &str) -> Result<FuturesContext> {
+        let base = self.config.binance_futures_api_url();
+        let premium: BinancePremiumIndex = self
+            .get_binance_json(&format!("{}/fapi/v1/premiumIndex?symbol={}", base, symbol), symbol)
+            .await?;
+        let open_interest: BinanceOpenInterest = self
+            .get_binance_json(&format!("{}/fapi/v1/openInterest?symbol={}", base, symbol), symbol)
+            .await?;
+        Ok(FuturesContext {
+            funding: premium
+                .try_into()
+                .wrap_err_with(|| format!("Invalid premium index values for {}", symbol))?,
+            open_interest: open_interest
+                .open_interest
+                .parse()
+                .wrap_err_with(|| format!("Invalid open interest for {}", symbol))?,
+        })
+    }
+}
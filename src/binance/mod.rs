@@ -1,3 +1,6 @@
 pub mod data_collector;
+pub mod futures;
 pub use data_collector::DataCollector;
-pub mod main_collector_runner;
\ No newline at end of file
+pub mod main_collector_runner;
+pub mod scheduler;
+pub use scheduler::DataCollectionScheduler;
\ No newline at end of file
@@ -0,0 +1,169 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::{interval_at, Instant, MissedTickBehavior};
+use tracing::{error, info, instrument};
+
+use crate::binance::DataCollector;
+use crate::bot::klines::is_data_fresh;
+use crate::config::Config;
+use crate::symbol::Symbol;
+
+/// Keeps every configured pair's Arrow file up to date in the background,
+/// on its own cadence, so analysis never has to trigger a collection.
+pub struct DataCollectionScheduler {
+    collector: DataCollector,
+    every: Duration,
+}
+
+impl DataCollectionScheduler {
+    pub fn new(config: Arc<Config>) -> color_eyre::Result<Self> {
+        let every = Duration::from_secs(config.collection_interval_secs.max(1));
+        let collector = DataCollector::new(config)?;
+        Ok(Self { collector, every })
+    }
+
+    /// Refresh all configured pairs once. Failures for a single pair are
+    /// logged by the collector and don't stop the others.
+    #[instrument(skip(self))]
+    pub async fn tick(&self) {
+        if let Err(e) = self.collector.collect_all_data().await {
+            error!("Scheduled data collection failed: {}", e);
+        }
+    }
+
+    /// `true` when any of `symbols` has no stored candle or its latest one
+    /// closed more than `max_staleness_secs` ago, i.e. a blocking `tick`
+    /// is needed before analysis.
+    pub async fn needs_refresh(&self, symbols: &[Symbol], max_staleness_secs: u64) -> color_eyre::Result<bool> {
+        let now = chrono::Utc::now().timestamp_millis();
+        for symbol in symbols {
+            let latest = self.collector.latest_candle(&symbol.to_string()).await?;
+            if !latest.is_some_and(|k| is_data_fresh(k.close_time, now, max_staleness_secs)) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Run until `shutdown` flips to true (or its sender is dropped).
+    /// The first collection happens after one interval; call `tick` first
+    /// when the data has to be fresh right away.
+    pub async fn run(self, shutdown: watch::Receiver<bool>) {
+        info!(interval_secs = self.every.as_secs(), "Starting data collection scheduler");
+        run_every(self.every, shutdown, || self.tick()).await;
+        info!("Data collection scheduler stopped");
+    }
+
+    pub fn spawn(self, shutdown: watch::Receiver<bool>) -> JoinHandle<()> {
+        tokio::spawn(self.run(shutdown))
+    }
+}
+
+/// Call `tick` every `every`, starting one interval from now, until
+/// `shutdown` flips to true or its sender is dropped.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::time::Duration;
+/// use botmarley::binance::scheduler::run_every;
+/// let rt = tokio::runtime::Runtime::new().unwrap();
+/// rt.block_on(async {
+///     let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+///     let ticks = Arc::new(AtomicUsize::new(0));
+///     let counter = ticks.clone();
+///     let task = tokio::spawn(run_every(Duration::from_millis(100), shutdown_rx, move || {
+///         counter.fetch_add(1, Ordering::SeqCst);
+///         async {}
+///     }));
+///     // nothing runs before the first interval
+///     tokio::time::sleep(Duration::from_millis(50)).await;
+///     assert_eq!(ticks.load(Ordering::SeqCst), 0);
+///     // ticks at 100 and 200 ms
+///     tokio::time::sleep(Duration::from_millis(200)).await;
+///     assert_eq!(ticks.load(Ordering::SeqCst), 2);
+///
+///     shutdown_tx.send(true).unwrap();
+///     tokio::time::timeout(Duration::from_millis(50), task).await.unwrap().unwrap();
+///     tokio::time::sleep(Duration::from_millis(150)).await;
+///     assert_eq!(ticks.load(Ordering::SeqCst), 2);
+///
+///     // dropping the sender stops it too
+///     let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+///     let task = tokio::spawn(run_every(Duration::from_secs(60), shutdown_rx, || async {}));
+///     drop(shutdown_tx);
+///     tokio::time::timeout(Duration::from_millis(50), task).await.unwrap().unwrap();
+/// });
+/// ```
+pub async fn run_every<F, Fut>(every: Duration, mut shutdown: watch::Receiver<bool>, mut tick: F)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let mut ticker = interval_at(Instant::now() + every, every);
+    // a slow collection shouldn't cause a burst of catch-up runs
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => tick().await,
+            changed = shutdown.changed() => {
+                if changed.is_err() || *shutdown.borrow() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Pairs that were collected and pairs that failed, with the error, in
+/// the order they were tried.
+#[derive(Debug, Default, PartialEq)]
+pub struct CollectionOutcome {
+    pub collected: Vec<String>,
+    pub failed: Vec<(String, String)>,
+}
+
+/// Collect every pair once through `fetch`, e.g. `DataCollector::collect_symbol_data`.
+/// A failing pair is recorded and doesn't stop the others.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::binance::scheduler::collect_pairs;
+/// let rt = tokio::runtime::Runtime::new().unwrap();
+/// rt.block_on(async {
+///     let pairs = ["BTCUSDC".to_string(), "ETHUSDC".to_string(), "SOLUSDC".to_string()];
+///     let mut fetched = Vec::new();
+///     let outcome = collect_pairs(&pairs, async |symbol: &str| {
+///         fetched.push(symbol.to_string());
+///         if symbol == "ETHUSDC" {
+///             return Err(color_eyre::eyre::eyre!("rate limited"));
+///         }
+///         Ok(())
+///     })
+///     .await;
+///     assert_eq!(fetched, pairs);
+///     assert_eq!(outcome.collected, ["BTCUSDC", "SOLUSDC"]);
+///     assert_eq!(outcome.failed, [("ETHUSDC".to_string(), "rate limited".to_string())]);
+/// });
+/// ```
+pub async fn collect_pairs(pairs: &[String], mut fetch: impl AsyncFnMut(&str) -> color_eyre::Result<()>) -> CollectionOutcome {
+    let mut outcome = CollectionOutcome::default();
+    for symbol in pairs {
+        match fetch(symbol).await {
+            Ok(()) => outcome.collected.push(symbol.clone()),
+            Err(e) => {
+                error!("Collecting {} failed: {}", symbol, e);
+                outcome.failed.push((symbol.clone(), e.to_string()));
+            }
+        }
+    }
+    outcome
+}
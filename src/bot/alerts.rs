@@ -0,0 +1,264 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use color_eyre::eyre::{eyre, WrapErr};
+use financial_indicators::mfi::money_flow_index;
+use financial_indicators::rsi::relative_strength_index;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::{interval, MissedTickBehavior};
+use tracing::{error, info, warn};
+
+use crate::bot::indicators::adx::calculate_dmi;
+use crate::bot::indicators::moving_averages::last_defined;
+use crate::bot::klines::{Klines, KlinesOHLC};
+use crate::config::Config;
+use crate::symbol::Symbol;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlertIndicator {
+    Price,
+    Rsi,
+    Mfi,
+    Adx,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Comparator {
+    Below,
+    Above,
+}
+
+/// A threshold rule such as `BTCUSDC RSI < 25`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub symbol: String,
+    pub indicator: AlertIndicator,
+    pub comparator: Comparator,
+    pub threshold: f64,
+}
+
+impl FromStr for AlertIndicator {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "PRICE" => Ok(AlertIndicator::Price),
+            "RSI" => Ok(AlertIndicator::Rsi),
+            "MFI" => Ok(AlertIndicator::Mfi),
+            "ADX" => Ok(AlertIndicator::Adx),
+            other => Err(eyre!("Unknown alert indicator '{}' (use PRICE, RSI, MFI or ADX)", other)),
+        }
+    }
+}
+
+/// Parse one rule: `<pair> <PRICE|RSI|MFI|ADX> <"<"|">"> <threshold>`.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::alerts::{AlertIndicator, AlertRule, Comparator};
+/// let rule: AlertRule = "btc_usdc rsi < 25".parse().unwrap();
+/// assert_eq!((rule.symbol.as_str(), rule.indicator, rule.comparator, rule.threshold), ("BTCUSDC", AlertIndicator::Rsi, Comparator::Below, 25.0));
+/// assert_eq!(rule.to_string(), "BTCUSDC RSI < 25");
+///
+/// let error = |rule: &str| rule.parse::<AlertRule>().unwrap_err().to_string();
+/// assert!(error("BTCUSDC RSI <").contains("must look like"));
+/// assert!(error("BTCUSDC RSI <= 25").contains("unknown comparator '<='"));
+/// assert!(error("BTCUSDC VWAP < 25").contains("Unknown alert indicator 'VWAP'"));
+/// assert!(error("BTCUSDC RSI < low").contains("invalid threshold"));
+/// assert!(error("BTCEUR PRICE > 1").contains("unsupported pair"));
+/// ```
+impl FromStr for AlertRule {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        let [symbol, indicator, comparator, threshold] = parts.as_slice() else {
+            return Err(eyre!("Alert rule '{}' must look like 'BTCUSDC RSI < 25'", s));
+        };
+        let symbol = symbol
+            .parse::<Symbol>()
+            .wrap_err_with(|| format!("Alert rule '{}': unsupported pair", s))?
+            .to_string();
+        let comparator = match *comparator {
+            "<" => Comparator::Below,
+            ">" => Comparator::Above,
+            other => return Err(eyre!("Alert rule '{}': unknown comparator '{}'", s, other)),
+        };
+        Ok(AlertRule {
+            symbol,
+            indicator: indicator.parse()?,
+            comparator,
+            threshold: threshold
+                .parse()
+                .wrap_err_with(|| format!("Alert rule '{}': invalid threshold", s))?,
+        })
+    }
+}
+
+impl std::fmt::Display for AlertRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let comparator = match self.comparator {
+            Comparator::Below => "<",
+            Comparator::Above => ">",
+        };
+        let indicator = format!("{:?}", self.indicator).to_uppercase();
+        write!(f, "{} {} {} {}", self.symbol, indicator, comparator, self.threshold)
+    }
+}
+
+impl AlertRule {
+    pub fn is_met(&self, value: f64) -> bool {
+        match self.comparator {
+            Comparator::Below => value < self.threshold,
+            Comparator::Above => value > self.threshold,
+        }
+    }
+}
+
+/// Parse `;` separated rules, e.g. `BTCUSDC RSI < 25; ETHUSDC PRICE > 4000`.
+pub fn parse_alert_rules(spec: &str) -> color_eyre::Result<Vec<AlertRule>> {
+    spec.split(';')
+        .map(str::trim)
+        .filter(|rule| !rule.is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+/// Latest value of the rule's indicator.
+pub fn indicator_value(indicator: AlertIndicator, ohlc: &KlinesOHLC) -> Option<f64> {
+    match indicator {
+        AlertIndicator::Price => ohlc.close.last().copied(),
+        AlertIndicator::Rsi => last_defined(&relative_strength_index(&ohlc.close, 14)),
+        AlertIndicator::Mfi => last_defined(&money_flow_index(&ohlc.high, &ohlc.low, &ohlc.close, &ohlc.volume, 14)),
+        AlertIndicator::Adx => last_defined(&calculate_dmi(&ohlc.high, &ohlc.low, &ohlc.close, 14).adx),
+    }
+}
+
+/// Tracks which rules currently hold so a rule only fires on the
+/// false -> true transition, not on every cycle while it stays true.
+#[derive(Debug, Clone)]
+pub struct AlertEngine {
+    rules: Vec<AlertRule>,
+    active: Vec<bool>,
+}
+
+impl AlertEngine {
+    pub fn new(rules: Vec<AlertRule>) -> Self {
+        let active = vec![false; rules.len()];
+        Self { rules, active }
+    }
+
+    pub fn rules(&self) -> &[AlertRule] {
+        &self.rules
+    }
+
+    /// Record the rule's current value; `true` when the rule just started holding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use botmarley::bot::alerts::AlertEngine;
+    /// let mut engine = AlertEngine::new(vec!["BTCUSDC RSI < 25".parse().unwrap()]);
+    /// assert!(!engine.update(0, 30.0));
+    /// // fires once when the condition starts holding
+    /// assert!(engine.update(0, 24.0));
+    /// // and stays quiet while it keeps holding
+    /// assert!(!engine.update(0, 20.0));
+    /// assert!(!engine.update(0, 22.0));
+    /// // until it clears and triggers again
+    /// assert!(!engine.update(0, 26.0));
+    /// assert!(engine.update(0, 23.0));
+    /// ```
+    pub fn update(&mut self, rule_index: usize, value: f64) -> bool {
+        let met = self.rules[rule_index].is_met(value);
+        let fired = met && !self.active[rule_index];
+        self.active[rule_index] = met;
+        fired
+    }
+}
+
+/// Background task evaluating the configured alert rules every collection interval.
+pub struct AlertMonitor {
+    engine: AlertEngine,
+    client: Client,
+    webhook_url: String,
+    every: Duration,
+}
+
+impl AlertMonitor {
+    pub fn new(config: &Config) -> color_eyre::Result<Self> {
+        Ok(Self {
+            engine: AlertEngine::new(parse_alert_rules(&config.alerts)?),
+            client: Client::new(),
+            webhook_url: config.alert_webhook_url.clone(),
+            every: Duration::from_secs(config.collection_interval_secs.max(1)),
+        })
+    }
+
+    pub fn has_rules(&self) -> bool {
+        !self.engine.rules().is_empty()
+    }
+
+    /// Post to a Discord compatible webhook, or just log when none is configured.
+    async fn notify(&self, message: &str) {
+        warn!("🔔 {}", message);
+        if self.webhook_url.is_empty() {
+            return;
+        }
+        let result = self
+            .client
+            .post(&self.webhook_url)
+            .json(&serde_json::json!({ "content": message }))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(e) = result {
+            error!("Failed to send alert notification: {}", e);
+        }
+    }
+
+    pub async fn check(&mut self) {
+        for index in 0..self.engine.rules().len() {
+            let rule = self.engine.rules()[index].clone();
+            let ohlc = match Klines::new(rule.symbol.as_str().into()).get_ohlc().await {
+                Ok(ohlc) => ohlc,
+                Err(e) => {
+                    warn!("Skipping alert '{}': {}", rule, e);
+                    continue;
+                }
+            };
+            let Some(value) = indicator_value(rule.indicator, &ohlc) else {
+                continue;
+            };
+            if self.engine.update(index, value) {
+                self.notify(&format!("Alert: {} (now {:.4})", rule, value)).await;
+            }
+        }
+    }
+
+    pub async fn run(mut self, mut shutdown: watch::Receiver<bool>) {
+        info!(rules = self.engine.rules().len(), "Starting alert monitor");
+        let mut ticker = interval(self.every);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => self.check().await,
+                changed = shutdown.changed() => {
+                    if changed.is_err() || *shutdown.borrow() {
+                        break;
+                    }
+                }
+            }
+        }
+        info!("Alert monitor stopped");
+    }
+
+    pub fn spawn(self, shutdown: watch::Receiver<bool>) -> JoinHandle<()> {
+        tokio::spawn(self.run(shutdown))
+    }
+}
+
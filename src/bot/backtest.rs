@@ -0,0 +1,776 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::WrapErr;
+use serde::{Deserialize, Serialize};
+
+use crate::bot::dataset::IndicatorPerformance;
+use crate::config::Config;
+use crate::utils::atomic_write::write_atomic;
+use crate::utils::report_math::{finite_or_zero, safe_pct};
+use crate::utils::slippage::fill_price;
+
+/// Decision the model returned for one backtest candle
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TradingDecision {
+    pub action: String, // "buy", "sell", "hold"
+    pub confidence: f64, // 0.0 to 1.0
+    /// `confidence` adjusted by the hit-rate of earlier decisions, when CALIBRATE_CONFIDENCE is set
+    #[serde(default)]
+    pub calibrated_confidence: Option<f64>,
+    pub reasoning: String,
+    pub thinking:Vec<Thought>,
+    pub price_target: Option<f64>,
+    pub stop_loss: Option<f64>,
+}
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Thought {
+    pub step: f64,  
+    pub thought: String,
+    pub require_next_step: bool,
+    pub conclusion: String,
+
+}
+
+/// A decision scored against the 30m, 1h and 2h price moves after it
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DecisionResult {
+    pub timestamp: i64,
+    pub price: f64,
+    pub decision: TradingDecision,
+    pub actual_price_30m: Option<f64>,
+    pub actual_price_1h: Option<f64>,
+    pub actual_price_2h: Option<f64>,
+    pub was_correct_30m: Option<bool>,
+    pub was_correct_1h: Option<bool>,
+    pub was_correct_2h: Option<bool>,
+    pub profit_loss_30m: Option<f64>,
+    pub profit_loss_1h: Option<f64>,
+    pub profit_loss_2h: Option<f64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TestReport {
+    // Test configuration
+    pub symbol: String,
+    pub model: String,
+    pub test_period_from: String,
+    pub test_period_to: String,
+    pub config: Config,
+    
+    // Basic statistics
+    pub total_decisions: usize,
+    pub buy_decisions: usize,
+    pub sell_decisions: usize,
+    pub hold_decisions: usize,
+    
+    // Accuracy metrics
+    pub correct_predictions_30m: usize,
+    pub correct_predictions_1h: usize,
+    pub correct_predictions_2h: usize,
+    pub wrong_predictions_30m: usize,
+    pub wrong_predictions_1h: usize,
+    pub wrong_predictions_2h: usize,
+    
+    // Decision-specific performance
+    pub good_buy_decisions_30m: usize,
+    pub good_buy_decisions_1h: usize,
+    pub good_buy_decisions_2h: usize,
+    pub bad_buy_decisions_30m: usize,
+    pub bad_buy_decisions_1h: usize,
+    pub bad_buy_decisions_2h: usize,
+    pub good_sell_decisions_30m: usize,
+    pub good_sell_decisions_1h: usize,
+    pub good_sell_decisions_2h: usize,
+    pub bad_sell_decisions_30m: usize,
+    pub bad_sell_decisions_1h: usize,
+    pub bad_sell_decisions_2h: usize,
+    
+    // Fatal decisions (buy when price drops significantly)
+    pub fatal_buy_decisions_30m: usize,
+    pub fatal_buy_decisions_1h: usize,
+    pub fatal_buy_decisions_2h: usize,
+    pub fatal_sell_decisions_30m: usize,
+    pub fatal_sell_decisions_1h: usize,
+    pub fatal_sell_decisions_2h: usize,
+    
+    // Profitability
+    pub profitable_decisions_30m: usize,
+    pub profitable_decisions_1h: usize,
+    pub profitable_decisions_2h: usize,
+    pub losing_decisions_30m: usize,
+    pub losing_decisions_1h: usize,
+    pub losing_decisions_2h: usize,
+    
+    // Financial metrics
+    pub total_profit_loss_30m: f64,
+    pub total_profit_loss_1h: f64,
+    pub total_profit_loss_2h: f64,
+    pub average_profit_per_decision_30m: f64,
+    pub average_profit_per_decision_1h: f64,
+    pub average_profit_per_decision_2h: f64,
+    pub best_decision_profit_30m: f64,
+    pub best_decision_profit_1h: f64,
+    pub best_decision_profit_2h: f64,
+    pub worst_decision_loss_30m: f64,
+    pub worst_decision_loss_1h: f64,
+    pub worst_decision_loss_2h: f64,
+    
+    // Accuracy percentages
+    pub accuracy_30m: f64,
+    pub accuracy_1h: f64,
+    pub accuracy_2h: f64,
+    pub buy_accuracy_30m: f64,
+    pub buy_accuracy_1h: f64,
+    pub buy_accuracy_2h: f64,
+    pub sell_accuracy_30m: f64,
+    pub sell_accuracy_1h: f64,
+    pub sell_accuracy_2h: f64,
+    
+    // Portfolio simulation
+    #[serde(flatten)]
+    pub portfolio: PortfolioSummary,
+
+    /// Hit-rate of each indicator's signal against the 1h close-to-close move
+    #[serde(default)]
+    pub indicator_performance: BTreeMap<String, IndicatorPerformance>,
+    
+    pub results: Vec<DecisionResult>,
+}
+
+impl TestReport {
+    /// Write the full report, per-decision results included, as pretty JSON
+    /// so separate runs can be diffed or loaded elsewhere. API keys in the
+    /// embedded `Config` are left out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use botmarley::bot::backtest::{DecisionResult, TestReport, TradingDecision};
+    /// use botmarley::config::Config;
+    /// let report = TestReport {
+    ///     symbol: "BTCUSDC".to_string(),
+    ///     config: Config {
+    ///         openai_api_key: "sk-secret-openai".to_string(),
+    ///         binance_api_key: "secret-binance-key".to_string(),
+    ///         binance_secret_key: "secret-binance-secret".to_string(),
+    ///         ..Default::default()
+    ///     },
+    ///     total_decisions: 1,
+    ///     buy_decisions: 1,
+    ///     results: vec![DecisionResult {
+    ///         timestamp: 1_700_000_000_000,
+    ///         price: 36500.0,
+    ///         decision: TradingDecision { action: "buy".to_string(), confidence: 0.8, ..Default::default() },
+    ///         profit_loss_1h: Some(1.25),
+    ///         ..Default::default()
+    ///     }],
+    ///     ..Default::default()
+    /// };
+    /// let path = std::env::temp_dir().join(format!("botmarley_report_{}", std::process::id())).join("report.json");
+    /// report.to_json_file(path.to_str().unwrap()).unwrap();
+    ///
+    /// let json = std::fs::read_to_string(&path).unwrap();
+    /// assert!(!json.contains("secret"));
+    /// let loaded: TestReport = serde_json::from_str(&json).unwrap();
+    /// assert_eq!((loaded.symbol.as_str(), loaded.buy_decisions), ("BTCUSDC", 1));
+    /// assert_eq!(loaded.results[0].decision.action, "buy");
+    /// assert_eq!(loaded.results[0].profit_loss_1h, Some(1.25));
+    /// assert_eq!(loaded.config.openai_model, Config::default().openai_model);
+    /// // a loaded report writes back out unchanged
+    /// assert_eq!(serde_json::to_string_pretty(&loaded).unwrap(), json);
+    /// std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    /// ```
+    pub fn to_json_file(&self, path: &str) -> color_eyre::Result<()> {
+        let json = serde_json::to_string_pretty(self).wrap_err("Failed to serialize test report")?;
+        write_atomic(Path::new(path), json)
+    }
+
+    /// Plain-text report printed at the end of a backtest: the aggregate
+    /// metrics, the per-decision table and a few insights.
+    ///
+    /// Empty and degenerate runs render zeros instead of `NaN` or `inf`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use botmarley::bot::backtest::{BacktestRun, DecisionResult, TradingDecision};
+    /// use botmarley::config::Config;
+    /// let config = Config::default();
+    /// let hold = |timestamp| DecisionResult {
+    ///     timestamp,
+    ///     price: 100.0,
+    ///     decision: TradingDecision { action: "hold".to_string(), ..Default::default() },
+    ///     ..Default::default()
+    /// };
+    /// let empty = BacktestRun::new("BTCUSDC", "2024-10-01", "2024-10-02", &config, Vec::new(), BTreeMap::new());
+    /// let all_hold = BacktestRun::new("BTCUSDC", "2024-10-01", "2024-10-02", &config, vec![hold(0), hold(300_000)], BTreeMap::new());
+    /// for run in [empty, all_hold] {
+    ///     let summary = run.report.summary();
+    ///     assert!(summary.contains("Total Decisions"));
+    ///     assert!(!summary.contains("NaN"), "{}", summary);
+    ///     assert!(!summary.to_lowercase().contains("inf"), "{}", summary);
+    ///     assert!(summary.contains("Max gain +0.00% | Max loss +0.00%"));
+    /// }
+    /// ```
+    pub fn summary(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&format!("\n{}\n", "=".repeat(80)));
+        out.push_str("                           TRADING DECISION ANALYSIS REPORT\n");
+        out.push_str(&format!("{}\n", "=".repeat(80)));
+    
+        out.push_str("\n📊 SUMMARY STATISTICS:\n");
+        out.push_str(&format!("├─ Total Decisions: {}\n", self.total_decisions));
+        out.push_str(&format!("├─ Buy Decisions: {} ({:.1}%)\n", self.buy_decisions,
+                 safe_pct(self.buy_decisions as f64, self.total_decisions as f64)));
+        out.push_str(&format!("├─ Sell Decisions: {} ({:.1}%)\n", self.sell_decisions,
+                 safe_pct(self.sell_decisions as f64, self.total_decisions as f64)));
+        out.push_str(&format!("└─ Hold Decisions: {} ({:.1}%)\n", self.hold_decisions,
+                 safe_pct(self.hold_decisions as f64, self.total_decisions as f64)));
+
+        out.push_str("\n🎯 OVERALL ACCURACY METRICS:\n");
+        out.push_str(&format!("├─ 30-minute: {:.1}% (✅{} / ❌{})\n", self.accuracy_30m,
+                 self.correct_predictions_30m, self.wrong_predictions_30m));
+        out.push_str(&format!("├─ 1-hour: {:.1}% (✅{} / ❌{})\n", self.accuracy_1h,
+                 self.correct_predictions_1h, self.wrong_predictions_1h));
+        out.push_str(&format!("└─ 2-hour: {:.1}% (✅{} / ❌{})\n", self.accuracy_2h,
+                 self.correct_predictions_2h, self.wrong_predictions_2h));
+
+        out.push_str("\n📈 BUY DECISION PERFORMANCE:\n");
+        out.push_str(&format!("├─ 30-minute: {:.1}% (✅{} / ❌{})\n", self.buy_accuracy_30m,
+                 self.good_buy_decisions_30m, self.bad_buy_decisions_30m));
+        out.push_str(&format!("├─ 1-hour: {:.1}% (✅{} / ❌{})\n", self.buy_accuracy_1h,
+                 self.good_buy_decisions_1h, self.bad_buy_decisions_1h));
+        out.push_str(&format!("└─ 2-hour: {:.1}% (✅{} / ❌{})\n", self.buy_accuracy_2h,
+                 self.good_buy_decisions_2h, self.bad_buy_decisions_2h));
+
+        out.push_str("\n📉 SELL DECISION PERFORMANCE:\n");
+        out.push_str(&format!("├─ 30-minute: {:.1}% (✅{} / ❌{})\n", self.sell_accuracy_30m,
+                 self.good_sell_decisions_30m, self.bad_sell_decisions_30m));
+        out.push_str(&format!("├─ 1-hour: {:.1}% (✅{} / ❌{})\n", self.sell_accuracy_1h,
+                 self.good_sell_decisions_1h, self.bad_sell_decisions_1h));
+        out.push_str(&format!("└─ 2-hour: {:.1}% (✅{} / ❌{})\n", self.sell_accuracy_2h,
+                 self.good_sell_decisions_2h, self.bad_sell_decisions_2h));
+
+        out.push_str("\n💰 PROFIT/LOSS ANALYSIS:\n");
+        out.push_str(&format!("├─ 30-minute: Total: {:+.2}% | Avg: {:+.2}% | Best: {:+.2}% | Worst: {:+.2}%\n",
+                 self.total_profit_loss_30m, self.average_profit_per_decision_30m,
+                 self.best_decision_profit_30m, self.worst_decision_loss_30m));
+        out.push_str(&format!("├─ 1-hour: Total: {:+.2}% | Avg: {:+.2}% | Best: {:+.2}% | Worst: {:+.2}%\n",
+                 self.total_profit_loss_1h, self.average_profit_per_decision_1h,
+                 self.best_decision_profit_1h, self.worst_decision_loss_1h));
+        out.push_str(&format!("└─ 2-hour: Total: {:+.2}% | Avg: {:+.2}% | Best: {:+.2}% | Worst: {:+.2}%\n",
+                 self.total_profit_loss_2h, self.average_profit_per_decision_2h,
+                 self.best_decision_profit_2h, self.worst_decision_loss_2h));
+
+        out.push_str("\n💸 PROFITABILITY BREAKDOWN:\n");
+        out.push_str(&format!("├─ 30-minute: 💚{} profitable | 💔{} losing\n",
+                 self.profitable_decisions_30m, self.losing_decisions_30m));
+        out.push_str(&format!("├─ 1-hour: 💚{} profitable | 💔{} losing\n",
+                 self.profitable_decisions_1h, self.losing_decisions_1h));
+        out.push_str(&format!("└─ 2-hour: 💚{} profitable | 💔{} losing\n",
+                 self.profitable_decisions_2h, self.losing_decisions_2h));
+
+        out.push_str("\n⚠️ FATAL DECISIONS (>3% Loss):\n");
+        out.push_str(&format!("├─ Fatal Buy Decisions: 30m: {} | 1h: {} | 2h: {}\n",
+                 self.fatal_buy_decisions_30m, self.fatal_buy_decisions_1h, self.fatal_buy_decisions_2h));
+        out.push_str(&format!("└─ Fatal Sell Decisions: 30m: {} | 1h: {} | 2h: {}\n",
+                 self.fatal_sell_decisions_30m, self.fatal_sell_decisions_1h, self.fatal_sell_decisions_2h));
+
+        let currency = &self.portfolio.portfolio_currency;
+        out.push_str(&format!("\n💰 PORTFOLIO SIMULATION ({:.2} {} initial):\n", self.portfolio.initial_portfolio_value, currency));
+        out.push_str(&format!("├─ 30-minute: {:.2} {} ({:+.2}%)\n",
+                 self.portfolio.final_portfolio_value_30m, currency, self.portfolio.portfolio_return_30m));
+        out.push_str(&format!("├─ 1-hour: {:.2} {} ({:+.2}%)\n",
+                 self.portfolio.final_portfolio_value_1h, currency, self.portfolio.portfolio_return_1h));
+        out.push_str(&format!("└─ 2-hour: {:.2} {} ({:+.2}%)\n",
+                 self.portfolio.final_portfolio_value_2h, currency, self.portfolio.portfolio_return_2h));
+
+        if !self.indicator_performance.is_empty() {
+            out.push_str("\n🧭 INDICATOR PERFORMANCE (signal vs 1h move):\n");
+            let mut ranked: Vec<_> = self.indicator_performance.iter().collect();
+            ranked.sort_by(|a, b| b.1.accuracy_pct().total_cmp(&a.1.accuracy_pct()));
+            for (name, performance) in ranked {
+                out.push_str(&format!("├─ {:<14} {:>5.1}% ({}/{} signals)\n", name, performance.accuracy_pct(), performance.correct, performance.signals));
+            }
+        }
+
+        out.push_str("\n📈 DETAILED RESULTS TABLE:\n");
+        out.push_str(&format!("{:<20} {:<8} {:<8} {:<6} {:<10} {:<10} {:<10} {:<8} {:<8} {:<8}\n",
+                 "Timestamp", "Price", "Action", "Conf", "30m P&L", "1h P&L", "2h P&L", "30m ✓", "1h ✓", "2h ✓"));
+        out.push_str(&format!("{}\n", "-".repeat(100)));
+
+        for result in &self.results {
+            let timestamp = DateTime::<Utc>::from_timestamp_millis(result.timestamp)
+                .unwrap_or_default()
+                .format("%m-%d %H:%M");
+        
+            out.push_str(&format!("{:<20} ${:<7.2} {:<8} {:<5.0}% {:>+9.2}% {:>+9.2}% {:>+9.2}% {:<8} {:<8} {:<8}\n",
+                     timestamp,
+                     result.price,
+                     result.decision.action.to_uppercase(),
+                     result.decision.confidence * 100.0,
+                     result.profit_loss_30m.unwrap_or(0.0),
+                     result.profit_loss_1h.unwrap_or(0.0),
+                     result.profit_loss_2h.unwrap_or(0.0),
+                     if result.was_correct_30m.unwrap_or(false) { "✅" } else { "❌" },
+                     if result.was_correct_1h.unwrap_or(false) { "✅" } else { "❌" },
+                     if result.was_correct_2h.unwrap_or(false) { "✅" } else { "❌" },
+            ));
+        }
+
+        out.push_str(&format!("\n{}\n", "=".repeat(80)));
+    
+        // Enhanced performance insights
+        out.push_str("\n🔍 PERFORMANCE INSIGHTS:\n");
+    
+        // Overall performance
+        if self.accuracy_30m > 60.0 {
+            out.push_str(&format!("✅ Strong short-term prediction accuracy (30m: {:.1}%)\n", self.accuracy_30m));
+        } else {
+            out.push_str(&format!("⚠️  Room for improvement in short-term predictions (30m: {:.1}%)\n", self.accuracy_30m));
+        }
+    
+        // Profitability insights
+        if self.total_profit_loss_2h > 0.0 {
+            out.push_str(&format!("✅ Positive overall returns over 2-hour periods ({:+.2}%)\n", self.total_profit_loss_2h));
+        } else {
+            out.push_str(&format!("⚠️  Negative overall returns - strategy needs refinement ({:+.2}%)\n", self.total_profit_loss_2h));
+        }
+    
+        // Best timeframe
+        let best_timeframe = if self.accuracy_2h >= self.accuracy_1h && self.accuracy_2h >= self.accuracy_30m {
+            "2-hour"
+        } else if self.accuracy_1h >= self.accuracy_30m {
+            "1-hour"
+        } else {
+            "30-minute"
+        };
+        out.push_str(&format!("📊 Best performing timeframe: {}\n", best_timeframe));
+    
+        // Action-specific insights
+        if self.buy_decisions > 0 {
+            let best_buy_timeframe = if self.buy_accuracy_2h >= self.buy_accuracy_1h && self.buy_accuracy_2h >= self.buy_accuracy_30m {
+                ("2-hour", self.buy_accuracy_2h)
+            } else if self.buy_accuracy_1h >= self.buy_accuracy_30m {
+                ("1-hour", self.buy_accuracy_1h)
+            } else {
+                ("30-minute", self.buy_accuracy_30m)
+            };
+            out.push_str(&format!("🟢 Buy decisions perform best at {} timeframe ({:.1}% accuracy)\n", best_buy_timeframe.0, best_buy_timeframe.1));
+        }
+    
+        if self.sell_decisions > 0 {
+            let best_sell_timeframe = if self.sell_accuracy_2h >= self.sell_accuracy_1h && self.sell_accuracy_2h >= self.sell_accuracy_30m {
+                ("2-hour", self.sell_accuracy_2h)
+            } else if self.sell_accuracy_1h >= self.sell_accuracy_30m {
+                ("1-hour", self.sell_accuracy_1h)
+            } else {
+                ("30-minute", self.sell_accuracy_30m)
+            };
+            out.push_str(&format!("🔴 Sell decisions perform best at {} timeframe ({:.1}% accuracy)\n", best_sell_timeframe.0, best_sell_timeframe.1));
+        }
+    
+        // Risk assessment
+        let max_loss = self.worst_decision_loss_30m.min(self.worst_decision_loss_1h).min(self.worst_decision_loss_2h);
+        let max_gain = self.best_decision_profit_30m.max(self.best_decision_profit_1h).max(self.best_decision_profit_2h);
+        out.push_str(&format!("⚖️  Risk/Reward: Max gain {:+.2}% | Max loss {:+.2}%\n", max_gain, max_loss));
+        out
+    }
+}
+
+/// Starting value, currency and outcome of the simulated portfolio, shared
+/// by the backtest reports. Flattened into them, so the JSON keeps its keys.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PortfolioSummary {
+    pub initial_portfolio_value: f64,
+    pub portfolio_currency: String,
+    pub final_portfolio_value_30m: f64,
+    pub final_portfolio_value_1h: f64,
+    pub final_portfolio_value_2h: f64,
+    pub portfolio_return_30m: f64,
+    pub portfolio_return_1h: f64,
+    pub portfolio_return_2h: f64,
+}
+
+impl PortfolioSummary {
+    /// Summary of a portfolio started with `initial_portfolio_value` in
+    /// `portfolio_currency` that ended at the 30m, 1h and 2h `final_values`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use botmarley::bot::backtest::PortfolioSummary;
+    /// use botmarley::config::Config;
+    /// let config = Config { initial_portfolio_value: 2500.0, portfolio_currency: "EUR".to_string(), ..Default::default() };
+    /// let summary = PortfolioSummary::new(&config, [2500.0, 2750.0, 2000.0]);
+    /// assert_eq!((summary.initial_portfolio_value, summary.portfolio_currency.as_str()), (2500.0, "EUR"));
+    /// assert_eq!((summary.portfolio_return_30m, summary.portfolio_return_1h, summary.portfolio_return_2h), (0.0, 10.0, -20.0));
+    /// // no starting capital doesn't divide by zero
+    /// let config = Config { initial_portfolio_value: 0.0, ..config };
+    /// assert_eq!(PortfolioSummary::new(&config, [0.0; 3]).portfolio_return_1h, 0.0);
+    /// ```
+    pub fn new(config: &Config, final_values: [f64; 3]) -> Self {
+        let initial = config.initial_portfolio_value;
+        let [final_30m, final_1h, final_2h] = final_values;
+        PortfolioSummary {
+            initial_portfolio_value: initial,
+            portfolio_currency: config.portfolio_currency.clone(),
+            final_portfolio_value_30m: final_30m,
+            final_portfolio_value_1h: final_1h,
+            final_portfolio_value_2h: final_2h,
+            portfolio_return_30m: safe_pct(final_30m - initial, initial),
+            portfolio_return_1h: safe_pct(final_1h - initial, initial),
+            portfolio_return_2h: safe_pct(final_2h - initial, initial),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioSimulation {
+    pub initial_value: f64,
+    pub current_value: f64,
+    pub current_position: String, // "cash", "crypto"
+    pub crypto_amount: f64,
+    pub cash_amount: f64,
+    pub transactions: Vec<Transaction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transaction {
+    pub timestamp: i64,
+    pub action: String, // "buy", "sell"
+    pub price: f64,
+    pub amount: f64,
+    pub value: f64,
+    pub portfolio_value_after: f64,
+}
+
+/// A finished backtest: the report and the three simulated portfolios it
+/// was built from.
+pub struct BacktestRun {
+    pub report: TestReport,
+    pub sim_30m: PortfolioSimulation,
+    pub sim_1h: PortfolioSimulation,
+    pub sim_2h: PortfolioSimulation,
+}
+
+impl BacktestRun {
+    /// Simulate the configured starting portfolio over the scored `results`
+    /// and aggregate them into a report.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use botmarley::bot::backtest::{BacktestRun, DecisionResult, TradingDecision};
+    /// use botmarley::config::Config;
+    /// let config = Config { initial_portfolio_value: 2500.0, portfolio_currency: "EUR".to_string(), ..Default::default() };
+    /// let buy = DecisionResult {
+    ///     price: 100.0,
+    ///     decision: TradingDecision { action: "buy".to_string(), ..Default::default() },
+    ///     actual_price_30m: Some(110.0),
+    ///     actual_price_1h: Some(110.0),
+    ///     actual_price_2h: Some(110.0),
+    ///     ..Default::default()
+    /// };
+    /// let run = BacktestRun::new("BTCUSDC", "2024-10-01", "2024-10-02", &config, vec![buy], BTreeMap::new());
+    /// assert_eq!(run.sim_1h.initial_value, 2500.0);
+    /// let portfolio = &run.report.portfolio;
+    /// assert_eq!((portfolio.initial_portfolio_value, portfolio.portfolio_currency.as_str()), (2500.0, "EUR"));
+    /// assert!((portfolio.final_portfolio_value_1h - 2750.0).abs() < 1e-9);
+    /// assert!((portfolio.portfolio_return_1h - 10.0).abs() < 1e-9);
+    /// ```
+    pub fn new(
+        symbol: &str,
+        test_period_from: &str,
+        test_period_to: &str,
+        config: &Config,
+        results: Vec<DecisionResult>,
+        indicator_performance: BTreeMap<String, IndicatorPerformance>,
+    ) -> Self {
+        let (sim_30m, sim_1h, sim_2h) = simulate_portfolio(&results, config.initial_portfolio_value, config.slippage_pct);
+
+        // Generate comprehensive report statistics
+        let mut buy_decisions = 0;
+        let mut sell_decisions = 0;
+        let mut hold_decisions = 0;
+        let mut correct_30m = 0;
+        let mut correct_1h = 0;
+        let mut correct_2h = 0;
+        let mut wrong_30m = 0;
+        let mut wrong_1h = 0;
+        let mut wrong_2h = 0;
+        let mut good_buy_30m = 0;
+        let mut good_buy_1h = 0;
+        let mut good_buy_2h = 0;
+        let mut bad_buy_30m = 0;
+        let mut bad_buy_1h = 0;
+        let mut bad_buy_2h = 0;
+        let mut good_sell_30m = 0;
+        let mut good_sell_1h = 0;
+        let mut good_sell_2h = 0;
+        let mut bad_sell_30m = 0;
+        let mut bad_sell_1h = 0;
+        let mut bad_sell_2h = 0;
+        let mut fatal_buy_30m = 0;
+        let mut fatal_buy_1h = 0;
+        let mut fatal_buy_2h = 0;
+        let mut fatal_sell_30m = 0;
+        let mut fatal_sell_1h = 0;
+        let mut fatal_sell_2h = 0;
+        let mut profitable_30m = 0;
+        let mut profitable_1h = 0;
+        let mut profitable_2h = 0;
+        let mut losing_30m = 0;
+        let mut losing_1h = 0;
+        let mut losing_2h = 0;
+        let mut total_pl_30m = 0.0;
+        let mut total_pl_1h = 0.0;
+        let mut total_pl_2h = 0.0;
+        let mut best_profit_30m = f64::NEG_INFINITY;
+        let mut best_profit_1h = f64::NEG_INFINITY;
+        let mut best_profit_2h = f64::NEG_INFINITY;
+        let mut worst_loss_30m = f64::INFINITY;
+        let mut worst_loss_1h = f64::INFINITY;
+        let mut worst_loss_2h = f64::INFINITY;
+
+        for result in &results {
+            match result.decision.action.as_str() {
+                "buy" => {
+                    buy_decisions += 1;
+                    if result.was_correct_30m.unwrap_or(false) { good_buy_30m += 1; } else { bad_buy_30m += 1; }
+                    if result.was_correct_1h.unwrap_or(false) { good_buy_1h += 1; } else { bad_buy_1h += 1; }
+                    if result.was_correct_2h.unwrap_or(false) { good_buy_2h += 1; } else { bad_buy_2h += 1; }
+            
+                    // Check for fatal buy decisions
+                    if let Some(pl) = result.profit_loss_30m && pl < -3.0 { fatal_buy_30m += 1; }
+                    if let Some(pl) = result.profit_loss_1h && pl < -3.0 { fatal_buy_1h += 1; }
+                    if let Some(pl) = result.profit_loss_2h && pl < -3.0 { fatal_buy_2h += 1; }
+                },
+                "sell" => {
+                    sell_decisions += 1;
+                    if result.was_correct_30m.unwrap_or(false) { good_sell_30m += 1; } else { bad_sell_30m += 1; }
+                    if result.was_correct_1h.unwrap_or(false) { good_sell_1h += 1; } else { bad_sell_1h += 1; }
+                    if result.was_correct_2h.unwrap_or(false) { good_sell_2h += 1; } else { bad_sell_2h += 1; }
+            
+                    // Check for fatal sell decisions
+                    if let Some(pl) = result.profit_loss_30m && pl < -3.0 { fatal_sell_30m += 1; }
+                    if let Some(pl) = result.profit_loss_1h && pl < -3.0 { fatal_sell_1h += 1; }
+                    if let Some(pl) = result.profit_loss_2h && pl < -3.0 { fatal_sell_2h += 1; }
+                },
+                "hold" => hold_decisions += 1,
+                _ => {}
+            }
+    
+            // Overall accuracy
+            if result.was_correct_30m.unwrap_or(false) { correct_30m += 1; } else { wrong_30m += 1; }
+            if result.was_correct_1h.unwrap_or(false) { correct_1h += 1; } else { wrong_1h += 1; }
+            if result.was_correct_2h.unwrap_or(false) { correct_2h += 1; } else { wrong_2h += 1; }
+    
+            // Profit/Loss tracking
+            if let Some(pl) = result.profit_loss_30m {
+                total_pl_30m += pl;
+                if pl > 0.0 { profitable_30m += 1; } else if pl < 0.0 { losing_30m += 1; }
+                if pl > best_profit_30m { best_profit_30m = pl; }
+                if pl < worst_loss_30m { worst_loss_30m = pl; }
+            }
+            if let Some(pl) = result.profit_loss_1h {
+                total_pl_1h += pl;
+                if pl > 0.0 { profitable_1h += 1; } else if pl < 0.0 { losing_1h += 1; }
+                if pl > best_profit_1h { best_profit_1h = pl; }
+                if pl < worst_loss_1h { worst_loss_1h = pl; }
+            }
+            if let Some(pl) = result.profit_loss_2h {
+                total_pl_2h += pl;
+                if pl > 0.0 { profitable_2h += 1; } else if pl < 0.0 { losing_2h += 1; }
+                if pl > best_profit_2h { best_profit_2h = pl; }
+                if pl < worst_loss_2h { worst_loss_2h = pl; }
+            }
+        }
+
+        let total_decisions = results.len();
+        let report = TestReport {
+            symbol: symbol.to_string(),
+            model: config.openai_model.clone(),
+            test_period_from: test_period_from.to_string(),
+            test_period_to: test_period_to.to_string(),
+            config: config.clone(),
+            total_decisions,
+            buy_decisions,
+            sell_decisions,
+            hold_decisions,
+            correct_predictions_30m: correct_30m,
+            correct_predictions_1h: correct_1h,
+            correct_predictions_2h: correct_2h,
+            wrong_predictions_30m: wrong_30m,
+            wrong_predictions_1h: wrong_1h,
+            wrong_predictions_2h: wrong_2h,
+            good_buy_decisions_30m: good_buy_30m,
+            good_buy_decisions_1h: good_buy_1h,
+            good_buy_decisions_2h: good_buy_2h,
+            bad_buy_decisions_30m: bad_buy_30m,
+            bad_buy_decisions_1h: bad_buy_1h,
+            bad_buy_decisions_2h: bad_buy_2h,
+            good_sell_decisions_30m: good_sell_30m,
+            good_sell_decisions_1h: good_sell_1h,
+            good_sell_decisions_2h: good_sell_2h,
+            bad_sell_decisions_30m: bad_sell_30m,
+            bad_sell_decisions_1h: bad_sell_1h,
+            bad_sell_decisions_2h: bad_sell_2h,
+            fatal_buy_decisions_30m: fatal_buy_30m,
+            fatal_buy_decisions_1h: fatal_buy_1h,
+            fatal_buy_decisions_2h: fatal_buy_2h,
+            fatal_sell_decisions_30m: fatal_sell_30m,
+            fatal_sell_decisions_1h: fatal_sell_1h,
+            fatal_sell_decisions_2h: fatal_sell_2h,
+            profitable_decisions_30m: profitable_30m,
+            profitable_decisions_1h: profitable_1h,
+            profitable_decisions_2h: profitable_2h,
+            losing_decisions_30m: losing_30m,
+            losing_decisions_1h: losing_1h,
+            losing_decisions_2h: losing_2h,
+            total_profit_loss_30m: total_pl_30m,
+            total_profit_loss_1h: total_pl_1h,
+            total_profit_loss_2h: total_pl_2h,
+            average_profit_per_decision_30m: if total_decisions > 0 { total_pl_30m / total_decisions as f64 } else { 0.0 },
+            average_profit_per_decision_1h: if total_decisions > 0 { total_pl_1h / total_decisions as f64 } else { 0.0 },
+            average_profit_per_decision_2h: if total_decisions > 0 { total_pl_2h / total_decisions as f64 } else { 0.0 },
+            best_decision_profit_30m: finite_or_zero(best_profit_30m),
+            best_decision_profit_1h: finite_or_zero(best_profit_1h),
+            best_decision_profit_2h: finite_or_zero(best_profit_2h),
+            worst_decision_loss_30m: finite_or_zero(worst_loss_30m),
+            worst_decision_loss_1h: finite_or_zero(worst_loss_1h),
+            worst_decision_loss_2h: finite_or_zero(worst_loss_2h),
+            accuracy_30m: safe_pct(correct_30m as f64, total_decisions as f64),
+            accuracy_1h: safe_pct(correct_1h as f64, total_decisions as f64),
+            accuracy_2h: safe_pct(correct_2h as f64, total_decisions as f64),
+            buy_accuracy_30m: safe_pct(good_buy_30m as f64, buy_decisions as f64),
+            buy_accuracy_1h: safe_pct(good_buy_1h as f64, buy_decisions as f64),
+            buy_accuracy_2h: safe_pct(good_buy_2h as f64, buy_decisions as f64),
+            sell_accuracy_30m: safe_pct(good_sell_30m as f64, sell_decisions as f64),
+            sell_accuracy_1h: safe_pct(good_sell_1h as f64, sell_decisions as f64),
+            sell_accuracy_2h: safe_pct(good_sell_2h as f64, sell_decisions as f64),
+            portfolio: PortfolioSummary::new(config, [sim_30m.current_value, sim_1h.current_value, sim_2h.current_value]),
+            indicator_performance,
+            results,
+        };
+
+        BacktestRun { report, sim_30m, sim_1h, sim_2h }
+    }
+}
+
+/// Replay `results` on three all-in portfolios starting at `initial_value`,
+/// valued at the 30m, 1h and 2h prices after each decision. Buys and sells
+/// fill `slippage_pct` percent worse than the decision price.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::backtest::{simulate_portfolio, DecisionResult, TradingDecision};
+/// let decision = |timestamp, action: &str, price, later| DecisionResult {
+///     timestamp,
+///     price,
+///     decision: TradingDecision { action: action.to_string(), ..Default::default() },
+///     actual_price_30m: Some(later),
+///     actual_price_1h: Some(later),
+///     actual_price_2h: Some(later),
+///     ..Default::default()
+/// };
+/// let results = [decision(0, "buy", 100.0, 105.0), decision(1, "hold", 105.0, 110.0), decision(2, "sell", 110.0, 108.0)];
+///
+/// let (_, exact, _) = simulate_portfolio(&results, 1000.0, 0.0);
+/// assert!((exact.current_value - 1100.0).abs() < 1e-9);
+/// let (_, slipped, _) = simulate_portfolio(&results, 1000.0, 0.5);
+/// assert!(slipped.current_value < exact.current_value);
+/// // bought at 100.5 and sold at 109.45
+/// assert!((slipped.current_value - 1000.0 / 100.5 * 109.45).abs() < 1e-9);
+/// assert_eq!(slipped.transactions.len(), 2);
+/// ```
+pub fn simulate_portfolio(results: &[DecisionResult], initial_value: f64, slippage_pct: f64) -> (PortfolioSimulation, PortfolioSimulation, PortfolioSimulation) {
+    let mut sim_30m = PortfolioSimulation {
+        initial_value,
+        current_value: initial_value,
+        current_position: "cash".to_string(),
+        crypto_amount: 0.0,
+        cash_amount: initial_value,
+        transactions: Vec::new(),
+    };
+    
+    let mut sim_1h = sim_30m.clone();
+    let mut sim_2h = sim_30m.clone();
+    
+    for result in results {
+        // 30-minute simulation
+        if let Some(future_price_30m) = result.actual_price_30m {
+            simulate_decision(&mut sim_30m, result, future_price_30m, result.timestamp, slippage_pct);
+        }
+        
+        // 1-hour simulation
+        if let Some(future_price_1h) = result.actual_price_1h {
+            simulate_decision(&mut sim_1h, result, future_price_1h, result.timestamp, slippage_pct);
+        }
+        
+        // 2-hour simulation
+        if let Some(future_price_2h) = result.actual_price_2h {
+            simulate_decision(&mut sim_2h, result, future_price_2h, result.timestamp, slippage_pct);
+        }
+    }
+    
+    (sim_30m, sim_1h, sim_2h)
+}
+
+/// Apply one decision to `portfolio`, which only switches between all cash
+/// and all crypto.
+pub fn simulate_decision(portfolio: &mut PortfolioSimulation, result: &DecisionResult, future_price: f64, timestamp: i64, slippage_pct: f64) {
+    match result.decision.action.as_str() {
+        "buy" if portfolio.current_position == "cash" && portfolio.cash_amount > 0.0 => {
+            // Buy crypto with all cash
+            let fill = fill_price(result.price, true, slippage_pct);
+            let crypto_bought = portfolio.cash_amount / fill;
+            portfolio.crypto_amount = crypto_bought;
+            portfolio.cash_amount = 0.0;
+            portfolio.current_position = "crypto".to_string();
+            
+            // Calculate portfolio value at future price
+            portfolio.current_value = portfolio.crypto_amount * future_price;
+            
+            portfolio.transactions.push(Transaction {
+                timestamp,
+                action: "buy".to_string(),
+                price: fill,
+                amount: crypto_bought,
+                value: portfolio.cash_amount,
+                portfolio_value_after: portfolio.current_value,
+            });
+        }
+        "sell" if portfolio.current_position == "crypto" && portfolio.crypto_amount > 0.0 => {
+            // Sell all crypto for cash
+            let fill = fill_price(result.price, false, slippage_pct);
+            portfolio.cash_amount = portfolio.crypto_amount * fill;
+            portfolio.crypto_amount = 0.0;
+            portfolio.current_position = "cash".to_string();
+            portfolio.current_value = portfolio.cash_amount;
+            
+            portfolio.transactions.push(Transaction {
+                timestamp,
+                action: "sell".to_string(),
+                price: fill,
+                amount: portfolio.crypto_amount,
+                value: portfolio.cash_amount,
+                portfolio_value_after: portfolio.current_value,
+            });
+        }
+        "hold" => {
+            // Update portfolio value based on current position
+            if portfolio.current_position == "crypto" {
+                portfolio.current_value = portfolio.crypto_amount * future_price;
+            } else {
+                portfolio.current_value = portfolio.cash_amount;
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use crate::bot::klines::Klines;
+
+/// One day of 5m candles
+pub const DEFAULT_CORRELATION_PERIOD: usize = 288;
+/// Pairs correlated above this are flagged as moving together
+pub const HIGH_CORRELATION: f64 = 0.8;
+
+pub type CorrelationMatrix = HashMap<(String, String), f64>;
+
+/// Candle-to-candle returns; a zero close yields no return for the next candle.
+pub fn returns(closes: &[f64]) -> Vec<f64> {
+    closes
+        .windows(2)
+        .filter(|w| w[0] != 0.0)
+        .map(|w| (w[1] - w[0]) / w[0])
+        .collect()
+}
+
+/// Pearson correlation of two equally long series, `None` when either is
+/// flat or there are fewer than 3 points. Longer series are cut to the
+/// shorter one's length, keeping their latest values.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::correlation::pearson;
+/// let a = [1.0, 2.0, 4.0, 3.0];
+/// assert!((pearson(&a, &[2.0, 4.0, 8.0, 6.0]).unwrap() - 1.0).abs() < 1e-12);
+/// assert!((pearson(&a, &[-1.0, -2.0, -4.0, -3.0]).unwrap() + 1.0).abs() < 1e-12);
+/// // zero variance
+/// assert_eq!(pearson(&a, &[5.0; 4]), None);
+/// // the extra leading values of the longer series are ignored
+/// assert!((pearson(&[100.0, -7.0, 2.0, 4.0, 3.0], &[2.0, 4.0, 3.0]).unwrap() - 1.0).abs() < 1e-12);
+/// assert_eq!(pearson(&[1.0, 2.0], &[1.0, 2.0]), None);
+/// ```
+pub fn pearson(a: &[f64], b: &[f64]) -> Option<f64> {
+    let n = a.len().min(b.len());
+    if n < 3 {
+        return None;
+    }
+    let (a, b) = (&a[a.len() - n..], &b[b.len() - n..]);
+    let mean_a = a.iter().sum::<f64>() / n as f64;
+    let mean_b = b.iter().sum::<f64>() / n as f64;
+
+    let (mut cov, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b.iter()) {
+        cov += (x - mean_a) * (y - mean_b);
+        var_a += (x - mean_a).powi(2);
+        var_b += (y - mean_b).powi(2);
+    }
+    if var_a == 0.0 || var_b == 0.0 {
+        return None;
+    }
+    Some((cov / (var_a.sqrt() * var_b.sqrt())).clamp(-1.0, 1.0))
+}
+
+/// Correlation of the last `period` returns of every pair with every other
+/// (and itself). Series are aligned on their latest candle.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use botmarley::bot::correlation::{compute_correlation_matrix, highly_correlated};
+/// let btc = vec![100.0, 102.0, 101.0, 104.0, 103.0, 107.0];
+/// let closes = HashMap::from([
+///     // same returns as BTC over a shorter history
+///     ("ETHUSDC".to_string(), vec![51.0, 50.5, 52.0, 51.5, 53.5]),
+///     ("BTCUSDC".to_string(), btc),
+///     ("USDTUSDC".to_string(), vec![1.0; 6]),
+/// ]);
+/// let matrix = compute_correlation_matrix(&closes, 10);
+/// let get = |a: &str, b: &str| matrix.get(&(a.to_string(), b.to_string())).copied();
+/// assert!((get("BTCUSDC", "BTCUSDC").unwrap() - 1.0).abs() < 1e-12);
+/// assert!((get("ETHUSDC", "ETHUSDC").unwrap() - 1.0).abs() < 1e-12);
+/// assert_eq!(get("BTCUSDC", "ETHUSDC"), get("ETHUSDC", "BTCUSDC"));
+/// assert!(get("BTCUSDC", "ETHUSDC").unwrap() > 0.99);
+/// // a flat pair has no correlation with anything, itself included
+/// assert_eq!(get("USDTUSDC", "USDTUSDC"), None);
+/// assert_eq!(get("BTCUSDC", "USDTUSDC"), None);
+/// assert_eq!(highly_correlated(&matrix).len(), 1);
+/// ```
+pub fn compute_correlation_matrix(closes: &HashMap<String, Vec<f64>>, period: usize) -> CorrelationMatrix {
+    let returns: HashMap<&String, Vec<f64>> = closes
+        .iter()
+        .map(|(pair, closes)| {
+            let r = returns(closes);
+            (pair, r[r.len().saturating_sub(period)..].to_vec())
+        })
+        .collect();
+
+    let mut matrix = CorrelationMatrix::new();
+    for (a, returns_a) in &returns {
+        for (b, returns_b) in &returns {
+            if let Some(c) = pearson(returns_a, returns_b) {
+                matrix.insert(((*a).clone(), (*b).clone()), c);
+            }
+        }
+    }
+    matrix
+}
+
+/// Distinct pairs correlated above `HIGH_CORRELATION`, strongest first.
+pub fn highly_correlated(matrix: &CorrelationMatrix) -> Vec<(String, String, f64)> {
+    let mut pairs: Vec<(String, String, f64)> = matrix
+        .iter()
+        .filter(|((a, b), c)| a < b && **c > HIGH_CORRELATION)
+        .map(|((a, b), c)| (a.clone(), b.clone(), *c))
+        .collect();
+    pairs.sort_by(|x, y| y.2.total_cmp(&x.2).then_with(|| x.0.cmp(&y.0)).then_with(|| x.1.cmp(&y.1)));
+    pairs
+}
+
+/// Readable matrix in `pairs` order, followed by the highly correlated pairs.
+pub fn format_correlation_matrix(pairs: &[String], matrix: &CorrelationMatrix) -> String {
+    let width = pairs.iter().map(String::len).max().unwrap_or(0).max(6);
+    let mut text = format!("{:<width$}", "", width = width);
+    for pair in pairs {
+        text.push_str(&format!(" {:>width$}", pair, width = width));
+    }
+    text.push('\n');
+    for a in pairs {
+        text.push_str(&format!("{:<width$}", a, width = width));
+        for b in pairs {
+            let cell = matrix
+                .get(&(a.clone(), b.clone()))
+                .map(|c| format!("{:+.2}", c))
+                .unwrap_or_else(|| "N/A".to_string());
+            text.push_str(&format!(" {:>width$}", cell, width = width));
+        }
+        text.push('\n');
+    }
+
+    let high = highly_correlated(matrix);
+    if high.is_empty() {
+        text.push_str(&format!("No pairs correlated above {:.1}\n", HIGH_CORRELATION));
+    } else {
+        text.push_str(&format!("Highly correlated (> {:.1}), avoid stacking positions:\n", HIGH_CORRELATION));
+        for (a, b, c) in high {
+            text.push_str(&format!("  {} / {}: {:+.2}\n", a, b, c));
+        }
+    }
+    text
+}
+
+/// Correlation matrix over the stored klines of `pairs`.
+pub async fn correlation_matrix(pairs: &[String], period: usize) -> color_eyre::Result<CorrelationMatrix> {
+    let mut closes = HashMap::new();
+    for pair in pairs {
+        let ohlc = Klines::new(pair.as_str().into()).get_ohlc().await?;
+        closes.insert(pair.clone(), ohlc.close);
+    }
+    Ok(compute_correlation_matrix(&closes, period))
+}
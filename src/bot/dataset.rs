@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use color_eyre::eyre::WrapErr;
+use serde::{Deserialize, Serialize};
+
+use crate::bot::indicators::IndicatorResult;
+
+/// One labelled example: what the model saw, what it decided and how that
+/// turned out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatasetRecord {
+    pub symbol: String,
+    /// Open time of the analyzed candle, in ms
+    pub timestamp: i64,
+    /// User message sent to the model
+    pub context: String,
+    /// Structured indicator values at the analyzed candle
+    pub features: Vec<IndicatorResult>,
+    pub decision: serde_json::Value,
+    /// Scored outcome of the decision, the label
+    pub outcome: serde_json::Value,
+}
+
+impl DatasetRecord {
+    pub fn new(
+        symbol: &str,
+        timestamp: i64,
+        context: &str,
+        features: Vec<IndicatorResult>,
+        decision: &impl Serialize,
+        outcome: &impl Serialize,
+    ) -> color_eyre::Result<Self> {
+        Ok(DatasetRecord {
+            symbol: symbol.to_string(),
+            timestamp,
+            context: context.to_string(),
+            features,
+            decision: serde_json::to_value(decision)?,
+            outcome: serde_json::to_value(outcome)?,
+        })
+    }
+}
+
+/// Appends scored decisions to `<dir>/<symbol>.jsonl`, one record per line,
+/// to build a fine-tuning or analysis dataset over time.
+///
+/// An empty directory disables the recorder.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::dataset::{DatasetRecord, DatasetRecorder};
+/// use botmarley::bot::indicators::IndicatorResult;
+/// let dir = std::env::temp_dir().join(format!("botmarley_datasets_{}", std::process::id()));
+/// let recorder = DatasetRecorder::new(dir.to_str().unwrap());
+/// let features = vec![IndicatorResult::new("rsi", "BTCUSDC").with_value("rsi", Some(28.5)).with_signal("bullish")];
+/// let decision = serde_json::json!({"action": "buy", "confidence": 0.8});
+/// let outcome = serde_json::json!({"was_correct_1h": true, "profit_loss_1h": 0.7});
+/// let record = DatasetRecord::new("BTCUSDC", 1_700_000_000_000, "Current price: 100", features, &decision, &outcome).unwrap();
+/// recorder.record(&record).unwrap();
+/// recorder.record(&record).unwrap();
+///
+/// let text = std::fs::read_to_string(dir.join("BTCUSDC.jsonl")).unwrap();
+/// assert_eq!(text.lines().count(), 2);
+/// let line: serde_json::Value = serde_json::from_str(text.lines().next().unwrap()).unwrap();
+/// assert_eq!(line["features"][0]["values"]["rsi"], 28.5);
+/// assert_eq!(line["decision"]["action"], "buy");
+/// assert_eq!(line["outcome"]["was_correct_1h"], true);
+/// std::fs::remove_dir_all(&dir).unwrap();
+///
+/// assert!(!DatasetRecorder::new("").is_enabled());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DatasetRecorder {
+    dir: Option<PathBuf>,
+}
+
+impl DatasetRecorder {
+    pub fn new(dir: &str) -> Self {
+        DatasetRecorder {
+            dir: (!dir.is_empty()).then(|| PathBuf::from(dir)),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.dir.is_some()
+    }
+
+    /// Append `record` to the file of its symbol, a no-op when disabled
+    pub fn record(&self, record: &DatasetRecord) -> color_eyre::Result<()> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        fs::create_dir_all(dir).wrap_err_with(|| format!("Failed to create {}", dir.display()))?;
+        let path = dir.join(format!("{}.jsonl", record.symbol));
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .wrap_err_with(|| format!("Failed to append to {}", path.display()))
+    }
+}
+
+/// How often an indicator's directional signal matched the later price move
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndicatorPerformance {
+    /// Bullish or bearish readings
+    pub signals: usize,
+    /// Readings followed by a move in their direction
+    pub correct: usize,
+}
+
+impl IndicatorPerformance {
+    pub fn accuracy_pct(&self) -> f64 {
+        if self.signals == 0 {
+            return 0.0;
+        }
+        self.correct as f64 / self.signals as f64 * 100.0
+    }
+}
+
+/// Per-indicator hit-rate over scored snapshots of `(indicator readings at
+/// decision time, percent price change afterwards)`. Only bullish and
+/// bearish signals count; neutral readings and flat moves never match.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::dataset::indicator_performance;
+/// use botmarley::bot::indicators::IndicatorResult;
+/// let reading = |name: &str, signal: &str| IndicatorResult::new(name, "BTCUSDC").with_signal(signal);
+/// let up = vec![reading("macd", "bullish"), reading("rsi", "bearish"), reading("stc", "neutral")];
+/// let down = vec![reading("macd", "bearish"), reading("rsi", "bearish")];
+/// let scored = [(up.as_slice(), 1.2), (down.as_slice(), -0.4), (up.as_slice(), 0.3)];
+/// let performance = indicator_performance(scored);
+/// assert_eq!((performance["macd"].signals, performance["macd"].correct), (3, 3));
+/// assert_eq!((performance["rsi"].signals, performance["rsi"].correct), (3, 1));
+/// assert!((performance["rsi"].accuracy_pct() - 100.0 / 3.0).abs() < 1e-9);
+/// assert!(!performance.contains_key("stc"));
+/// ```
+pub fn indicator_performance<'a>(scored: impl IntoIterator<Item = (&'a [IndicatorResult], f64)>) -> BTreeMap<String, IndicatorPerformance> {
+    let mut performance: BTreeMap<String, IndicatorPerformance> = BTreeMap::new();
+    for (readings, change_pct) in scored {
+        for reading in readings {
+            let correct = match reading.signal.as_deref() {
+                Some("bullish") => change_pct > 0.0,
+                Some("bearish") => change_pct < 0.0,
+                _ => continue,
+            };
+            let entry = performance.entry(reading.name.clone()).or_default();
+            entry.signals += 1;
+            entry.correct += correct as usize;
+        }
+    }
+    performance
+}
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+
+use crate::bot::indicators::atr::{true_range, wilder_smooth};
+use crate::bot::indicators::moving_averages::on_defined;
+
+/// Directional Movement Index values, aligned with the klines.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dmi {
+    pub plus_di: Vec<Option<f64>>,
+    pub minus_di: Vec<Option<f64>>,
+    pub adx: Vec<Option<f64>>,
+}
+
+/// Calculate +DI, -DI and ADX using Wilder's smoothing.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::adx::calculate_dmi;
+/// // every candle makes a higher high and a higher low
+/// let high: Vec<f64> = (0..40).map(|i| 101.0 + i as f64).collect();
+/// let low: Vec<f64> = (0..40).map(|i| 99.0 + i as f64).collect();
+/// let close: Vec<f64> = (0..40).map(|i| 100.0 + i as f64).collect();
+/// let dmi = calculate_dmi(&high, &low, &close, 14);
+/// assert_eq!(dmi.plus_di[12], None);
+/// assert!(dmi.adx[25].is_none() && dmi.adx[26].is_some());
+/// // no down moves at all: -DI stays at 0 and ADX reads a maximal trend
+/// assert_eq!(dmi.minus_di[39], Some(0.0));
+/// assert!(dmi.plus_di[39].unwrap() > 0.0);
+/// assert!((dmi.adx[39].unwrap() - 100.0).abs() < 1e-9);
+/// ```
+pub fn calculate_dmi(high: &[f64], low: &[f64], close: &[f64], period: usize) -> Dmi {
+    let len = high.len().min(low.len()).min(close.len());
+    let mut plus_dm = vec![0.0; len];
+    let mut minus_dm = vec![0.0; len];
+    for i in 1..len {
+        let up = high[i] - high[i - 1];
+        let down = low[i - 1] - low[i];
+        if up > down && up > 0.0 {
+            plus_dm[i] = up;
+        }
+        if down > up && down > 0.0 {
+            minus_dm[i] = down;
+        }
+    }
+
+    let tr = wilder_smooth(&true_range(high, low, close), period);
+    let plus = wilder_smooth(&plus_dm, period);
+    let minus = wilder_smooth(&minus_dm, period);
+
+    let mut plus_di = vec![None; len];
+    let mut minus_di = vec![None; len];
+    let mut dx = vec![None; len];
+    for i in 0..len {
+        if let (Some(tr), Some(p), Some(m)) = (tr[i], plus[i], minus[i])
+            && tr > 0.0
+        {
+            let pdi = 100.0 * p / tr;
+            let mdi = 100.0 * m / tr;
+            plus_di[i] = Some(pdi);
+            minus_di[i] = Some(mdi);
+            let sum = pdi + mdi;
+            dx[i] = Some(if sum > 0.0 { 100.0 * (pdi - mdi).abs() / sum } else { 0.0 });
+        }
+    }
+
+    let adx = on_defined(&dx, |values| wilder_smooth(values, period));
+
+    Dmi { plus_di, minus_di, adx }
+}
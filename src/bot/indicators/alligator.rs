@@ -0,0 +1,247 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::bot::indicators::IndicatorResult;
+use crate::bot::indicators::atr::wilder_smooth;
+use crate::bot::klines::{Klines, KlinesOHLC};
+
+/// Bill Williams Alligator: three smoothed moving averages of the median
+/// price, each shifted forward by a number of bars.
+pub struct Alligator {
+    pub klines: Arc<Mutex<Klines>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlligatorState {
+    /// Lines intertwined, no trend
+    Sleeping,
+    /// Lips > teeth > jaw
+    FeedingUp,
+    /// Lips < teeth < jaw
+    FeedingDown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FractalKind {
+    /// Local high, the middle of five candles
+    Up,
+    /// Local low, the middle of five candles
+    Down,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fractal {
+    pub index: usize,
+    pub kind: FractalKind,
+    pub price: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlligatorResult {
+    pub jaw: Vec<Option<f64>>,
+    pub teeth: Vec<Option<f64>>,
+    pub lips: Vec<Option<f64>>,
+    pub fractals: Vec<Fractal>,
+    pub state: AlligatorState,
+}
+
+/// Smoothed moving average (SMMA), aligned with the input.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::alligator::smma;
+/// // seeded with (1 + 2) / 2, then prev + (x - prev) / 2
+/// assert_eq!(smma(&[1.0, 2.0, 3.0, 4.0], 2), vec![None, Some(1.5), Some(2.25), Some(3.125)]);
+/// ```
+pub fn smma(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    wilder_smooth(values, period)
+}
+
+/// Shift a series forward by `shift` bars: the value at index `i` is the
+/// value computed at `i - shift`. Values shifted past the end are dropped.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::alligator::shift_forward;
+/// let series = [Some(1.0), Some(2.0), Some(3.0), Some(4.0)];
+/// assert_eq!(shift_forward(&series, 2), vec![None, None, Some(1.0), Some(2.0)]);
+/// assert_eq!(shift_forward(&series, 0), series.to_vec());
+/// ```
+pub fn shift_forward(series: &[Option<f64>], shift: usize) -> Vec<Option<f64>> {
+    (0..series.len())
+        .map(|i| if i >= shift { series[i - shift] } else { None })
+        .collect()
+}
+
+/// Jaw, teeth and lips, aligned with the klines.
+pub type AlligatorLines = (Vec<Option<f64>>, Vec<Option<f64>>, Vec<Option<f64>>);
+
+/// Jaw (13, shift 8), teeth (8, shift 5) and lips (5, shift 3).
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::alligator::calculate_alligator;
+/// use botmarley::bot::klines::KlinesOHLC;
+/// let ohlc = KlinesOHLC {
+///     time: (0..30).collect(),
+///     open: vec![100.0; 30],
+///     high: vec![102.0; 30],
+///     low: vec![98.0; 30],
+///     close: vec![100.0; 30],
+///     volume: vec![1.0; 30],
+///     count: 30,
+/// };
+/// let (jaw, teeth, lips) = calculate_alligator(&ohlc);
+/// // each line starts `period - 1 + shift` candles in, at the median price
+/// assert_eq!((jaw[19], jaw[20]), (None, Some(100.0)));
+/// assert_eq!((teeth[11], teeth[12]), (None, Some(100.0)));
+/// assert_eq!((lips[6], lips[7]), (None, Some(100.0)));
+/// ```
+pub fn calculate_alligator(ohlc: &KlinesOHLC) -> AlligatorLines {
+    let median: Vec<f64> = ohlc
+        .high
+        .iter()
+        .zip(ohlc.low.iter())
+        .map(|(h, l)| (h + l) / 2.0)
+        .collect();
+
+    let jaw = shift_forward(&smma(&median, 13), 8);
+    let teeth = shift_forward(&smma(&median, 8), 5);
+    let lips = shift_forward(&smma(&median, 5), 3);
+    (jaw, teeth, lips)
+}
+
+/// Five-candle fractals. The last two candles can't be confirmed yet.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::alligator::{detect_fractals, Fractal, FractalKind};
+/// // the peak at index 1 lacks two candles before it, the one at 4 is confirmed
+/// let high = [1.0, 5.0, 2.0, 3.0, 6.0, 3.0, 2.0];
+/// assert_eq!(detect_fractals(&high, &[0.0; 7]), vec![Fractal { index: 4, kind: FractalKind::Up, price: 6.0 }]);
+/// // the lowest low is the last candle, which can't be confirmed yet
+/// let low = [5.0, 4.0, 1.0, 3.0, 4.0, 5.0, 0.0];
+/// assert_eq!(detect_fractals(&[10.0; 7], &low), vec![Fractal { index: 2, kind: FractalKind::Down, price: 1.0 }]);
+/// assert!(detect_fractals(&[1.0, 2.0, 5.0, 2.0], &[0.0; 4]).is_empty());
+/// ```
+pub fn detect_fractals(high: &[f64], low: &[f64]) -> Vec<Fractal> {
+    let len = high.len().min(low.len());
+    let mut fractals = Vec::new();
+    if len < 5 {
+        return fractals;
+    }
+
+    for i in 2..len - 2 {
+        let neighbours = [i - 2, i - 1, i + 1, i + 2];
+        if neighbours.iter().all(|&j| high[i] > high[j]) {
+            fractals.push(Fractal { index: i, kind: FractalKind::Up, price: high[i] });
+        }
+        if neighbours.iter().all(|&j| low[i] < low[j]) {
+            fractals.push(Fractal { index: i, kind: FractalKind::Down, price: low[i] });
+        }
+    }
+    fractals
+}
+
+/// Feeding when the three lines are fanned out in order, sleeping otherwise.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::alligator::{alligator_state, AlligatorState};
+/// assert_eq!(alligator_state(Some(1.0), Some(2.0), Some(3.0)), AlligatorState::FeedingUp);
+/// assert_eq!(alligator_state(Some(3.0), Some(2.0), Some(1.0)), AlligatorState::FeedingDown);
+/// assert_eq!(alligator_state(Some(2.0), Some(1.0), Some(3.0)), AlligatorState::Sleeping);
+/// assert_eq!(alligator_state(None, Some(2.0), Some(3.0)), AlligatorState::Sleeping);
+/// ```
+pub fn alligator_state(jaw: Option<f64>, teeth: Option<f64>, lips: Option<f64>) -> AlligatorState {
+    match (jaw, teeth, lips) {
+        (Some(j), Some(t), Some(l)) if l > t && t > j => AlligatorState::FeedingUp,
+        (Some(j), Some(t), Some(l)) if l < t && t < j => AlligatorState::FeedingDown,
+        _ => AlligatorState::Sleeping,
+    }
+}
+
+impl AlligatorResult {
+    pub fn from_ohlc(ohlc: &KlinesOHLC) -> Self {
+        let (jaw, teeth, lips) = calculate_alligator(ohlc);
+        let fractals = detect_fractals(&ohlc.high, &ohlc.low);
+        let state = alligator_state(
+            jaw.last().copied().flatten(),
+            teeth.last().copied().flatten(),
+            lips.last().copied().flatten(),
+        );
+        AlligatorResult {
+            jaw,
+            teeth,
+            lips,
+            fractals,
+            state,
+        }
+    }
+
+    /// Jaw, teeth and lips on candle `index`, `None` while warming up.
+    fn lines_at(&self, index: usize) -> (Option<f64>, Option<f64>, Option<f64>) {
+        let at = |line: &[Option<f64>]| line.get(index).copied().flatten();
+        (at(&self.jaw), at(&self.teeth), at(&self.lips))
+    }
+
+    pub fn indicator_result(&self, symbol: &str) -> IndicatorResult {
+        self.indicator_result_at(symbol, self.jaw.len().saturating_sub(1))
+    }
+
+    pub fn indicator_result_at(&self, symbol: &str, index: usize) -> IndicatorResult {
+        let (jaw, teeth, lips) = self.lines_at(index);
+        let signal = match alligator_state(jaw, teeth, lips) {
+            AlligatorState::Sleeping => "neutral",
+            AlligatorState::FeedingUp => "bullish",
+            AlligatorState::FeedingDown => "bearish",
+        };
+        IndicatorResult::new("alligator", symbol)
+            .with_value("jaw", jaw)
+            .with_value("teeth", teeth)
+            .with_value("lips", lips)
+            .with_signal(signal)
+    }
+
+    pub fn summary(&self) -> String {
+        self.summary_at(self.jaw.len().saturating_sub(1))
+    }
+
+    pub fn summary_at(&self, index: usize) -> String {
+        let (jaw, teeth, lips) = self.lines_at(index);
+        let state = match alligator_state(jaw, teeth, lips) {
+            AlligatorState::Sleeping => "sleeping (lines intertwined, no clear trend)",
+            AlligatorState::FeedingUp => "feeding up (lips > teeth > jaw, uptrend)",
+            AlligatorState::FeedingDown => "feeding down (lips < teeth < jaw, downtrend)",
+        };
+        let mut lines = vec![format!("Alligator: {}", state)];
+        if let (Some(j), Some(t), Some(l)) = (jaw, teeth, lips) {
+            lines.push(format!("Jaw: {:.4}, Teeth: {:.4}, Lips: {:.4}", j, t, l));
+        }
+        // a fractal needs two later candles to be confirmed
+        let mut confirmed = self.fractals.iter().rev().filter(|f| f.index + 2 <= index);
+        let last_up = confirmed.clone().find(|f| f.kind == FractalKind::Up);
+        let last_down = confirmed.find(|f| f.kind == FractalKind::Down);
+        if let Some(f) = last_up {
+            lines.push(format!("Last up fractal: {:.4} (candle {})", f.price, f.index));
+        }
+        if let Some(f) = last_down {
+            lines.push(format!("Last down fractal: {:.4} (candle {})", f.price, f.index));
+        }
+        lines.join("\n")
+    }
+}
+
+impl Alligator {
+    pub async fn calculate(&mut self) -> color_eyre::Result<AlligatorResult> {
+        let ohlc = self.klines.lock().await.get_ohlc().await?;
+        Ok(AlligatorResult::from_ohlc(&ohlc))
+    }
+}
@@ -0,0 +1,58 @@
+/// True range of every candle. The first candle has no previous close, so its
+/// true range is just `high - low`.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::atr::true_range;
+/// // the second candle gaps up from a close of 10, the third gaps down
+/// let tr = true_range(&[11.0, 14.0, 9.0], &[9.0, 13.0, 7.0], &[10.0, 13.5, 8.0]);
+/// assert_eq!(tr, vec![2.0, 4.0, 6.5]);
+/// ```
+pub fn true_range(high: &[f64], low: &[f64], close: &[f64]) -> Vec<f64> {
+    let len = high.len().min(low.len()).min(close.len());
+    (0..len)
+        .map(|i| {
+            let range = high[i] - low[i];
+            if i == 0 {
+                range
+            } else {
+                let prev_close = close[i - 1];
+                range
+                    .max((high[i] - prev_close).abs())
+                    .max((low[i] - prev_close).abs())
+            }
+        })
+        .collect()
+}
+
+/// Wilder's smoothing: seeded with the plain average of the first `period`
+/// values, then `prev + (x - prev) / period`. Aligned with the input.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::atr::wilder_smooth;
+/// // seed (2 + 4) / 2 = 3, then 3 + (7 - 3) / 2 = 5
+/// assert_eq!(wilder_smooth(&[2.0, 4.0, 7.0], 2), vec![None, Some(3.0), Some(5.0)]);
+/// assert_eq!(wilder_smooth(&[2.0], 2), vec![None]);
+/// ```
+pub fn wilder_smooth(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut out = vec![None; values.len()];
+    if period == 0 || values.len() < period {
+        return out;
+    }
+
+    let mut prev = values[..period].iter().sum::<f64>() / period as f64;
+    out[period - 1] = Some(prev);
+    for i in period..values.len() {
+        prev += (values[i] - prev) / period as f64;
+        out[i] = Some(prev);
+    }
+    out
+}
+
+/// Average True Range (Wilder), aligned with the klines.
+pub fn calculate_atr(high: &[f64], low: &[f64], close: &[f64], period: usize) -> Vec<Option<f64>> {
+    wilder_smooth(&true_range(high, low, close), period)
+}
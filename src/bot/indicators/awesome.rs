@@ -0,0 +1,199 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::bot::indicators::{series_until, IndicatorResult};
+use crate::bot::indicators::moving_averages::{last_defined, on_defined, sma};
+use crate::bot::klines::{Klines, KlinesOHLC};
+
+/// Bill Williams' Awesome Oscillator with its Accelerator Oscillator.
+pub struct Awesome {
+    pub klines: Arc<Mutex<Klines>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ZeroCross {
+    Bullish,
+    Bearish,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Saucer {
+    /// AO above zero, one falling bar followed by a rising one
+    Bullish,
+    /// AO below zero, one rising bar followed by a falling one
+    Bearish,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AwesomeResult {
+    pub ao: Vec<Option<f64>>,
+    pub ac: Vec<Option<f64>>,
+    /// Zero-line cross on the latest candle
+    pub zero_cross: Option<ZeroCross>,
+    /// Saucer completed on the latest candle
+    pub saucer: Option<Saucer>,
+}
+
+/// AO: SMA(5) minus SMA(34) of the median price, aligned with the klines.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::awesome::{calculate_ac, calculate_ao};
+/// use botmarley::bot::klines::KlinesOHLC;
+/// // median price i: SMA(5) is i - 2, SMA(34) is i - 16.5
+/// let median: Vec<f64> = (0..50).map(|i| i as f64).collect();
+/// let ohlc = KlinesOHLC {
+///     time: (0..50).collect(),
+///     open: median.clone(),
+///     high: median.iter().map(|m| m + 3.0).collect(),
+///     low: median.iter().map(|m| m - 3.0).collect(),
+///     close: median.clone(),
+///     volume: vec![1.0; 50],
+///     count: 50,
+/// };
+/// let ao = calculate_ao(&ohlc);
+/// assert_eq!((ao[32], ao[33]), (None, Some(14.5)));
+/// // a constant AO has no acceleration, AC needs 4 more candles for its SMA(5)
+/// let ac = calculate_ac(&ohlc);
+/// assert_eq!((ac[36], ac[37]), (None, Some(0.0)));
+/// ```
+pub fn calculate_ao(ohlc: &KlinesOHLC) -> Vec<Option<f64>> {
+    let median: Vec<f64> = ohlc
+        .high
+        .iter()
+        .zip(ohlc.low.iter())
+        .map(|(h, l)| (h + l) / 2.0)
+        .collect();
+
+    sma(&median, 5)
+        .into_iter()
+        .zip(sma(&median, 34))
+        .map(|(fast, slow)| Some(fast? - slow?))
+        .collect()
+}
+
+/// AC: AO minus its own SMA(5).
+pub fn calculate_ac(ohlc: &KlinesOHLC) -> Vec<Option<f64>> {
+    let ao = calculate_ao(ohlc);
+    let ao_sma = on_defined(&ao, |values| sma(values, 5));
+    ao.iter()
+        .zip(ao_sma.iter())
+        .map(|(ao, avg)| Some((*ao)? - (*avg)?))
+        .collect()
+}
+
+fn last_three(ao: &[Option<f64>]) -> Option<(f64, f64, f64)> {
+    match ao {
+        [.., Some(a), Some(b), Some(c)] => Some((*a, *b, *c)),
+        _ => None,
+    }
+}
+
+/// AO crossing the zero line on the latest candle.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::awesome::{detect_zero_cross, ZeroCross};
+/// assert_eq!(detect_zero_cross(&[Some(-2.0), Some(-1.0), Some(0.5)]), Some(ZeroCross::Bullish));
+/// assert_eq!(detect_zero_cross(&[Some(2.0), Some(0.0), Some(-0.5)]), Some(ZeroCross::Bearish));
+/// assert_eq!(detect_zero_cross(&[Some(1.0), Some(2.0), Some(3.0)]), None);
+/// assert_eq!(detect_zero_cross(&[None, Some(-1.0), Some(1.0)]), None);
+/// ```
+pub fn detect_zero_cross(ao: &[Option<f64>]) -> Option<ZeroCross> {
+    let (_, prev, current) = last_three(ao)?;
+    if prev <= 0.0 && current > 0.0 {
+        Some(ZeroCross::Bullish)
+    } else if prev >= 0.0 && current < 0.0 {
+        Some(ZeroCross::Bearish)
+    } else {
+        None
+    }
+}
+
+/// Saucer completed on the latest candle, all three bars on the same side of zero.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::awesome::{detect_saucer, Saucer};
+/// assert_eq!(detect_saucer(&[Some(3.0), Some(2.0), Some(2.5)]), Some(Saucer::Bullish));
+/// assert_eq!(detect_saucer(&[Some(-3.0), Some(-2.0), Some(-2.5)]), Some(Saucer::Bearish));
+/// // the dip crossed zero, not a saucer
+/// assert_eq!(detect_saucer(&[Some(1.0), Some(-0.5), Some(0.5)]), None);
+/// // still falling
+/// assert_eq!(detect_saucer(&[Some(3.0), Some(2.0), Some(1.0)]), None);
+/// ```
+pub fn detect_saucer(ao: &[Option<f64>]) -> Option<Saucer> {
+    let (a, b, c) = last_three(ao)?;
+    if a > 0.0 && b > 0.0 && c > 0.0 && b < a && c > b {
+        Some(Saucer::Bullish)
+    } else if a < 0.0 && b < 0.0 && c < 0.0 && b > a && c < b {
+        Some(Saucer::Bearish)
+    } else {
+        None
+    }
+}
+
+impl AwesomeResult {
+    pub fn from_ohlc(ohlc: &KlinesOHLC) -> Self {
+        let ao = calculate_ao(ohlc);
+        let ac = calculate_ac(ohlc);
+        AwesomeResult {
+            zero_cross: detect_zero_cross(&ao),
+            saucer: detect_saucer(&ao),
+            ao,
+            ac,
+        }
+    }
+
+    pub fn indicator_result(&self, symbol: &str) -> IndicatorResult {
+        self.indicator_result_at(symbol, self.ao.len().saturating_sub(1))
+    }
+
+    pub fn indicator_result_at(&self, symbol: &str, index: usize) -> IndicatorResult {
+        let ao = last_defined(series_until(&self.ao, index));
+        let result = IndicatorResult::new("awesome_oscillator", symbol)
+            .with_value("ao", ao)
+            .with_value("ac", last_defined(series_until(&self.ac, index)));
+        match ao {
+            Some(v) if v > 0.0 => result.with_signal("bullish"),
+            Some(v) if v < 0.0 => result.with_signal("bearish"),
+            Some(_) => result.with_signal("neutral"),
+            None => result,
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        self.summary_at(self.ao.len().saturating_sub(1))
+    }
+
+    pub fn summary_at(&self, index: usize) -> String {
+        let ao = series_until(&self.ao, index);
+        let (Some(current), Some(ac)) = (last_defined(ao), last_defined(series_until(&self.ac, index))) else {
+            return "Awesome Oscillator: not enough data (needs 38 candles)".to_string();
+        };
+        let mut lines = vec![format!("Awesome Oscillator: {:+.4}, Accelerator: {:+.4}", current, ac)];
+        match detect_zero_cross(ao) {
+            Some(ZeroCross::Bullish) => lines.push("AO crossed above zero (bullish momentum shift)".to_string()),
+            Some(ZeroCross::Bearish) => lines.push("AO crossed below zero (bearish momentum shift)".to_string()),
+            None => {}
+        }
+        match detect_saucer(ao) {
+            Some(Saucer::Bullish) => lines.push("Bullish saucer: AO dipped and turned up above zero".to_string()),
+            Some(Saucer::Bearish) => lines.push("Bearish saucer: AO bounced and turned down below zero".to_string()),
+            None => {}
+        }
+        lines.join("\n")
+    }
+}
+
+impl Awesome {
+    pub async fn calculate(&mut self) -> color_eyre::Result<AwesomeResult> {
+        let ohlc = self.klines.lock().await.get_ohlc().await?;
+        Ok(AwesomeResult::from_ohlc(&ohlc))
+    }
+}
@@ -0,0 +1,163 @@
+use std::sync::Arc;
+
+use financial_indicators::bollinger::bollinger_bands;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::bot::indicators::{series_until, IndicatorResult};
+use crate::bot::indicators::moving_averages::last_defined;
+use crate::bot::klines::Klines;
+
+/// Candles the bandwidth is compared against when looking for a squeeze
+pub const SQUEEZE_LOOKBACK: usize = 120;
+
+/// Bollinger bands with %B and bandwidth.
+pub struct Bollinger {
+    pub klines: Arc<Mutex<Klines>>,
+    pub period: usize,
+    pub k: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BollingerResult {
+    pub period: usize,
+    pub k: f64,
+    pub upper: Vec<Option<f64>>,
+    pub middle: Vec<Option<f64>>,
+    pub lower: Vec<Option<f64>>,
+    pub percent_b: Vec<Option<f64>>,
+    pub bandwidth: Vec<Option<f64>>,
+    /// Bandwidth at its lowest of the last `SQUEEZE_LOOKBACK` candles
+    pub squeeze: bool,
+}
+
+/// %B, `(price - lower) / (upper - lower)`: 0 at the lower band, 1 at the
+/// upper, outside 0..1 when price closes outside the bands. `None` during
+/// warm-up and when the bands collapse.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::bollinger::calculate_percent_b;
+/// let upper = [Some(110.0), Some(110.0), Some(110.0), None];
+/// let lower = [Some(90.0), Some(90.0), Some(90.0), None];
+/// let pb = calculate_percent_b(&[90.0, 110.0, 115.0, 100.0], &upper, &lower);
+/// assert_eq!(pb, vec![Some(0.0), Some(1.0), Some(1.25), None]);
+/// ```
+pub fn calculate_percent_b(close: &[f64], upper: &[Option<f64>], lower: &[Option<f64>]) -> Vec<Option<f64>> {
+    close
+        .iter()
+        .zip(upper.iter().zip(lower.iter()))
+        .map(|(c, (u, l))| match (u, l) {
+            (Some(u), Some(l)) if u > l => Some((c - l) / (u - l)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Bandwidth, `(upper - lower) / middle`; narrow bands give small values.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::bollinger::calculate_bandwidth;
+/// let wide = calculate_bandwidth(&[Some(120.0)], &[Some(100.0)], &[Some(80.0)]);
+/// let narrow = calculate_bandwidth(&[Some(101.0)], &[Some(100.0)], &[Some(99.0)]);
+/// assert_eq!(wide, vec![Some(0.4)]);
+/// assert_eq!(narrow, vec![Some(0.02)]);
+/// ```
+pub fn calculate_bandwidth(upper: &[Option<f64>], middle: &[Option<f64>], lower: &[Option<f64>]) -> Vec<Option<f64>> {
+    upper
+        .iter()
+        .zip(middle.iter().zip(lower.iter()))
+        .map(|(u, (m, l))| match (u, m, l) {
+            (Some(u), Some(m), Some(l)) if *m != 0.0 => Some((u - l) / m),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Latest bandwidth is the lowest of the last `SQUEEZE_LOOKBACK` defined values.
+pub fn is_squeeze(bandwidth: &[Option<f64>]) -> bool {
+    let recent: Vec<f64> = bandwidth.iter().rev().flatten().take(SQUEEZE_LOOKBACK).copied().collect();
+    match recent.first() {
+        Some(current) if recent.len() >= SQUEEZE_LOOKBACK => recent.iter().all(|bw| current <= bw),
+        _ => false,
+    }
+}
+
+/// Where %B puts the price relative to the bands.
+pub fn percent_b_label(percent_b: f64) -> &'static str {
+    if percent_b > 1.0 {
+        "above upper band"
+    } else if percent_b < 0.0 {
+        "below lower band"
+    } else {
+        "inside bands"
+    }
+}
+
+impl BollingerResult {
+    pub fn from_close(close: &[f64], period: usize, k: f64) -> Self {
+        let (upper, middle, lower) = bollinger_bands(close, period, k);
+        let percent_b = calculate_percent_b(close, &upper, &lower);
+        let bandwidth = calculate_bandwidth(&upper, &middle, &lower);
+        BollingerResult {
+            period,
+            k,
+            squeeze: is_squeeze(&bandwidth),
+            upper,
+            middle,
+            lower,
+            percent_b,
+            bandwidth,
+        }
+    }
+
+    pub fn indicator_result(&self, symbol: &str) -> IndicatorResult {
+        self.indicator_result_at(symbol, self.percent_b.len().saturating_sub(1))
+    }
+
+    pub fn indicator_result_at(&self, symbol: &str, index: usize) -> IndicatorResult {
+        let percent_b = last_defined(series_until(&self.percent_b, index));
+        let signal = match percent_b {
+            Some(pb) if pb < 0.0 => "bullish",
+            Some(pb) if pb > 1.0 => "bearish",
+            _ => "neutral",
+        };
+        IndicatorResult::new("bollinger", symbol)
+            .with_value("upper", last_defined(series_until(&self.upper, index)))
+            .with_value("middle", last_defined(series_until(&self.middle, index)))
+            .with_value("lower", last_defined(series_until(&self.lower, index)))
+            .with_value("percent_b", percent_b)
+            .with_value("bandwidth", last_defined(series_until(&self.bandwidth, index)))
+            .with_signal(signal)
+    }
+
+    pub fn summary(&self) -> String {
+        self.summary_at(self.percent_b.len().saturating_sub(1))
+    }
+
+    pub fn summary_at(&self, index: usize) -> String {
+        let bandwidth = series_until(&self.bandwidth, index);
+        let (Some(percent_b), Some(current_bandwidth)) = (last_defined(series_until(&self.percent_b, index)), last_defined(bandwidth)) else {
+            return format!("Bollinger ({}, {}): not enough data", self.period, self.k);
+        };
+        format!(
+            "Bollinger ({}, {}): %B {:.2} ({}), bandwidth {:.4}{}",
+            self.period,
+            self.k,
+            percent_b,
+            percent_b_label(percent_b),
+            current_bandwidth,
+            if is_squeeze(bandwidth) { ", SQUEEZE" } else { "" }
+        )
+    }
+}
+
+impl Bollinger {
+    pub async fn calculate(&mut self) -> color_eyre::Result<BollingerResult> {
+        let ohlc = self.klines.lock().await.get_ohlc().await?;
+        Ok(BollingerResult::from_close(&ohlc.close, self.period, self.k))
+    }
+}
@@ -0,0 +1,133 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::bot::indicators::{series_until, IndicatorResult};
+use crate::bot::indicators::moving_averages::{last_defined, sma};
+use crate::bot::klines::{Klines, KlinesOHLC};
+
+/// Candles the smoothed BOP must keep its sign for to count as sustained
+pub const SUSTAINED_CANDLES: usize = 3;
+
+/// Balance of Power: `(close - open) / (high - low)`, smoothed.
+pub struct Bop {
+    pub klines: Arc<Mutex<Klines>>,
+    pub smoothing: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Control {
+    Buyers,
+    Sellers,
+    /// Sign flipped within the last `SUSTAINED_CANDLES` candles
+    Contested,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BopResult {
+    pub smoothing: usize,
+    pub values: Vec<Option<f64>>,
+    pub control: Control,
+}
+
+/// Raw BOP of one candle; zero-range candles (no trades, flat) count as 0.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::bop::balance_of_power;
+/// // closing up on half the range is +0.5, the mirror image is -0.5
+/// assert_eq!(balance_of_power(100.0, 104.0, 100.0, 102.0), 0.5);
+/// assert_eq!(balance_of_power(102.0, 104.0, 100.0, 100.0), -0.5);
+/// // a flat candle would divide by zero
+/// assert_eq!(balance_of_power(100.0, 100.0, 100.0, 100.0), 0.0);
+/// ```
+pub fn balance_of_power(open: f64, high: f64, low: f64, close: f64) -> f64 {
+    let range = high - low;
+    if range <= 0.0 { 0.0 } else { (close - open) / range }
+}
+
+/// SMA of the raw BOP over `smoothing` candles, aligned with the klines.
+pub fn calculate_bop(ohlc: &KlinesOHLC, smoothing: usize) -> Vec<Option<f64>> {
+    let raw: Vec<f64> = (0..ohlc.close.len())
+        .map(|i| balance_of_power(ohlc.open[i], ohlc.high[i], ohlc.low[i], ohlc.close[i]))
+        .collect();
+    sma(&raw, smoothing.max(1))
+}
+
+/// Who held control over the last `SUSTAINED_CANDLES` smoothed values.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::bop::{control, Control};
+/// assert_eq!(control(&[Some(-0.2), Some(0.1), Some(0.3), Some(0.2)]), Control::Buyers);
+/// assert_eq!(control(&[Some(-0.1), Some(-0.3), Some(-0.2)]), Control::Sellers);
+/// assert_eq!(control(&[Some(0.1), Some(-0.1), Some(0.2)]), Control::Contested);
+/// // still warming up
+/// assert_eq!(control(&[None, Some(0.1), Some(0.2)]), Control::Contested);
+/// ```
+pub fn control(values: &[Option<f64>]) -> Control {
+    let recent: Vec<f64> = values.iter().rev().take(SUSTAINED_CANDLES).flatten().copied().collect();
+    if recent.len() < SUSTAINED_CANDLES {
+        Control::Contested
+    } else if recent.iter().all(|v| *v > 0.0) {
+        Control::Buyers
+    } else if recent.iter().all(|v| *v < 0.0) {
+        Control::Sellers
+    } else {
+        Control::Contested
+    }
+}
+
+impl BopResult {
+    pub fn from_ohlc(ohlc: &KlinesOHLC, smoothing: usize) -> Self {
+        let values = calculate_bop(ohlc, smoothing);
+        BopResult {
+            smoothing,
+            control: control(&values),
+            values,
+        }
+    }
+
+    pub fn indicator_result(&self, symbol: &str) -> IndicatorResult {
+        self.indicator_result_at(symbol, self.values.len().saturating_sub(1))
+    }
+
+    pub fn indicator_result_at(&self, symbol: &str, index: usize) -> IndicatorResult {
+        let values = series_until(&self.values, index);
+        let signal = match control(values) {
+            Control::Buyers => "bullish",
+            Control::Sellers => "bearish",
+            Control::Contested => "neutral",
+        };
+        IndicatorResult::new("bop", symbol)
+            .with_value("bop", last_defined(values))
+            .with_signal(signal)
+    }
+
+    pub fn summary(&self) -> String {
+        self.summary_at(self.values.len().saturating_sub(1))
+    }
+
+    pub fn summary_at(&self, index: usize) -> String {
+        let values = series_until(&self.values, index);
+        let Some(bop) = last_defined(values) else {
+            return format!("Balance of Power ({}): not enough data", self.smoothing);
+        };
+        let control = match control(values) {
+            Control::Buyers => "buyers in control",
+            Control::Sellers => "sellers in control",
+            Control::Contested => "no sustained control",
+        };
+        format!("Balance of Power ({}): {:+.3}, {}", self.smoothing, bop, control)
+    }
+}
+
+impl Bop {
+    pub async fn calculate(&mut self) -> color_eyre::Result<BopResult> {
+        let ohlc = self.klines.lock().await.get_ohlc().await?;
+        Ok(BopResult::from_ohlc(&ohlc, self.smoothing))
+    }
+}
@@ -0,0 +1,129 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::bot::indicators::IndicatorResult;
+use crate::bot::indicators::atr::true_range;
+use crate::bot::indicators::moving_averages::last_defined;
+use crate::bot::klines::{Klines, KlinesOHLC};
+
+/// Above this the market is consolidating (Fibonacci 61.8)
+pub const CHOP_RANGING: f64 = 61.8;
+/// Below this the market is trending (Fibonacci 38.2)
+pub const CHOP_TRENDING: f64 = 38.2;
+
+/// Choppiness Index: tells trending markets (low) from ranging ones (high).
+pub struct Choppiness {
+    pub klines: Arc<Mutex<Klines>>,
+    pub period: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MarketRegime {
+    Trending,
+    Ranging,
+    Transitional,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChoppinessResult {
+    pub period: usize,
+    pub values: Vec<Option<f64>>,
+}
+
+/// `100 * log10(sum(TR, n) / (max(high, n) - min(low, n))) / log10(n)`,
+/// aligned with the klines and clamped to 0..100.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::choppiness::{calculate_choppiness, market_regime, MarketRegime};
+/// use botmarley::bot::klines::KlinesOHLC;
+/// let ohlc = |close: Vec<f64>, range: f64| KlinesOHLC {
+///     time: (0..close.len() as i64).collect(),
+///     open: close.clone(),
+///     high: close.iter().map(|c| c + range).collect(),
+///     low: close.iter().map(|c| c - range).collect(),
+///     volume: vec![1.0; close.len()],
+///     count: close.len(),
+///     close,
+/// };
+/// let rally = calculate_choppiness(&ohlc((0..30).map(|i| 100.0 + 2.0 * i as f64).collect(), 1.0), 14);
+/// let chop = calculate_choppiness(&ohlc((0..30).map(|i| 100.0 + (i % 2) as f64).collect(), 1.0), 14);
+/// assert_eq!(rally[12], None);
+/// assert_eq!(market_regime(rally[29].unwrap()), MarketRegime::Trending);
+/// assert_eq!(market_regime(chop[29].unwrap()), MarketRegime::Ranging);
+/// assert!(rally.iter().chain(&chop).flatten().all(|v| (0.0..=100.0).contains(v)));
+///
+/// // a gap from the previous close inflates the true range past the window's range
+/// let gapped = calculate_choppiness(&ohlc(vec![0.0, 100.0, 100.0], 0.5), 2);
+/// assert_eq!(gapped[2], Some(100.0));
+/// // a window without any range has no reading
+/// assert!(calculate_choppiness(&ohlc(vec![100.0; 20], 0.0), 14).iter().all(Option::is_none));
+/// ```
+pub fn calculate_choppiness(ohlc: &KlinesOHLC, period: usize) -> Vec<Option<f64>> {
+    let tr = true_range(&ohlc.high, &ohlc.low, &ohlc.close);
+    let mut out = vec![None; tr.len()];
+    if period < 2 || tr.len() < period {
+        return out;
+    }
+
+    let log_period = (period as f64).log10();
+    for (i, slot) in out.iter_mut().enumerate().skip(period - 1) {
+        let window = i + 1 - period..=i;
+        let tr_sum: f64 = tr[window.clone()].iter().sum();
+        let highest = ohlc.high[window.clone()].iter().cloned().fold(f64::MIN, f64::max);
+        let lowest = ohlc.low[window].iter().cloned().fold(f64::MAX, f64::min);
+        let range = highest - lowest;
+        if range > 0.0 && tr_sum > 0.0 {
+            *slot = Some((100.0 * (tr_sum / range).log10() / log_period).clamp(0.0, 100.0));
+        }
+    }
+    out
+}
+
+pub fn market_regime(chop: f64) -> MarketRegime {
+    if chop > CHOP_RANGING {
+        MarketRegime::Ranging
+    } else if chop < CHOP_TRENDING {
+        MarketRegime::Trending
+    } else {
+        MarketRegime::Transitional
+    }
+}
+
+impl ChoppinessResult {
+    pub fn indicator_result(&self, symbol: &str) -> IndicatorResult {
+        let current = last_defined(&self.values);
+        let result = IndicatorResult::new("choppiness", symbol).with_value("choppiness", current);
+        match current.map(market_regime) {
+            Some(MarketRegime::Trending) => result.with_signal("trending"),
+            Some(MarketRegime::Ranging) => result.with_signal("ranging"),
+            Some(MarketRegime::Transitional) => result.with_signal("neutral"),
+            None => result,
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let Some(chop) = last_defined(&self.values) else {
+            return format!("Choppiness Index ({}): not enough data", self.period);
+        };
+        let regime = match market_regime(chop) {
+            MarketRegime::Ranging => "consolidation, favour mean reversion and avoid trend trades",
+            MarketRegime::Trending => "trending, favour breakout and trend-following setups",
+            MarketRegime::Transitional => "no clear regime",
+        };
+        format!("Choppiness Index ({}): {:.1} ({})", self.period, chop, regime)
+    }
+}
+
+impl Choppiness {
+    pub async fn calculate(&mut self) -> color_eyre::Result<ChoppinessResult> {
+        let ohlc = self.klines.lock().await.get_ohlc().await?;
+        Ok(ChoppinessResult {
+            period: self.period,
+            values: calculate_choppiness(&ohlc, self.period),
+        })
+    }
+}
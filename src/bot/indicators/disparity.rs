@@ -0,0 +1,95 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::bot::indicators::{series_until, IndicatorResult};
+use crate::bot::indicators::moving_averages::{last_defined, sma};
+use crate::bot::klines::Klines;
+
+/// Price more than this percent away from its average is overextended
+pub const DISPARITY_EXTREME: f64 = 2.0;
+
+/// Disparity Index: percent distance of the close from its SMA.
+pub struct Disparity {
+    pub klines: Arc<Mutex<Klines>>,
+    pub ma_period: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisparityResult {
+    pub ma_period: usize,
+    pub values: Vec<Option<f64>>,
+}
+
+/// `(close - ma) / ma * 100`, aligned with the closes.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::disparity::calculate_disparity;
+/// // SMA(4) of the last candle is 100, the close 106
+/// let disparity = calculate_disparity(&[98.0, 100.0, 96.0, 106.0], 4);
+/// assert_eq!(disparity, vec![None, None, None, Some(6.0)]);
+/// ```
+pub fn calculate_disparity(closes: &[f64], ma_period: usize) -> Vec<Option<f64>> {
+    closes
+        .iter()
+        .zip(sma(closes, ma_period))
+        .map(|(close, ma)| match ma {
+            Some(ma) if ma != 0.0 => Some((close - ma) / ma * 100.0),
+            _ => None,
+        })
+        .collect()
+}
+
+impl DisparityResult {
+    pub fn from_close(closes: &[f64], ma_period: usize) -> Self {
+        DisparityResult {
+            ma_period,
+            values: calculate_disparity(closes, ma_period),
+        }
+    }
+
+    /// Overextension is a mean-reversion reading: far above the average is
+    /// bearish, far below bullish.
+    pub fn indicator_result(&self, symbol: &str) -> IndicatorResult {
+        self.indicator_result_at(symbol, self.values.len().saturating_sub(1))
+    }
+
+    pub fn indicator_result_at(&self, symbol: &str, index: usize) -> IndicatorResult {
+        let current = last_defined(series_until(&self.values, index));
+        let result = IndicatorResult::new("disparity", symbol).with_value("disparity", current);
+        match current {
+            Some(v) if v > DISPARITY_EXTREME => result.with_signal("bearish"),
+            Some(v) if v < -DISPARITY_EXTREME => result.with_signal("bullish"),
+            Some(_) => result.with_signal("neutral"),
+            None => result,
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        self.summary_at(self.values.len().saturating_sub(1))
+    }
+
+    pub fn summary_at(&self, index: usize) -> String {
+        let Some(disparity) = last_defined(series_until(&self.values, index)) else {
+            return format!("Disparity Index ({}): not enough data", self.ma_period);
+        };
+        let reading = if disparity > DISPARITY_EXTREME {
+            "overextended above the average, pullback risk"
+        } else if disparity < -DISPARITY_EXTREME {
+            "overextended below the average, bounce potential"
+        } else {
+            "near the average"
+        };
+        format!("Disparity Index ({}): {:+.2}% ({})", self.ma_period, disparity, reading)
+    }
+}
+
+impl Disparity {
+    pub async fn calculate(&mut self) -> color_eyre::Result<DisparityResult> {
+        let close = self.klines.lock().await.get_ohlc().await?.close;
+        Ok(DisparityResult::from_close(&close, self.ma_period))
+    }
+}
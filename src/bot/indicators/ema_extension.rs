@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+
+use crate::bot::indicators::{series_until, IndicatorResult};
+use crate::bot::indicators::moving_averages::ema;
+use crate::bot::klines::Klines;
+
+/// EMAs price extension is measured from
+pub const EXTENSION_PERIODS: [usize; 4] = [20, 50, 100, 200];
+/// Standard deviations from the mean distance that count as extreme
+pub const EXTENSION_SIGMAS: f64 = 2.0;
+
+/// Distance of price from one EMA
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EmaExtension {
+    pub period: usize,
+    /// `(close - ema) / ema * 100` on the latest candle
+    pub distance_pct: f64,
+    /// Beyond `EXTENSION_SIGMAS` of this EMA's historical distance
+    pub extreme: bool,
+}
+
+/// Latest percent distance of the close from each EMA, flagged when it is
+/// further than `EXTENSION_SIGMAS` standard deviations from the mean
+/// distance over the history. EMAs without enough history are left out.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::ema_extension::calculate_ema_extension;
+/// let wave: Vec<f64> = (0..300).map(|i| 100.0 + (i as f64 / 5.0).sin()).collect();
+/// let normal = calculate_ema_extension(&wave, &[20]);
+/// assert_eq!(normal.len(), 1);
+/// assert!(!normal[0].extreme);
+///
+/// let mut spike = wave.clone();
+/// spike.extend([104.0, 108.0, 112.0]);
+/// let extended = calculate_ema_extension(&spike, &[20, 500]);
+/// assert_eq!(extended.len(), 1);
+/// assert!(extended[0].extreme && extended[0].distance_pct > 5.0);
+/// ```
+pub fn calculate_ema_extension(closes: &[f64], periods: &[usize]) -> Vec<EmaExtension> {
+    extension_at(&extension_series(closes, periods), closes.len().saturating_sub(1))
+}
+
+/// For each of `periods`, the `EmaExtension` every candle would have as the
+/// latest one, aligned with the closes. The mean and variance of the earlier
+/// distances are kept running, so the whole history takes a single pass.
+pub fn extension_series(closes: &[f64], periods: &[usize]) -> Vec<Vec<Option<EmaExtension>>> {
+    periods
+        .iter()
+        .map(|&period| {
+            // Welford's running mean and variance of the distances so far
+            let (mut count, mut mean, mut m2) = (0usize, 0.0, 0.0);
+            closes
+                .iter()
+                .zip(ema(closes, period))
+                .map(|(close, ema)| {
+                    let distance_pct = ema.filter(|e| *e != 0.0).map(|e| (close - e) / e * 100.0)?;
+                    let extension = (count >= 2).then(|| EmaExtension {
+                        period,
+                        distance_pct,
+                        extreme: (distance_pct - mean).abs() > EXTENSION_SIGMAS * (m2 / count as f64).sqrt(),
+                    });
+                    count += 1;
+                    let delta = distance_pct - mean;
+                    mean += delta / count as f64;
+                    m2 += delta * (distance_pct - mean);
+                    extension
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// `calculate_ema_extension` for the closes up to candle `index`, read from
+/// an `extension_series` of a longer history.
+pub fn extension_at(series: &[Vec<Option<EmaExtension>>], index: usize) -> Vec<EmaExtension> {
+    series
+        .iter()
+        .filter_map(|line| series_until(line, index).iter().rev().find_map(|e| *e))
+        .collect()
+}
+
+/// Mean-reversion reading of a set of extensions: stretched above the EMAs
+/// is bearish, below bullish.
+pub fn extension_indicator_result(extensions: &[EmaExtension], symbol: &str) -> IndicatorResult {
+    let mut result = IndicatorResult::new("ema_extension", symbol);
+    for extension in extensions {
+        result = result.with_value(&format!("ema{}_distance_pct", extension.period), Some(extension.distance_pct));
+    }
+    let extreme: Vec<&EmaExtension> = extensions.iter().filter(|e| e.extreme).collect();
+    if extreme.is_empty() {
+        return if extensions.is_empty() { result } else { result.with_signal("neutral") };
+    }
+    if extreme.iter().all(|e| e.distance_pct > 0.0) {
+        result.with_signal("bearish")
+    } else if extreme.iter().all(|e| e.distance_pct < 0.0) {
+        result.with_signal("bullish")
+    } else {
+        result.with_signal("neutral")
+    }
+}
+
+pub fn extension_summary(extensions: &[EmaExtension]) -> String {
+    if extensions.is_empty() {
+        return "EMA extension: not enough data".to_string();
+    }
+    let parts: Vec<String> = extensions
+        .iter()
+        .map(|e| format!("EMA{} {:+.2}%{}", e.period, e.distance_pct, if e.extreme { " (extreme)" } else { "" }))
+        .collect();
+    let mut summary = format!("EMA extension: {}", parts.join(", "));
+    if extensions.iter().any(|e| e.extreme) {
+        summary.push_str(" - overextended, mean-reversion candidate, avoid chasing");
+    }
+    summary
+}
+
+/// `(period, distance %, extreme)` for `EXTENSION_PERIODS` over the stored
+/// klines of `symbol`.
+pub async fn ema_extension(symbol: &str) -> color_eyre::Result<Vec<(usize, f64, bool)>> {
+    let close = Klines::new(symbol.into()).get_ohlc().await?.close;
+    Ok(calculate_ema_extension(&close, &EXTENSION_PERIODS)
+        .into_iter()
+        .map(|e| (e.period, e.distance_pct, e.extreme))
+        .collect())
+}
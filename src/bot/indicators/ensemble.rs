@@ -0,0 +1,209 @@
+use financial_indicators::bollinger::bollinger_bands;
+use financial_indicators::kdj::KDJ;
+use financial_indicators::macd::MACD;
+use financial_indicators::rsi::relative_strength_index;
+use serde::{Deserialize, Serialize};
+use smart_default::SmartDefault;
+use tracing::instrument;
+
+use crate::bot::indicators::IndicatorResult;
+use crate::bot::indicators::adx::calculate_dmi;
+use crate::bot::indicators::moving_averages::{last_defined, sma};
+use crate::bot::klines::{Klines, KlinesOHLC};
+use crate::symbol::Symbol;
+
+/// Relative weight of every signal in the composite score.
+#[derive(Debug, Clone, Serialize, Deserialize, SmartDefault)]
+pub struct EnsembleWeights {
+    #[default = 1.0]
+    pub rsi: f64,
+    #[default = 1.0]
+    pub macd: f64,
+    #[default = 1.0]
+    pub bollinger: f64,
+    #[default = 0.75]
+    pub stochastic: f64,
+    #[default = 1.25]
+    pub trend: f64,
+    #[default = 0.5]
+    pub volume: f64,
+}
+
+/// A single normalized signal (-1 bearish .. +1 bullish) and its weight.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignalComponent {
+    pub name: String,
+    pub signal: f64,
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnsembleSignal {
+    pub symbol: String,
+    /// Composite score clamped to -1..+1
+    pub score: f64,
+    pub components: Vec<SignalComponent>,
+}
+
+impl EnsembleSignal {
+    /// Direction of the composite score, with a +-0.2 neutral band.
+    pub fn bias(&self) -> &'static str {
+        if self.score > 0.2 {
+            "bullish"
+        } else if self.score < -0.2 {
+            "bearish"
+        } else {
+            "neutral"
+        }
+    }
+
+    /// Short human readable summary with the contributing breakdown.
+    pub fn summary(&self) -> String {
+        let mut lines = vec![format!(
+            "Ensemble signal for {}: {:+.2} ({})",
+            self.symbol,
+            self.score,
+            self.bias()
+        )];
+        for c in &self.components {
+            lines.push(format!(
+                "  {}: {:+.2} (weight {:.2})",
+                c.name, c.signal, c.weight
+            ));
+        }
+        lines.join("\n")
+    }
+
+    /// Score plus every component signal as structured output.
+    pub fn indicator_result(&self) -> IndicatorResult {
+        self.components
+            .iter()
+            .fold(
+                IndicatorResult::new("ensemble", &self.symbol).with_value("score", Some(self.score)),
+                |result, c| result.with_value(&c.name, Some(c.signal)),
+            )
+            .with_signal(self.bias())
+    }
+}
+
+/// Weighted average of the components, clamped to -1..+1.
+/// Components with a non-positive weight are ignored.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::ensemble::{weighted_score, SignalComponent};
+/// let component = |signal: f64, weight: f64| SignalComponent { name: "x".to_string(), signal, weight };
+/// // (1.0 * 3 + -1.0 * 1) / 4
+/// assert_eq!(weighted_score(&[component(1.0, 3.0), component(-1.0, 1.0)]), 0.5);
+/// // out of range signals count as +-1, zero and negative weights are skipped
+/// assert_eq!(weighted_score(&[component(5.0, 1.0), component(-1.0, 0.0), component(-1.0, -2.0)]), 1.0);
+/// assert_eq!(weighted_score(&[component(-3.0, 1.0), component(f64::NAN, 1.0)]), -1.0);
+/// assert_eq!(weighted_score(&[]), 0.0);
+/// ```
+pub fn weighted_score(components: &[SignalComponent]) -> f64 {
+    let (sum, total_weight) = components
+        .iter()
+        .filter(|c| c.weight > 0.0 && c.signal.is_finite())
+        .fold((0.0, 0.0), |(sum, w), c| {
+            (sum + c.signal.clamp(-1.0, 1.0) * c.weight, w + c.weight)
+        });
+    if total_weight == 0.0 {
+        return 0.0;
+    }
+    (sum / total_weight).clamp(-1.0, 1.0)
+}
+
+/// Build the ensemble from already loaded OHLC data.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::ensemble::{ensemble_from_ohlc, EnsembleWeights};
+/// use botmarley::bot::klines::KlinesOHLC;
+/// let close: Vec<f64> = (0..60).map(|i| 100.0 + i as f64).collect();
+/// let ohlc = KlinesOHLC {
+///     time: (0..60).collect(),
+///     open: close.iter().map(|c| c - 0.5).collect(),
+///     high: close.iter().map(|c| c + 1.0).collect(),
+///     low: close.iter().map(|c| c - 1.0).collect(),
+///     volume: vec![10.0; 60],
+///     close,
+///     count: 60,
+/// };
+/// let signal = ensemble_from_ohlc("BTC/USDC".to_string(), &ohlc, &EnsembleWeights::default());
+/// assert!((-1.0..=1.0).contains(&signal.score));
+/// assert!(signal.components.iter().all(|c| (-1.0..=1.0).contains(&c.signal)));
+/// // a steady rally: +DI leads, so the trend component is bullish
+/// let trend = signal.components.iter().find(|c| c.name == "trend").unwrap();
+/// assert!(trend.signal > 0.0 && trend.weight == 1.25);
+/// ```
+pub fn ensemble_from_ohlc(symbol: String, ohlc: &KlinesOHLC, weights: &EnsembleWeights) -> EnsembleSignal {
+    let mut components = Vec::new();
+    let mut push = |name: &str, signal: Option<f64>, weight: f64| {
+        if let Some(signal) = signal {
+            components.push(SignalComponent {
+                name: name.to_string(),
+                signal: signal.clamp(-1.0, 1.0),
+                weight,
+            });
+        }
+    };
+
+    // RSI: oversold is bullish, overbought is bearish
+    let rsi = last_defined(&relative_strength_index(&ohlc.close, 14));
+    push("rsi", rsi.map(|r| (50.0 - r) / 50.0), weights.rsi);
+
+    // MACD: histogram relative to the size of both lines
+    let macd = MACD::new(&ohlc.close, 12, 26, 9).last().map(|m| {
+        let scale = m.macd.abs() + m.signal.abs();
+        if scale > 0.0 { m.histogram / scale } else { 0.0 }
+    });
+    push("macd", macd, weights.macd);
+
+    // Bollinger: position inside the bands, lower band is bullish
+    let (upper, _, lower) = bollinger_bands(&ohlc.close, 20, 2.0);
+    let bollinger = match (last_defined(&upper), last_defined(&lower), ohlc.close.last()) {
+        (Some(u), Some(l), Some(c)) if u > l => Some(1.0 - 2.0 * (c - l) / (u - l)),
+        _ => None,
+    };
+    push("bollinger", bollinger, weights.bollinger);
+
+    // Stochastic (KDJ %K): oversold is bullish
+    let stochastic = KDJ::new(&ohlc.high, &ohlc.low, &ohlc.close, 9)
+        .last()
+        .map(|kdj| (50.0 - kdj.k) / 50.0);
+    push("stochastic", stochastic, weights.stochastic);
+
+    // Trend: DI direction weighted by ADX strength
+    let dmi = calculate_dmi(&ohlc.high, &ohlc.low, &ohlc.close, 14);
+    let trend = match (
+        last_defined(&dmi.plus_di),
+        last_defined(&dmi.minus_di),
+        last_defined(&dmi.adx),
+    ) {
+        (Some(p), Some(m), Some(adx)) => Some((p - m).signum() * (adx / 50.0).min(1.0)),
+        _ => None,
+    };
+    push("trend", trend, weights.trend);
+
+    // Volume confluence: above-average volume confirms the last candle direction
+    let avg_volume = last_defined(&sma(&ohlc.volume, 20));
+    let volume = match (avg_volume, ohlc.volume.last(), ohlc.open.last(), ohlc.close.last()) {
+        (Some(avg), Some(v), Some(o), Some(c)) if avg > 0.0 => {
+            Some((c - o).signum() * (v / avg - 1.0).clamp(0.0, 1.0))
+        }
+        _ => None,
+    };
+    push("volume", volume, weights.volume);
+
+    let score = weighted_score(&components);
+    EnsembleSignal { symbol, score, components }
+}
+
+/// Composite -1..+1 score for a symbol using the default weights.
+#[instrument]
+pub async fn compute_ensemble_signal(symbol: Symbol) -> color_eyre::Result<EnsembleSignal> {
+    let ohlc = Klines::new(symbol.clone()).get_ohlc().await?;
+    Ok(ensemble_from_ohlc(symbol.to_string(), &ohlc, &EnsembleWeights::default()))
+}
@@ -0,0 +1,201 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::bot::indicators::{series_until, IndicatorResult};
+use crate::bot::indicators::moving_averages::last_defined;
+use crate::bot::klines::{Klines, KlinesOHLC};
+
+/// Crossovers beyond this Fisher value count as reversals
+pub const FISHER_EXTREME: f64 = 1.5;
+
+/// Ehlers' Fisher Transform of the median price.
+pub struct Fisher {
+    pub klines: Arc<Mutex<Klines>>,
+    pub period: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FisherReversal {
+    /// Fisher crossed above its trigger while deeply negative
+    Bullish,
+    /// Fisher crossed below its trigger while strongly positive
+    Bearish,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FisherResult {
+    pub period: usize,
+    pub fisher: Vec<Option<f64>>,
+    /// Fisher shifted by one candle
+    pub trigger: Vec<Option<f64>>,
+    /// Reversal on the latest candle
+    pub reversal: Option<FisherReversal>,
+}
+
+/// Position of `price` within `low..high` rescaled to -1..1, clamped to
+/// +-0.999 so the transform stays finite. A flat window maps to 0.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::fisher::normalize;
+/// assert_eq!(normalize(15.0, 10.0, 20.0), 0.0);
+/// assert!((normalize(12.5, 10.0, 20.0) + 0.5).abs() < 1e-12);
+/// // the window extremes are clamped short of +-1
+/// assert_eq!(normalize(20.0, 10.0, 20.0), 0.999);
+/// assert_eq!(normalize(10.0, 10.0, 20.0), -0.999);
+/// assert_eq!(normalize(10.0, 10.0, 10.0), 0.0);
+/// ```
+pub fn normalize(price: f64, low: f64, high: f64) -> f64 {
+    if high <= low {
+        return 0.0;
+    }
+    (2.0 * (price - low) / (high - low) - 1.0).clamp(-0.999, 0.999)
+}
+
+/// Fisher line and its trigger, aligned with the klines.
+///
+/// Each normalized median price is smoothed as `0.33 * x + 0.67 * previous`
+/// before the transform, as in Ehlers' original.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::fisher::calculate_fisher;
+/// use botmarley::bot::klines::KlinesOHLC;
+/// // steady climb: each median is the top of its window
+/// let closes: Vec<f64> = (0..40).map(|i| 100.0 + i as f64).collect();
+/// let ohlc = KlinesOHLC {
+///     time: (0..40).collect(), open: closes.clone(), high: closes.clone(), low: closes.clone(),
+///     volume: vec![1.0; 40], count: 40, close: closes,
+/// };
+/// let (fisher, trigger) = calculate_fisher(&ohlc, 10);
+/// assert!(fisher[8].is_none() && fisher[9].is_some());
+/// assert_eq!(trigger[10], fisher[9]);
+/// // each step moves the smoothed value a third of the way: 0.33, then 0.33 + 0.67 * 0.33
+/// let transform = |v: f64| 0.5 * ((1.0 + v) / (1.0 - v)).ln();
+/// let (v1, v2) = (0.33 * 0.999, 0.33 * 0.999 + 0.67 * 0.33 * 0.999);
+/// assert!((fisher[9].unwrap() - transform(v1)).abs() < 1e-9);
+/// assert!((fisher[10].unwrap() - (transform(v2) + 0.5 * transform(v1))).abs() < 1e-9);
+/// // well short of the 0.999 clamp after two candles
+/// assert!(fisher[10].unwrap() < 1.5);
+/// ```
+pub fn calculate_fisher(ohlc: &KlinesOHLC, period: usize) -> (Vec<Option<f64>>, Vec<Option<f64>>) {
+    let median: Vec<f64> = ohlc
+        .high
+        .iter()
+        .zip(ohlc.low.iter())
+        .map(|(h, l)| (h + l) / 2.0)
+        .collect();
+
+    let mut fisher = vec![None; median.len()];
+    if period == 0 || median.len() < period {
+        return (fisher.clone(), fisher);
+    }
+
+    let mut value = 0.0;
+    let mut previous = 0.0;
+    for i in period - 1..median.len() {
+        let window = &median[i + 1 - period..=i];
+        let low = window.iter().copied().fold(f64::INFINITY, f64::min);
+        let high = window.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        // Ehlers smooths the normalized price before the transform
+        value = (0.33 * normalize(median[i], low, high) + 0.67 * value).clamp(-0.999, 0.999);
+        let current = 0.5 * ((1.0 + value) / (1.0 - value)).ln() + 0.5 * previous;
+        fisher[i] = Some(current);
+        previous = current;
+    }
+
+    let trigger = (0..fisher.len())
+        .map(|i| if i == 0 { None } else { fisher[i - 1] })
+        .collect();
+    (fisher, trigger)
+}
+
+/// Fisher/trigger crossover between the last two candles, only beyond `extreme`.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::fisher::{detect_reversal, FisherReversal};
+/// // deeply negative Fisher crossing up through its trigger
+/// let fisher = [Some(-2.0), Some(-1.6)];
+/// let trigger = [Some(-1.8), Some(-2.0)];
+/// assert_eq!(detect_reversal(&fisher, &trigger, 1.5), Some(FisherReversal::Bullish));
+/// // the same cross near zero is no reversal
+/// assert_eq!(detect_reversal(&[Some(-0.5), Some(-0.1)], &[Some(-0.3), Some(-0.5)], 1.5), None);
+/// // strongly positive Fisher crossing down
+/// assert_eq!(detect_reversal(&[Some(2.0), Some(1.7)], &[Some(1.9), Some(2.0)], 1.5), Some(FisherReversal::Bearish));
+/// // no cross
+/// assert_eq!(detect_reversal(&[Some(2.0), Some(2.2)], &[Some(1.9), Some(2.0)], 1.5), None);
+/// assert_eq!(detect_reversal(&[None, Some(2.2)], &[Some(1.9), Some(2.0)], 1.5), None);
+/// ```
+pub fn detect_reversal(fisher: &[Option<f64>], trigger: &[Option<f64>], extreme: f64) -> Option<FisherReversal> {
+    match (fisher, trigger) {
+        ([.., Some(f0), Some(f1)], [.., Some(t0), Some(t1)]) => {
+            if f0 <= t0 && f1 > t1 && *f0 < -extreme {
+                Some(FisherReversal::Bullish)
+            } else if f0 >= t0 && f1 < t1 && *f0 > extreme {
+                Some(FisherReversal::Bearish)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+impl FisherResult {
+    pub fn from_ohlc(ohlc: &KlinesOHLC, period: usize) -> Self {
+        let (fisher, trigger) = calculate_fisher(ohlc, period);
+        FisherResult {
+            period,
+            reversal: detect_reversal(&fisher, &trigger, FISHER_EXTREME),
+            fisher,
+            trigger,
+        }
+    }
+
+    pub fn indicator_result(&self, symbol: &str) -> IndicatorResult {
+        self.indicator_result_at(symbol, self.fisher.len().saturating_sub(1))
+    }
+
+    pub fn indicator_result_at(&self, symbol: &str, index: usize) -> IndicatorResult {
+        let (fisher, trigger) = (series_until(&self.fisher, index), series_until(&self.trigger, index));
+        let result = IndicatorResult::new("fisher", symbol)
+            .with_value("fisher", last_defined(fisher))
+            .with_value("trigger", last_defined(trigger));
+        match detect_reversal(fisher, trigger, FISHER_EXTREME) {
+            Some(FisherReversal::Bullish) => result.with_signal("bullish"),
+            Some(FisherReversal::Bearish) => result.with_signal("bearish"),
+            None => result.with_signal("neutral"),
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        self.summary_at(self.fisher.len().saturating_sub(1))
+    }
+
+    pub fn summary_at(&self, index: usize) -> String {
+        let (fisher, trigger) = (series_until(&self.fisher, index), series_until(&self.trigger, index));
+        let (Some(current), Some(current_trigger)) = (last_defined(fisher), last_defined(trigger)) else {
+            return format!("Fisher Transform ({}): not enough data", self.period);
+        };
+        let mut text = format!("Fisher Transform ({}): {:+.2}, Trigger: {:+.2}", self.period, current, current_trigger);
+        match detect_reversal(fisher, trigger, FISHER_EXTREME) {
+            Some(FisherReversal::Bullish) => text.push_str("\nFisher turned up from an extreme low (bullish reversal)"),
+            Some(FisherReversal::Bearish) => text.push_str("\nFisher turned down from an extreme high (bearish reversal)"),
+            None => {}
+        }
+        text
+    }
+}
+
+impl Fisher {
+    pub async fn calculate(&mut self) -> color_eyre::Result<FisherResult> {
+        let ohlc = self.klines.lock().await.get_ohlc().await?;
+        Ok(FisherResult::from_ohlc(&ohlc, self.period))
+    }
+}
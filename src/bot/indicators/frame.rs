@@ -0,0 +1,157 @@
+use color_eyre::eyre::WrapErr;
+use polars::prelude::*;
+use polars::series::ops::NullBehavior;
+
+use crate::binance::data_collector::KlineData;
+
+// Indicator columns as Polars expressions, evaluated directly on the kline
+// DataFrame. Meant for backtests over long histories, where converting to
+// `KlinesOHLC` first costs more than the indicators themselves.
+// Warm-up rows are null, matching the `None`s of the Vec based helpers.
+
+fn fixed_window(period: usize) -> RollingOptionsFixedWindow {
+    RollingOptionsFixedWindow {
+        window_size: period,
+        min_periods: period,
+        ..Default::default()
+    }
+}
+
+fn recursive_average(alpha: f64, period: usize) -> EWMOptions {
+    EWMOptions {
+        alpha,
+        adjust: false,
+        min_periods: period,
+        ..Default::default()
+    }
+}
+
+pub fn sma_expr(column: &str, period: usize) -> Expr {
+    col(column).rolling_mean(fixed_window(period))
+}
+
+/// EMA with `alpha = 2 / (period + 1)`. Polars seeds it with the first value
+/// instead of an SMA, so the first few dozen rows after warm-up differ slightly
+/// from `moving_averages::ema` before both converge.
+pub fn ema_expr(column: &str, period: usize) -> Expr {
+    col(column).ewm_mean(recursive_average(2.0 / (period as f64 + 1.0), period))
+}
+
+/// RSI with Wilder's smoothing (`alpha = 1 / period`) of gains and losses.
+pub fn rsi_expr(column: &str, period: usize) -> Expr {
+    let change = col(column).diff(lit(1), NullBehavior::Ignore);
+    let gain = when(change.clone().gt(lit(0.0))).then(change.clone()).otherwise(lit(0.0));
+    let loss = when(change.clone().lt(lit(0.0))).then(lit(0.0) - change).otherwise(lit(0.0));
+    // the first row has no change, so the first full window ends one row later
+    let wilder = recursive_average(1.0 / period as f64, period + 1);
+    let relative_strength = gain.ewm_mean(wilder) / loss.ewm_mean(wilder);
+    lit(100.0) - lit(100.0) / (lit(1.0) + relative_strength)
+}
+
+/// Upper, middle and lower Bollinger bands, `k` population standard
+/// deviations wide (ddof 0, as in Bollinger's definition).
+pub fn bollinger_exprs(column: &str, period: usize, k: f64) -> [Expr; 3] {
+    let middle = sma_expr(column, period);
+    let population = RollingOptionsFixedWindow {
+        fn_params: Some(RollingFnParams::Var(RollingVarParams { ddof: 0 })),
+        ..fixed_window(period)
+    };
+    let width = col(column).rolling_std(population) * lit(k);
+    [
+        (middle.clone() + width.clone()).alias("bb_upper"),
+        middle.clone().alias("bb_middle"),
+        (middle - width).alias("bb_lower"),
+    ]
+}
+
+/// Add the default indicator set (SMA/EMA 6/14/20, RSI 14, Bollinger 20/2)
+/// as columns to a kline frame sorted by `open_time`.
+pub fn with_indicators(klines: LazyFrame) -> LazyFrame {
+    let mut columns = Vec::new();
+    for period in [6, 14, 20] {
+        columns.push(sma_expr("close", period).alias(format!("sma_{period}")));
+        columns.push(ema_expr("close", period).alias(format!("ema_{period}")));
+    }
+    columns.push(rsi_expr("close", 14).alias("rsi_14"));
+    columns.extend(bollinger_exprs("close", 20, 2.0));
+    klines.with_columns(columns)
+}
+
+/// `with_indicators` evaluated once over a whole kline history, so a
+/// backtest reads each candle's values instead of recomputing them on
+/// every step.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::binance::data_collector::KlineData;
+/// use botmarley::bot::indicators::frame::IndicatorColumns;
+/// use botmarley::bot::indicators::value_at_kline;
+/// use financial_indicators::bollinger::bollinger_bands;
+/// use financial_indicators::ema::exponential_moving_average;
+/// use financial_indicators::ma::simple_moving_average;
+/// let closes: Vec<f64> = (0..200).map(|i| 100.0 + 5.0 * (i as f64 / 7.0).sin() + i as f64 * 0.1).collect();
+/// let klines: Vec<KlineData> = closes.iter().enumerate().map(|(i, close)| KlineData {
+///     open_time: i as i64 * 300_000, close_time: i as i64 * 300_000 + 299_999, symbol: "BTCUSDC".into(),
+///     open: *close, high: close + 1.0, low: close - 1.0, close: *close, volume: 1.0,
+///     quote_asset_volume: 0.0, number_of_trades: 0,
+///     taker_buy_base_asset_volume: 0.0, taker_buy_quote_asset_volume: 0.0,
+/// }).collect();
+/// let columns = IndicatorColumns::from_klines(&klines).unwrap();
+/// assert_eq!(columns.len(), 200);
+/// assert_eq!(columns.value("sma_20", 18), None);
+///
+/// // both sides within a tolerance wherever both are defined
+/// let compare = |column: &str, reference: &[Option<f64>], from: usize| {
+///     let mut compared = 0;
+///     for i in from..closes.len() {
+///         if let (Some(ours), Some(Some(theirs))) = (columns.value(column, i), value_at_kline(reference, closes.len(), i)) {
+///             assert!((ours - theirs).abs() < 1e-6, "{} at {}: {} vs {}", column, i, ours, theirs);
+///             compared += 1;
+///         }
+///     }
+///     assert!(compared > 0, "nothing to compare for {}", column);
+/// };
+/// for period in [6, 14, 20] {
+///     compare(&format!("sma_{period}"), &simple_moving_average(&closes, period), 0);
+///     // the EMAs are seeded differently, compare once the seed has decayed
+///     compare(&format!("ema_{period}"), &exponential_moving_average(&closes, period), 150);
+/// }
+/// let (upper, middle, lower) = bollinger_bands(&closes, 20, 2.0);
+/// compare("bb_upper", &upper, 0);
+/// compare("bb_middle", &middle, 0);
+/// compare("bb_lower", &lower, 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct IndicatorColumns {
+    frame: DataFrame,
+}
+
+impl IndicatorColumns {
+    /// `klines` must be in time order
+    pub fn from_klines(klines: &[KlineData]) -> color_eyre::Result<Self> {
+        let frame = df![
+            "open_time" => klines.iter().map(|k| k.open_time).collect::<Vec<_>>(),
+            "close" => klines.iter().map(|k| k.close).collect::<Vec<_>>(),
+        ]
+        .wrap_err("Failed to build the indicator frame")?;
+        let frame = with_indicators(frame.lazy())
+            .collect()
+            .wrap_err("Failed to evaluate the indicator columns")?;
+        Ok(IndicatorColumns { frame })
+    }
+
+    pub fn len(&self) -> usize {
+        self.frame.height()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frame.height() == 0
+    }
+
+    /// Value of `column` (e.g. `sma_20`, `bb_upper`) at kline `index`,
+    /// `None` while warming up or for an unknown column
+    pub fn value(&self, column: &str, index: usize) -> Option<f64> {
+        self.frame.column(column).ok()?.f64().ok()?.get(index)
+    }
+}
@@ -0,0 +1,133 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::bot::indicators::{series_until, IndicatorResult};
+use crate::bot::indicators::moving_averages::{last_defined, sma};
+use crate::bot::klines::{Klines, KlinesOHLC};
+
+/// Gann HiLo Activator: a trailing line that flips between the SMA of the
+/// lows (uptrend) and the SMA of the highs (downtrend).
+pub struct GannHiLo {
+    pub klines: Arc<Mutex<Klines>>,
+    pub period: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GannHiLoResult {
+    pub period: usize,
+    pub activator: Vec<Option<f64>>,
+    /// `true` while in an uptrend
+    pub uptrend: Vec<Option<bool>>,
+}
+
+/// Activator line and trend direction, aligned with the candles.
+///
+/// A close above the previous candle's SMA of the highs turns the trend up,
+/// a close below the previous SMA of the lows turns it down, anything in
+/// between keeps it. The line trails below price on the lows SMA in an
+/// uptrend and above it on the highs SMA in a downtrend.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::gann_hilo::calculate_gann_hilo;
+/// // rally, then a reversal
+/// let close: Vec<f64> = (0..20).map(|i| 100.0 + i as f64).chain((0..20).map(|i| 119.0 - 2.0 * i as f64)).collect();
+/// let high: Vec<f64> = close.iter().map(|c| c + 1.0).collect();
+/// let low: Vec<f64> = close.iter().map(|c| c - 1.0).collect();
+/// let (activator, uptrend) = calculate_gann_hilo(&high, &low, &close, 3);
+/// assert_eq!(uptrend[19], Some(true));
+/// // trailing under price on the lows while rising
+/// assert!(activator[19].unwrap() < low[19]);
+/// let flip = (20..40).find(|&i| uptrend[i] == Some(false)).unwrap();
+/// assert!(flip <= 22);
+/// // the close broke below the previous SMA of the lows
+/// assert!(close[flip] < (low[flip - 3..flip].iter().sum::<f64>() / 3.0));
+/// assert!(activator[39].unwrap() > high[39]);
+/// ```
+pub fn calculate_gann_hilo(high: &[f64], low: &[f64], close: &[f64], period: usize) -> (Vec<Option<f64>>, Vec<Option<bool>>) {
+    let len = close.len().min(high.len()).min(low.len());
+    let sma_high = sma(&high[..len], period);
+    let sma_low = sma(&low[..len], period);
+    let mut activator = vec![None; close.len()];
+    let mut uptrend = vec![None; close.len()];
+
+    let mut trend: Option<bool> = None;
+    for i in 1..len {
+        let (Some(prev_high), Some(prev_low)) = (sma_high[i - 1], sma_low[i - 1]) else {
+            continue;
+        };
+        if close[i] > prev_high {
+            trend = Some(true);
+        } else if close[i] < prev_low {
+            trend = Some(false);
+        }
+        // before the first break, seed from the side of the channel mid
+        let up = trend.unwrap_or(close[i] >= (prev_high + prev_low) / 2.0);
+        uptrend[i] = Some(up);
+        activator[i] = if up { sma_low[i] } else { sma_high[i] };
+    }
+    (activator, uptrend)
+}
+
+impl GannHiLoResult {
+    pub fn from_ohlc(ohlc: &KlinesOHLC, period: usize) -> Self {
+        let (activator, uptrend) = calculate_gann_hilo(&ohlc.high, &ohlc.low, &ohlc.close, period);
+        GannHiLoResult { period, activator, uptrend }
+    }
+
+    /// Candles since the trend last flipped, `None` without a flip
+    pub fn candles_since_flip(&self) -> Option<usize> {
+        self.candles_since_flip_at(self.uptrend.len().saturating_sub(1))
+    }
+
+    pub fn candles_since_flip_at(&self, index: usize) -> Option<usize> {
+        let mut defined = series_until(&self.uptrend, index).iter().rev().flatten();
+        let current = *defined.next()?;
+        defined.position(|up| *up != current).map(|n| n + 1)
+    }
+
+    pub fn indicator_result(&self, symbol: &str) -> IndicatorResult {
+        self.indicator_result_at(symbol, self.uptrend.len().saturating_sub(1))
+    }
+
+    pub fn indicator_result_at(&self, symbol: &str, index: usize) -> IndicatorResult {
+        let current = series_until(&self.uptrend, index).iter().rev().find_map(|v| *v);
+        let result = IndicatorResult::new("gann_hilo", symbol)
+            .with_value("activator", last_defined(series_until(&self.activator, index)));
+        match current {
+            Some(true) => result.with_signal("bullish"),
+            Some(false) => result.with_signal("bearish"),
+            None => result,
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        self.summary_at(self.uptrend.len().saturating_sub(1))
+    }
+
+    pub fn summary_at(&self, index: usize) -> String {
+        let (Some(up), Some(line)) = (
+            series_until(&self.uptrend, index).iter().rev().find_map(|v| *v),
+            last_defined(series_until(&self.activator, index)),
+        ) else {
+            return format!("Gann HiLo ({}): not enough data", self.period);
+        };
+        let trend = if up { "uptrend, trailing below" } else { "downtrend, trailing above" };
+        let flip = match self.candles_since_flip_at(index) {
+            Some(0) => "flipped on this candle".to_string(),
+            Some(n) => format!("last flip {} candles ago", n),
+            None => "no flip in the loaded history".to_string(),
+        };
+        format!("Gann HiLo ({}): {} at {:.4}, {}", self.period, trend, line, flip)
+    }
+}
+
+impl GannHiLo {
+    pub async fn calculate(&mut self) -> color_eyre::Result<GannHiLoResult> {
+        let ohlc = self.klines.lock().await.get_ohlc().await?;
+        Ok(GannHiLoResult::from_ohlc(&ohlc, self.period))
+    }
+}
@@ -0,0 +1,179 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::bot::indicators::{series_until, IndicatorResult};
+use crate::bot::indicators::moving_averages::{ema, last_defined};
+use crate::bot::klines::Klines;
+
+/// Trader group EMAs
+pub const SHORT_PERIODS: [usize; 6] = [3, 5, 8, 10, 12, 15];
+/// Investor group EMAs
+pub const LONG_PERIODS: [usize; 6] = [30, 35, 40, 45, 50, 60];
+
+/// Candles compared when deciding whether the ribbons compress or expand
+const RIBBON_LOOKBACK: usize = 5;
+
+/// Guppy Multiple Moving Average: two EMA ribbons showing how strongly
+/// short-term traders and long-term investors agree on the trend.
+pub struct Gmma {
+    pub klines: Arc<Mutex<Klines>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RibbonTrend {
+    /// Groups moving apart, trend gaining conviction
+    Expanding,
+    /// Groups converging, trend losing conviction or about to turn
+    Compressing,
+    Flat,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GmmaResult {
+    pub short: Vec<Vec<Option<f64>>>,
+    pub long: Vec<Vec<Option<f64>>>,
+    /// Distance between the group averages in % of the long group, aligned with the klines
+    pub separation: Vec<Option<f64>>,
+    pub short_above_long: bool,
+    pub ribbon_trend: RibbonTrend,
+}
+
+/// Average of a group of EMAs, defined only once every EMA is warmed up.
+fn group_average(group: &[Vec<Option<f64>>], len: usize) -> Vec<Option<f64>> {
+    (0..len)
+        .map(|i| {
+            let values: Option<Vec<f64>> = group.iter().map(|line| line[i]).collect();
+            values.map(|v| v.iter().sum::<f64>() / v.len() as f64)
+        })
+        .collect()
+}
+
+/// Percentage separation between the short and long group averages.
+pub fn ribbon_separation(short: &[Vec<Option<f64>>], long: &[Vec<Option<f64>>], len: usize) -> Vec<Option<f64>> {
+    group_average(short, len)
+        .into_iter()
+        .zip(group_average(long, len))
+        .map(|(s, l)| match (s, l) {
+            (Some(s), Some(l)) if l != 0.0 => Some((s - l) / l * 100.0),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Compare the absolute separation now against `lookback` candles ago.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::gmma::{ribbon_trend, RibbonTrend};
+/// let series = |values: &[f64]| values.iter().map(|v| Some(*v)).collect::<Vec<_>>();
+/// assert_eq!(ribbon_trend(&series(&[1.0, 1.1, 1.2, 1.5]), 3), RibbonTrend::Expanding);
+/// // a bearish separation shrinking towards zero
+/// assert_eq!(ribbon_trend(&series(&[-2.0, -1.8, -1.5, -1.0]), 3), RibbonTrend::Compressing);
+/// assert_eq!(ribbon_trend(&series(&[1.0, 2.0, 0.0, 1.005]), 3), RibbonTrend::Flat);
+/// assert_eq!(ribbon_trend(&series(&[1.0, 5.0]), 3), RibbonTrend::Flat);
+/// ```
+pub fn ribbon_trend(separation: &[Option<f64>], lookback: usize) -> RibbonTrend {
+    let recent: Vec<f64> = separation.iter().rev().flatten().take(lookback + 1).map(|s| s.abs()).collect();
+    if recent.len() <= lookback {
+        return RibbonTrend::Flat;
+    }
+    let (now, before) = (recent[0], recent[lookback]);
+    // ignore changes below 0.01% of price, that is just noise
+    if now - before > 0.01 {
+        RibbonTrend::Expanding
+    } else if before - now > 0.01 {
+        RibbonTrend::Compressing
+    } else {
+        RibbonTrend::Flat
+    }
+}
+
+/// Both EMA groups and the trend read from their separation.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::gmma::calculate_gmma;
+/// let rally: Vec<f64> = (0..100).map(|i| 100.0 + i as f64).collect();
+/// let gmma = calculate_gmma(&rally);
+/// let last = |line: &Vec<Option<f64>>| line[99].unwrap();
+/// // faster EMAs lag less, so in a rally every line sits above the next slower one
+/// let lines: Vec<f64> = gmma.short.iter().chain(gmma.long.iter()).map(last).collect();
+/// assert!(lines.windows(2).all(|pair| pair[0] > pair[1]));
+/// assert!(gmma.short_above_long);
+/// assert_eq!(gmma.indicator_result("BTC/USDC").signal.as_deref(), Some("bullish"));
+/// // the slowest EMA warms up at 60 candles
+/// assert_eq!((gmma.separation[58], gmma.separation[59].is_some()), (None, true));
+///
+/// let selloff: Vec<f64> = rally.iter().rev().copied().collect();
+/// let gmma = calculate_gmma(&selloff);
+/// assert!(!gmma.short_above_long);
+/// assert_eq!(gmma.indicator_result("BTC/USDC").signal.as_deref(), Some("bearish"));
+/// assert_eq!(calculate_gmma(&rally[..50]).indicator_result("BTC/USDC").signal, None);
+/// ```
+pub fn calculate_gmma(close: &[f64]) -> GmmaResult {
+    let short: Vec<_> = SHORT_PERIODS.iter().map(|p| ema(close, *p)).collect();
+    let long: Vec<_> = LONG_PERIODS.iter().map(|p| ema(close, *p)).collect();
+    let separation = ribbon_separation(&short, &long, close.len());
+    let short_above_long = last_defined(&separation).is_some_and(|s| s > 0.0);
+    let ribbon_trend = ribbon_trend(&separation, RIBBON_LOOKBACK);
+
+    GmmaResult {
+        short,
+        long,
+        separation,
+        short_above_long,
+        ribbon_trend,
+    }
+}
+
+impl GmmaResult {
+    pub fn indicator_result(&self, symbol: &str) -> IndicatorResult {
+        self.indicator_result_at(symbol, self.separation.len().saturating_sub(1))
+    }
+
+    pub fn indicator_result_at(&self, symbol: &str, index: usize) -> IndicatorResult {
+        let separation = last_defined(series_until(&self.separation, index));
+        let result = IndicatorResult::new("gmma", symbol).with_value("separation_pct", separation);
+        match separation {
+            Some(s) if s > 0.0 => result.with_signal("bullish"),
+            Some(_) => result.with_signal("bearish"),
+            None => result,
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        self.summary_at(self.separation.len().saturating_sub(1))
+    }
+
+    pub fn summary_at(&self, index: usize) -> String {
+        let history = series_until(&self.separation, index);
+        let Some(separation) = last_defined(history) else {
+            return "GMMA: not enough data (needs 60 candles)".to_string();
+        };
+        let position = if separation > 0.0 {
+            "short-term group above long-term group (bullish)"
+        } else {
+            "short-term group below long-term group (bearish)"
+        };
+        let ribbons = match ribbon_trend(history, RIBBON_LOOKBACK) {
+            RibbonTrend::Expanding => "ribbons expanding, trend gaining strength",
+            RibbonTrend::Compressing => "ribbons compressing, trend weakening",
+            RibbonTrend::Flat => "ribbon spacing unchanged",
+        };
+        format!(
+            "GMMA: {}\nSeparation: {:+.2}%, {}",
+            position, separation, ribbons
+        )
+    }
+}
+
+impl Gmma {
+    pub async fn calculate(&mut self) -> color_eyre::Result<GmmaResult> {
+        let close = self.klines.lock().await.get_ohlc().await?.close;
+        Ok(calculate_gmma(&close))
+    }
+}
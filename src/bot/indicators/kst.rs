@@ -0,0 +1,176 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::bot::indicators::{series_until, IndicatorResult};
+use crate::bot::indicators::moving_averages::{last_defined, on_defined, sma};
+use crate::bot::klines::Klines;
+
+/// Pring's daily defaults
+pub const ROC_PERIODS: [usize; 4] = [10, 15, 20, 30];
+pub const SMA_PERIODS: [usize; 4] = [10, 10, 10, 15];
+pub const WEIGHTS: [f64; 4] = [1.0, 2.0, 3.0, 4.0];
+pub const SIGNAL_PERIOD: usize = 9;
+
+/// Know Sure Thing: weighted sum of four smoothed rates of change.
+pub struct Kst {
+    pub klines: Arc<Mutex<Klines>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KstCross {
+    /// KST crossed above its signal line
+    Bullish,
+    /// KST crossed below its signal line
+    Bearish,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KstResult {
+    pub kst: Vec<Option<f64>>,
+    pub signal: Vec<Option<f64>>,
+    /// Signal-line crossover on the latest candle
+    pub cross: Option<KstCross>,
+}
+
+/// Rate of change in percent over `period` candles, aligned with the input.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::kst::rate_of_change;
+/// assert_eq!(rate_of_change(&[100.0, 110.0, 121.0], 2), vec![None, None, Some(21.0)]);
+/// // no rate of change from a zero base
+/// assert_eq!(rate_of_change(&[0.0, 5.0], 1), vec![None, None]);
+/// ```
+pub fn rate_of_change(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    (0..values.len())
+        .map(|i| {
+            let base = values[i.checked_sub(period)?];
+            (base != 0.0).then(|| (values[i] - base) / base * 100.0)
+        })
+        .collect()
+}
+
+/// KST line and its signal line, both aligned with the closes.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::kst::calculate_kst;
+/// // +10% then +10%: three 1-candle ROCs of 10% and one 2-candle ROC of 21%,
+/// // weighted 1, 2, 3 and 4: 10 + 20 + 30 + 84
+/// let closes = [100.0, 110.0, 121.0];
+/// let (kst, signal) = calculate_kst(&closes, &[1, 1, 1, 2], &[1, 1, 1, 1], &[1.0, 2.0, 3.0, 4.0], 1);
+/// assert_eq!(kst[1], None);
+/// assert!((kst[2].unwrap() - 144.0).abs() < 1e-9);
+/// assert_eq!(signal[2], kst[2]);
+/// ```
+pub fn calculate_kst(
+    closes: &[f64],
+    roc_periods: &[usize; 4],
+    sma_periods: &[usize; 4],
+    weights: &[f64; 4],
+    signal_period: usize,
+) -> (Vec<Option<f64>>, Vec<Option<f64>>) {
+    let smoothed: Vec<Vec<Option<f64>>> = roc_periods
+        .iter()
+        .zip(sma_periods.iter())
+        .map(|(roc, avg)| on_defined(&rate_of_change(closes, *roc), |values| sma(values, *avg)))
+        .collect();
+
+    let kst: Vec<Option<f64>> = (0..closes.len())
+        .map(|i| {
+            smoothed
+                .iter()
+                .zip(weights.iter())
+                .map(|(line, weight)| line[i].map(|v| v * weight))
+                .sum::<Option<f64>>()
+        })
+        .collect();
+    let signal = on_defined(&kst, |values| sma(values, signal_period));
+    (kst, signal)
+}
+
+/// Crossover between the last two candles.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::kst::{detect_kst_cross, KstCross};
+/// let signal = [Some(5.0), Some(5.0)];
+/// assert_eq!(detect_kst_cross(&[Some(4.0), Some(6.0)], &signal), Some(KstCross::Bullish));
+/// assert_eq!(detect_kst_cross(&[Some(5.0), Some(4.0)], &signal), Some(KstCross::Bearish));
+/// assert_eq!(detect_kst_cross(&[Some(6.0), Some(7.0)], &signal), None);
+/// assert_eq!(detect_kst_cross(&[Some(4.0), Some(6.0)], &[None, Some(5.0)]), None);
+/// ```
+pub fn detect_kst_cross(kst: &[Option<f64>], signal: &[Option<f64>]) -> Option<KstCross> {
+    match (kst, signal) {
+        ([.., Some(k0), Some(k1)], [.., Some(s0), Some(s1)]) => {
+            if k0 <= s0 && k1 > s1 {
+                Some(KstCross::Bullish)
+            } else if k0 >= s0 && k1 < s1 {
+                Some(KstCross::Bearish)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+impl KstResult {
+    /// KST with Pring's default periods and weights
+    pub fn from_close(close: &[f64]) -> Self {
+        let (kst, signal) = calculate_kst(close, &ROC_PERIODS, &SMA_PERIODS, &WEIGHTS, SIGNAL_PERIOD);
+        KstResult {
+            cross: detect_kst_cross(&kst, &signal),
+            kst,
+            signal,
+        }
+    }
+
+    pub fn indicator_result(&self, symbol: &str) -> IndicatorResult {
+        self.indicator_result_at(symbol, self.kst.len().saturating_sub(1))
+    }
+
+    pub fn indicator_result_at(&self, symbol: &str, index: usize) -> IndicatorResult {
+        let kst = last_defined(series_until(&self.kst, index));
+        let signal = last_defined(series_until(&self.signal, index));
+        let result = IndicatorResult::new("kst", symbol)
+            .with_value("kst", kst)
+            .with_value("signal", signal);
+        match (kst, signal) {
+            (Some(k), Some(s)) if k > s => result.with_signal("bullish"),
+            (Some(k), Some(s)) if k < s => result.with_signal("bearish"),
+            (Some(_), Some(_)) => result.with_signal("neutral"),
+            _ => result,
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        self.summary_at(self.kst.len().saturating_sub(1))
+    }
+
+    pub fn summary_at(&self, index: usize) -> String {
+        let (kst, signal) = (series_until(&self.kst, index), series_until(&self.signal, index));
+        let (Some(current), Some(current_signal)) = (last_defined(kst), last_defined(signal)) else {
+            return "KST: not enough data".to_string();
+        };
+        let mut text = format!("KST: {:.2}, Signal: {:.2}", current, current_signal);
+        match detect_kst_cross(kst, signal) {
+            Some(KstCross::Bullish) => text.push_str("\nKST crossed above its signal line (bullish)"),
+            Some(KstCross::Bearish) => text.push_str("\nKST crossed below its signal line (bearish)"),
+            None => {}
+        }
+        text
+    }
+}
+
+impl Kst {
+    pub async fn calculate(&mut self) -> color_eyre::Result<KstResult> {
+        let close = self.klines.lock().await.get_ohlc().await?.close;
+        Ok(KstResult::from_close(&close))
+    }
+}
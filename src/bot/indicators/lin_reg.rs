@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::bot::indicators::IndicatorResult;
+use crate::bot::klines::Klines;
+
+/// Linear regression channel: least-squares line over the last `period`
+/// closes, `dev_mult` standard deviations of the residuals wide.
+pub struct LinRegChannel {
+    pub klines: Arc<Mutex<Klines>>,
+    pub period: usize,
+    pub dev_mult: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinRegResult {
+    pub period: usize,
+    pub dev_mult: f64,
+    pub close: Option<f64>,
+    /// `(regression value, slope, upper, lower)` at the latest candle
+    pub channel: Option<(f64, f64, f64, f64)>,
+}
+
+/// Least-squares `(slope, intercept)` of `values` against their index.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::lin_reg::least_squares;
+/// let line: Vec<f64> = (0..10).map(|x| 3.0 + 0.5 * x as f64).collect();
+/// assert_eq!(least_squares(&line), Some((0.5, 3.0)));
+/// assert_eq!(least_squares(&[1.0]), None);
+/// ```
+pub fn least_squares(values: &[f64]) -> Option<(f64, f64)> {
+    let n = values.len() as f64;
+    if values.len() < 2 {
+        return None;
+    }
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = values.iter().sum::<f64>() / n;
+    let (mut cov, mut var_x) = (0.0, 0.0);
+    for (x, y) in values.iter().enumerate() {
+        let dx = x as f64 - mean_x;
+        cov += dx * (y - mean_y);
+        var_x += dx * dx;
+    }
+    let slope = cov / var_x;
+    Some((slope, mean_y - slope * mean_x))
+}
+
+/// `(regression value, slope, upper, lower)` at the last of `period` closes,
+/// `None` without enough closes.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::lin_reg::calculate_regression_channel;
+/// let closes: Vec<f64> = (0..20).map(|x| 100.0 + 2.0 * x as f64).collect();
+/// let (value, slope, upper, lower) = calculate_regression_channel(&closes, 10, 2.0).unwrap();
+/// assert!((value - 138.0).abs() < 1e-9 && (slope - 2.0).abs() < 1e-9);
+/// // a perfect line has no residuals, so the channel collapses onto it
+/// assert!((upper - value).abs() < 1e-9 && (lower - value).abs() < 1e-9);
+/// ```
+pub fn calculate_regression_channel(closes: &[f64], period: usize, dev_mult: f64) -> Option<(f64, f64, f64, f64)> {
+    if period < 2 || closes.len() < period {
+        return None;
+    }
+    let window = &closes[closes.len() - period..];
+    let (slope, intercept) = least_squares(window)?;
+    let residual_var = window
+        .iter()
+        .enumerate()
+        .map(|(x, y)| (y - (intercept + slope * x as f64)).powi(2))
+        .sum::<f64>()
+        / period as f64;
+    let deviation = residual_var.sqrt() * dev_mult;
+    let value = intercept + slope * (period - 1) as f64;
+    Some((value, slope, value + deviation, value - deviation))
+}
+
+impl LinRegResult {
+    pub fn from_close(closes: &[f64], period: usize, dev_mult: f64) -> Self {
+        LinRegResult {
+            period,
+            dev_mult,
+            close: closes.last().copied(),
+            channel: calculate_regression_channel(closes, period, dev_mult),
+        }
+    }
+
+    /// Price position in the channel, 0 at the lower and 1 at the upper line
+    pub fn position(&self) -> Option<f64> {
+        let (_, _, upper, lower) = self.channel?;
+        let close = self.close?;
+        (upper > lower).then(|| (close - lower) / (upper - lower))
+    }
+
+    pub fn indicator_result(&self, symbol: &str) -> IndicatorResult {
+        let result = IndicatorResult::new("lin_reg", symbol)
+            .with_value("regression", self.channel.map(|c| c.0))
+            .with_value("slope", self.channel.map(|c| c.1))
+            .with_value("upper", self.channel.map(|c| c.2))
+            .with_value("lower", self.channel.map(|c| c.3))
+            .with_value("position", self.position());
+        match self.channel {
+            Some((_, slope, _, _)) if slope > 0.0 => result.with_signal("bullish"),
+            Some((_, slope, _, _)) if slope < 0.0 => result.with_signal("bearish"),
+            Some(_) => result.with_signal("neutral"),
+            None => result,
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let (Some((value, slope, upper, lower)), Some(close)) = (self.channel, self.close) else {
+            return format!("Linear Regression Channel ({}): not enough data", self.period);
+        };
+        let slope_pct = if value != 0.0 { slope / value * 100.0 } else { 0.0 };
+        let location = match self.position() {
+            Some(p) if p > 1.0 => "above the channel (extended)".to_string(),
+            Some(p) if p < 0.0 => "below the channel (extended)".to_string(),
+            Some(p) => format!("{:.0}% up the channel", p * 100.0),
+            None => "on the regression line".to_string(),
+        };
+        format!(
+            "Linear Regression Channel ({}, {}σ): line {:.4} (slope {:+.4}/candle, {:+.3}%), upper {:.4}, lower {:.4}, price {:.4} {}",
+            self.period, self.dev_mult, value, slope, slope_pct, upper, lower, close, location
+        )
+    }
+}
+
+impl LinRegChannel {
+    pub async fn calculate(&mut self) -> color_eyre::Result<LinRegResult> {
+        let close = self.klines.lock().await.get_ohlc().await?.close;
+        Ok(LinRegResult::from_close(&close, self.period, self.dev_mult))
+    }
+}
@@ -4,6 +4,7 @@ use color_eyre::eyre::Ok;
 use financial_indicators::macd::MACD;
 use tokio::sync::Mutex;
 
+use crate::bot::indicators::{value_at_kline, IndicatorResult};
 use crate::bot::klines::Klines;
 
 pub struct Macd {
@@ -21,19 +22,43 @@ pub struct AlignedMacdResult {
 }
 
 impl AlignedMacdResult {
+    /// MACD(12, 26, 9) over the closes
+    pub fn from_close(close: &[f64]) -> Self {
+        let macd_values = MACD::new(close, 12, 26, 9);
+        AlignedMacdResult {
+            offset: close.len() - macd_values.len(),
+            total_klines: close.len(),
+            macd_values,
+        }
+    }
+
     /// Get MACD value for a specific kline index, returns None if in warm-up period
     pub fn get_macd_for_kline(&self, kline_index: usize) -> Option<&MACD> {
-        if kline_index < self.offset {
-            None // Warm-up period
-        } else {
-            let macd_index = kline_index - self.offset;
-            self.macd_values.get(macd_index)
-        }
+        value_at_kline(&self.macd_values, self.total_klines, kline_index)
     }
-    
+
     /// Check if a kline index has a corresponding MACD value
     pub fn has_macd_for_kline(&self, kline_index: usize) -> bool {
-        kline_index >= self.offset && (kline_index - self.offset) < self.macd_values.len()
+        self.get_macd_for_kline(kline_index).is_some()
+    }
+
+    /// Latest MACD reading as structured output
+    pub fn indicator_result(&self, symbol: &str) -> IndicatorResult {
+        self.indicator_result_at(symbol, self.total_klines.saturating_sub(1))
+    }
+
+    pub fn indicator_result_at(&self, symbol: &str, kline_index: usize) -> IndicatorResult {
+        let last = self.get_macd_for_kline(kline_index);
+        let result = IndicatorResult::new("macd", symbol)
+            .with_value("macd", last.map(|m| m.macd))
+            .with_value("signal", last.map(|m| m.signal))
+            .with_value("histogram", last.map(|m| m.histogram));
+        match last {
+            Some(m) if m.histogram > 0.0 => result.with_signal("bullish"),
+            Some(m) if m.histogram < 0.0 => result.with_signal("bearish"),
+            Some(_) => result.with_signal("neutral"),
+            None => result,
+        }
     }
 }
 
@@ -46,17 +71,7 @@ impl Macd{
     
     /// Calculate MACD with proper alignment information
     pub async fn calculate_aligned(&mut self) -> color_eyre::Result<AlignedMacdResult> {
-        let ohlc = self.klines.lock().await.get_ohlc().await?;
-        let close = ohlc.close;
-        let total_klines = ohlc.count;
-        
-        let macd_values = MACD::new(&close, 12, 26, 9);
-        let offset = total_klines - macd_values.len();
-        
-        Ok(AlignedMacdResult {
-            macd_values,
-            offset,
-            total_klines,
-        })
+        let close = self.klines.lock().await.get_ohlc().await?.close;
+        Ok(AlignedMacdResult::from_close(&close))
     }
 }
\ No newline at end of file
@@ -0,0 +1,214 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::bot::indicators::{series_until, IndicatorResult};
+use crate::bot::indicators::moving_averages::{ema, on_defined, sma};
+use crate::bot::klines::{Klines, KlinesOHLC};
+
+/// Mass Index crossing above this level starts a reversal bulge
+pub const BULGE_SETUP: f64 = 27.0;
+/// Dropping back below this level after the setup completes the bulge
+pub const BULGE_TRIGGER: f64 = 26.5;
+
+pub struct MassIndex {
+    pub klines: Arc<Mutex<Klines>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MassIndexResult {
+    pub values: Vec<Option<f64>>,
+    /// Kline index where the latest reversal bulge completed
+    pub last_bulge: Option<usize>,
+    /// Kline indices of every completed reversal bulge, oldest first
+    pub bulges: Vec<usize>,
+}
+
+/// Ratio of the single EMA to the double EMA of the high-low range.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::mass_index::ema_ratio;
+/// use botmarley::bot::klines::KlinesOHLC;
+/// // ranges 1, 1, 4: EMA(2) is 1 then 2/3 * 4 + 1/3 * 1 = 3,
+/// // the EMA(2) of that is (1 + 3) / 2 = 2, so the ratio is 3 / 2
+/// let ohlc = KlinesOHLC {
+///     time: vec![0, 1, 2],
+///     open: vec![10.0; 3],
+///     high: vec![11.0, 11.0, 14.0],
+///     low: vec![10.0; 3],
+///     close: vec![10.0; 3],
+///     volume: vec![1.0; 3],
+///     count: 3,
+/// };
+/// assert_eq!(ema_ratio(&ohlc, 2), vec![None, None, Some(1.5)]);
+/// ```
+pub fn ema_ratio(ohlc: &KlinesOHLC, ema_period: usize) -> Vec<Option<f64>> {
+    let range: Vec<f64> = ohlc
+        .high
+        .iter()
+        .zip(ohlc.low.iter())
+        .map(|(h, l)| h - l)
+        .collect();
+
+    let single = ema(&range, ema_period);
+    let double = on_defined(&single, |values| ema(values, ema_period));
+
+    single
+        .iter()
+        .zip(double.iter())
+        .map(|(s, d)| match (s, d) {
+            (Some(s), Some(d)) if *d != 0.0 => Some(s / d),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Mass Index: sum of the EMA ratio over `sum_period` candles (classic 9 / 25).
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::mass_index::calculate_mass_index;
+/// use botmarley::bot::klines::KlinesOHLC;
+/// // a constant range keeps the ratio at 1, so the index sits at `sum_period`
+/// let ohlc = KlinesOHLC {
+///     time: (0..50).collect(),
+///     open: vec![100.0; 50],
+///     high: vec![101.0; 50],
+///     low: vec![99.0; 50],
+///     close: vec![100.0; 50],
+///     volume: vec![1.0; 50],
+///     count: 50,
+/// };
+/// let values = calculate_mass_index(&ohlc, 9, 25);
+/// // 8 + 8 candles of EMA warm-up, then 24 more for the sum
+/// assert_eq!(values[39], None);
+/// assert!((values[40].unwrap() - 25.0).abs() < 1e-9);
+/// ```
+pub fn calculate_mass_index(ohlc: &KlinesOHLC, ema_period: usize, sum_period: usize) -> Vec<Option<f64>> {
+    let ratio = ema_ratio(ohlc, ema_period);
+    on_defined(&ratio, |values| sma(values, sum_period))
+        .into_iter()
+        .map(|v| v.map(|avg| avg * sum_period as f64))
+        .collect()
+}
+
+/// Find the latest reversal bulge: the index rises above 27 and later
+/// falls back below 26.5. Returns the index where it fell below.
+/// `reversal_bulges` lists all of them.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::mass_index::detect_reversal_bulge;
+/// assert_eq!(detect_reversal_bulge(&[None, Some(26.0), Some(27.5), Some(26.8), Some(26.4)]), Some(4));
+/// // above 27 but not yet back below 26.5
+/// assert_eq!(detect_reversal_bulge(&[Some(27.5), Some(26.8)]), None);
+/// // touching 27 doesn't arm the setup
+/// assert_eq!(detect_reversal_bulge(&[Some(27.0), Some(26.0)]), None);
+/// // the latest of two bulges
+/// assert_eq!(detect_reversal_bulge(&[Some(27.2), Some(26.0), Some(27.1), Some(26.1)]), Some(3));
+/// ```
+pub fn detect_reversal_bulge(values: &[Option<f64>]) -> Option<usize> {
+    reversal_bulges(values).last().copied()
+}
+
+/// Indices where each reversal bulge completed, oldest first. A bulge only
+/// depends on the values up to its index, so the ones at or before a candle
+/// are exactly what that candle's history would find.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::mass_index::reversal_bulges;
+/// assert_eq!(reversal_bulges(&[Some(27.2), Some(26.0), Some(27.1), Some(26.1)]), vec![1, 3]);
+/// assert!(reversal_bulges(&[Some(27.5), Some(26.8)]).is_empty());
+/// ```
+pub fn reversal_bulges(values: &[Option<f64>]) -> Vec<usize> {
+    let mut armed = false;
+    let mut bulges = Vec::new();
+    for (i, value) in values.iter().enumerate() {
+        let Some(value) = *value else { continue };
+        if value > BULGE_SETUP {
+            armed = true;
+        } else if armed && value < BULGE_TRIGGER {
+            armed = false;
+            bulges.push(i);
+        }
+    }
+    bulges
+}
+
+impl MassIndexResult {
+    /// Classic 9 / 25 Mass Index from already loaded OHLC data.
+    pub fn from_ohlc(ohlc: &KlinesOHLC) -> Self {
+        let values = calculate_mass_index(ohlc, 9, 25);
+        let bulges = reversal_bulges(&values);
+        MassIndexResult {
+            values,
+            last_bulge: bulges.last().copied(),
+            bulges,
+        }
+    }
+
+    fn last_bulge_at(&self, index: usize) -> Option<usize> {
+        self.bulges.iter().rev().find(|&&i| i <= index).copied()
+    }
+
+    pub fn indicator_result(&self, symbol: &str) -> IndicatorResult {
+        self.indicator_result_at(symbol, self.values.len().saturating_sub(1))
+    }
+
+    pub fn indicator_result_at(&self, symbol: &str, index: usize) -> IndicatorResult {
+        let last_bulge = self.last_bulge_at(index);
+        let result = IndicatorResult::new("mass_index", symbol)
+            .with_value("mass_index", series_until(&self.values, index).iter().rev().find_map(|v| *v))
+            .with_value("last_bulge_index", last_bulge.map(|i| i as f64));
+        if last_bulge.is_some() {
+            result.with_signal("reversal")
+        } else {
+            result.with_signal("neutral")
+        }
+    }
+
+    pub fn summary(&self, total_klines: usize) -> String {
+        let current = self.values.iter().rev().find_map(|v| *v);
+        Self::describe(current, self.last_bulge, total_klines)
+    }
+
+    pub fn summary_at(&self, index: usize) -> String {
+        let current = series_until(&self.values, index).iter().rev().find_map(|v| *v);
+        Self::describe(current, self.last_bulge_at(index), index + 1)
+    }
+
+    fn describe(current: Option<f64>, last_bulge: Option<usize>, total_klines: usize) -> String {
+        let mut text = match current {
+            Some(v) => format!("Mass Index (9/25): {:.2}", v),
+            None => "Mass Index (9/25): not enough data".to_string(),
+        };
+        match last_bulge {
+            Some(i) => text.push_str(&format!(
+                "\n⚠️ Reversal bulge completed {} candles ago (rose above {} then fell below {})",
+                total_klines.saturating_sub(i + 1),
+                BULGE_SETUP,
+                BULGE_TRIGGER
+            )),
+            None if current.is_some_and(|v| v > BULGE_SETUP) => text.push_str(&format!(
+                "\nRange expanding: above {}, a drop below {} would complete a reversal bulge",
+                BULGE_SETUP, BULGE_TRIGGER
+            )),
+            None => {}
+        }
+        text
+    }
+}
+
+impl MassIndex {
+    pub async fn calculate(&mut self) -> color_eyre::Result<MassIndexResult> {
+        let ohlc = self.klines.lock().await.get_ohlc().await?;
+        Ok(MassIndexResult::from_ohlc(&ohlc))
+    }
+}
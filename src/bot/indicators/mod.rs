@@ -1,5 +1,36 @@
+use std::collections::BTreeMap;
+
+use financial_indicators::rsi::relative_strength_index;
 use serde::{Deserialize, Serialize};
+
+use crate::bot::klines::{Klines, KlinesOHLC};
 pub mod macd;
+pub mod moving_averages;
+pub mod atr;
+pub mod adx;
+pub mod ensemble;
+pub mod alligator;
+pub mod mass_index;
+pub mod gmma;
+pub mod pgo;
+pub mod choppiness;
+pub mod awesome;
+pub mod kst;
+pub mod frame;
+pub mod patterns;
+pub mod rvi;
+pub mod fisher;
+pub mod bop;
+pub mod bollinger;
+pub mod volume_indicators;
+pub mod regime;
+pub mod lin_reg;
+pub mod disparity;
+pub mod stc;
+pub mod gann_hilo;
+pub mod ema_extension;
+pub mod qstick;
+pub mod rsi;
 
 #[derive(Debug,Serialize,Deserialize)]
 pub struct Indicator{
@@ -9,6 +40,303 @@ pub struct Indicator{
     
 }
 
+/// Machine-readable indicator output, the structured counterpart of the
+/// human readable `summary()` text.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndicatorResult {
+    pub name: String,
+    pub symbol: String,
+    /// Latest values keyed by series name
+    pub values: BTreeMap<String, f64>,
+    /// Directional reading, e.g. "bullish", "bearish", "neutral"
+    pub signal: Option<String>,
+}
+
+impl IndicatorResult {
+    pub fn new(name: &str, symbol: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            symbol: symbol.to_string(),
+            values: BTreeMap::new(),
+            signal: None,
+        }
+    }
+
+    /// Add a value, skipping it when it's not available (warm-up) or not finite
+    pub fn with_value(mut self, key: &str, value: Option<f64>) -> Self {
+        if let Some(value) = value.filter(|v| v.is_finite()) {
+            self.values.insert(key.to_string(), value);
+        }
+        self
+    }
+
+    pub fn with_signal(mut self, signal: &str) -> Self {
+        self.signal = Some(signal.to_string());
+        self
+    }
+
+    /// Serialize for dataset records and logs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use botmarley::bot::indicators::IndicatorResult;
+    /// let result = IndicatorResult::new("rsi", "BTC/USDC")
+    ///     .with_value("rsi", Some(28.5))
+    ///     .with_value("warming_up", None)
+    ///     .with_value("broken", Some(f64::NAN))
+    ///     .with_signal("bullish");
+    /// let json = result.to_json().unwrap();
+    /// assert_eq!(json, r#"{"name":"rsi","symbol":"BTC/USDC","values":{"rsi":28.5},"signal":"bullish"}"#);
+    /// assert_eq!(serde_json::from_str::<IndicatorResult>(&json).unwrap(), result);
+    /// ```
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+}
+
+/// Value of a warm-up trimmed indicator series at `kline_index`.
+///
+/// Indicator crates drop the warm-up values, so a series shorter than the
+/// `total_klines` it was computed from ends on the last kline and starts
+/// `total_klines - values.len()` klines in. Returns `None` during warm-up
+/// and past the end instead of falling back to a default.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::value_at_kline;
+/// // 5 klines, indicator with a 2 candle warm-up
+/// let values = [10.0, 11.0, 12.0];
+/// assert_eq!(value_at_kline(&values, 5, 1), None);
+/// assert_eq!(value_at_kline(&values, 5, 2), Some(&10.0));
+/// assert_eq!(value_at_kline(&values, 5, 4), Some(&12.0));
+/// assert_eq!(value_at_kline(&values, 5, 5), None);
+/// ```
+pub fn value_at_kline<T>(values: &[T], total_klines: usize, kline_index: usize) -> Option<&T> {
+    let offset = total_klines.checked_sub(values.len())?;
+    if kline_index < offset || kline_index >= total_klines {
+        return None;
+    }
+    values.get(kline_index - offset)
+}
+
+/// `value_at_kline` for series that already mark warm-up with `None`.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::option_at_kline;
+/// let aligned = [None, Some(1.0), Some(2.0)];
+/// assert_eq!(option_at_kline(&aligned, 3, 0), None);
+/// assert_eq!(option_at_kline(&aligned, 3, 2), Some(2.0));
+/// let trimmed = [Some(1.0), Some(2.0)];
+/// assert_eq!(option_at_kline(&trimmed, 3, 1), Some(1.0));
+/// ```
+pub fn option_at_kline(values: &[Option<f64>], total_klines: usize, kline_index: usize) -> Option<f64> {
+    value_at_kline(values, total_klines, kline_index).copied().flatten()
+}
+
+/// The part of a kline-aligned series known at `kline_index`, that is up to
+/// and including it. Lets a series computed once over a whole backtest be
+/// read candle by candle without recomputing it over every prefix.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::series_until;
+/// let values = [None, Some(1.0), Some(2.0)];
+/// assert_eq!(series_until(&values, 1), &[None, Some(1.0)]);
+/// assert_eq!(series_until(&values, 7), &values);
+/// ```
+pub fn series_until<T>(values: &[T], kline_index: usize) -> &[T] {
+    &values[..values.len().min(kline_index.saturating_add(1))]
+}
+
+/// Structured results of every indicator that gives a directional reading.
+/// Regime classifications other than a trend ("ranging", "volatile") are
+/// included but not counted by `tally_signals`.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::directional_signals;
+/// use botmarley::bot::klines::KlinesOHLC;
+/// let close: Vec<f64> = (0..80).map(|i| 100.0 + i as f64).collect();
+/// let ohlc = KlinesOHLC {
+///     time: (0..80).collect(),
+///     open: close.iter().map(|c| c - 0.5).collect(),
+///     high: close.iter().map(|c| c + 1.0).collect(),
+///     low: close.iter().map(|c| c - 1.0).collect(),
+///     volume: vec![10.0; 80],
+///     close,
+///     count: 80,
+/// };
+/// let names: Vec<String> = directional_signals(&ohlc, "BTC/USDC").into_iter().map(|r| r.name).collect();
+/// for name in ["rsi", "bollinger", "stc", "fisher", "rvi", "bop", "regime", "macd", "gmma"] {
+///     assert!(names.iter().any(|n| n == name), "{} missing from {:?}", name, names);
+/// }
+/// ```
+pub fn directional_signals(ohlc: &KlinesOHLC, symbol: &str) -> Vec<IndicatorResult> {
+    DirectionalSeries::from_ohlc(ohlc).signals_at(symbol, ohlc.close.len().saturating_sub(1))
+}
+
+/// Every series behind `directional_signals`, computed once over a whole
+/// history. A backtest builds it once per run and reads each candle with
+/// `signals_at` instead of recomputing the indicators over every prefix.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::{directional_signals, DirectionalSeries};
+/// use botmarley::bot::klines::KlinesOHLC;
+/// let close: Vec<f64> = (0..240).map(|i| 100.0 + (i as f64 / 7.0).sin() * 5.0 + i as f64 * 0.05).collect();
+/// let ohlc = |len: usize| KlinesOHLC {
+///     time: (0..len as i64).collect(),
+///     open: close[..len].iter().enumerate().map(|(i, c)| c + if i % 3 == 0 { 0.8 } else { -0.4 }).collect(),
+///     high: close[..len].iter().map(|c| c + 1.2).collect(),
+///     low: close[..len].iter().map(|c| c - 1.1).collect(),
+///     volume: vec![10.0; len],
+///     close: close[..len].to_vec(),
+///     count: len,
+/// };
+/// let series = DirectionalSeries::from_ohlc(&ohlc(close.len()));
+/// // reading a candle gives what the history up to it would give on its own
+/// for index in [0, 20, 25, 60, 150, 239] {
+///     assert_eq!(series.signals_at("BTC/USDC", index), directional_signals(&ohlc(index + 1), "BTC/USDC"));
+/// }
+/// ```
+pub struct DirectionalSeries {
+    pub close: Vec<f64>,
+    pub gmma: gmma::GmmaResult,
+    pub pgo: pgo::PgoResult,
+    pub alligator: alligator::AlligatorResult,
+    pub awesome: awesome::AwesomeResult,
+    pub kst: kst::KstResult,
+    pub rsi: rsi::RsiSeries,
+    pub bollinger: bollinger::BollingerResult,
+    pub stc: stc::StcResult,
+    pub fisher: fisher::FisherResult,
+    pub rvi: rvi::RviResult,
+    pub bop: bop::BopResult,
+    pub qstick: qstick::QstickResult,
+    pub gann_hilo: gann_hilo::GannHiLoResult,
+    pub disparity: disparity::DisparityResult,
+    pub ema_extension: Vec<Vec<Option<ema_extension::EmaExtension>>>,
+    /// Every pattern of the history, `PatternsResult::at` keeps the recent ones
+    pub patterns: Vec<patterns::Pattern>,
+    pub regime: regime::RegimeSeries,
+    /// `None` below the 26 candles MACD needs
+    pub macd: Option<macd::AlignedMacdResult>,
+}
+
+impl DirectionalSeries {
+    pub fn from_ohlc(ohlc: &KlinesOHLC) -> Self {
+        DirectionalSeries {
+            close: ohlc.close.clone(),
+            gmma: gmma::calculate_gmma(&ohlc.close),
+            pgo: pgo::PgoResult {
+                period: 14,
+                values: pgo::calculate_pgo(ohlc, 14),
+            },
+            alligator: alligator::AlligatorResult::from_ohlc(ohlc),
+            awesome: awesome::AwesomeResult::from_ohlc(ohlc),
+            kst: kst::KstResult::from_close(&ohlc.close),
+            rsi: rsi::RsiSeries::new(&ohlc.close, relative_strength_index(&ohlc.close, 14)),
+            bollinger: bollinger::BollingerResult::from_close(&ohlc.close, 20, 2.0),
+            stc: stc::StcResult::from_close(&ohlc.close, 23, 50, 10, 3, 3),
+            fisher: fisher::FisherResult::from_ohlc(ohlc, 10),
+            rvi: rvi::RviResult::from_ohlc(ohlc, 10),
+            bop: bop::BopResult::from_ohlc(ohlc, 14),
+            qstick: qstick::QstickResult::from_ohlc(ohlc, 8),
+            gann_hilo: gann_hilo::GannHiLoResult::from_ohlc(ohlc, 10),
+            disparity: disparity::DisparityResult::from_close(&ohlc.close, 20),
+            ema_extension: ema_extension::extension_series(&ohlc.close, &ema_extension::EXTENSION_PERIODS),
+            patterns: patterns::detect_candlestick_patterns(ohlc),
+            regime: regime::RegimeSeries::from_ohlc(ohlc),
+            macd: (ohlc.close.len() >= 26).then(|| macd::AlignedMacdResult::from_close(&ohlc.close)),
+        }
+    }
+
+    /// `directional_signals` for the history up to and including candle `index`.
+    pub fn signals_at(&self, symbol: &str, index: usize) -> Vec<IndicatorResult> {
+        let close = series_until(&self.close, index);
+        let mut results = vec![
+            self.gmma.indicator_result_at(symbol, index),
+            self.pgo.indicator_result_at(symbol, index),
+            self.alligator.indicator_result_at(symbol, index),
+            self.awesome.indicator_result_at(symbol, index),
+            self.kst.indicator_result_at(symbol, index),
+            self.rsi.indicator_result_at(symbol, index),
+            self.bollinger.indicator_result_at(symbol, index),
+            self.stc.indicator_result_at(symbol, index),
+            self.fisher.indicator_result_at(symbol, index),
+            self.rvi.indicator_result_at(symbol, index),
+            self.bop.indicator_result_at(symbol, index),
+            self.qstick.indicator_result_at(symbol, index),
+            self.gann_hilo.indicator_result_at(symbol, index),
+            // only the last `period` closes go into the channel
+            lin_reg::LinRegResult::from_close(close, 50, 2.0).indicator_result(symbol),
+            self.disparity.indicator_result_at(symbol, index),
+            ema_extension::extension_indicator_result(&ema_extension::extension_at(&self.ema_extension, index), symbol),
+            patterns::PatternsResult::at(&self.patterns, index).indicator_result(symbol),
+            self.regime.at(index).indicator_result(symbol),
+        ];
+        if close.len() >= 26
+            && let Some(macd) = &self.macd
+        {
+            results.push(macd.indicator_result_at(symbol, index));
+        }
+        results
+    }
+}
+
+/// Count `(bullish, bearish, neutral)` signals. Non-directional readings
+/// (e.g. "ranging") and indicators still warming up are not counted.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::{agreement_summary, tally_signals, IndicatorResult};
+/// let results = [
+///     IndicatorResult::new("a", "BTC/USDC").with_signal("bullish"),
+///     IndicatorResult::new("b", "BTC/USDC").with_signal("bullish"),
+///     IndicatorResult::new("c", "BTC/USDC").with_signal("bearish"),
+///     IndicatorResult::new("d", "BTC/USDC").with_signal("neutral"),
+///     IndicatorResult::new("e", "BTC/USDC").with_signal("ranging"),
+///     IndicatorResult::new("f", "BTC/USDC"),
+/// ];
+/// assert_eq!(tally_signals(&results), (2, 1, 1));
+/// assert_eq!(
+///     agreement_summary(tally_signals(&results)),
+///     "Indicator agreement: 2 bullish / 1 bearish / 1 neutral indicators"
+/// );
+/// ```
+pub fn tally_signals(results: &[IndicatorResult]) -> (usize, usize, usize) {
+    results
+        .iter()
+        .fold((0, 0, 0), |(bullish, bearish, neutral), result| match result.signal.as_deref() {
+            Some("bullish") => (bullish + 1, bearish, neutral),
+            Some("bearish") => (bullish, bearish + 1, neutral),
+            Some("neutral") => (bullish, bearish, neutral + 1),
+            _ => (bullish, bearish, neutral),
+        })
+}
+
+/// One-line consensus for the LLM context.
+pub fn agreement_summary((bullish, bearish, neutral): (usize, usize, usize)) -> String {
+    format!(
+        "Indicator agreement: {} bullish / {} bearish / {} neutral indicators",
+        bullish, bearish, neutral
+    )
+}
+
+/// Run the directional indicators on the stored klines and tally their signals.
+pub async fn indicator_agreement(symbol: &str) -> color_eyre::Result<(usize, usize, usize)> {
+    let ohlc = Klines::new(symbol.into()).get_ohlc().await?;
+    Ok(tally_signals(&directional_signals(&ohlc, symbol)))
+}
+
 // pub trait indidator{
     
-// }
\ No newline at end of file
+// }
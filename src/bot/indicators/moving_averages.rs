@@ -0,0 +1,288 @@
+use serde::{Deserialize, Serialize};
+
+use crate::bot::indicators::series_until;
+
+/// Simple moving average aligned with the input series.
+///
+/// The result has the same length as `values`; entries inside the warm-up
+/// period (the first `period - 1` values) are `None`.
+pub fn sma(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut out = vec![None; values.len()];
+    if period == 0 || values.len() < period {
+        return out;
+    }
+
+    let mut sum: f64 = values[..period].iter().sum();
+    out[period - 1] = Some(sum / period as f64);
+    for i in period..values.len() {
+        sum += values[i] - values[i - period];
+        out[i] = Some(sum / period as f64);
+    }
+    out
+}
+
+/// Exponential moving average aligned with the input series.
+///
+/// Seeded with the SMA of the first `period` values, then smoothed with
+/// `alpha = 2 / (period + 1)`.
+pub fn ema(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut out = vec![None; values.len()];
+    if period == 0 || values.len() < period {
+        return out;
+    }
+
+    let alpha = 2.0 / (period as f64 + 1.0);
+    let mut prev = values[..period].iter().sum::<f64>() / period as f64;
+    out[period - 1] = Some(prev);
+    for i in period..values.len() {
+        prev = alpha * values[i] + (1.0 - alpha) * prev;
+        out[i] = Some(prev);
+    }
+    out
+}
+
+/// Double EMA, `2 * EMA - EMA(EMA)`, aligned with the input series.
+///
+/// Lags less than a plain EMA of the same period:
+///
+/// ```
+/// use botmarley::bot::indicators::moving_averages::{calculate_dema, calculate_tema, ema};
+/// let ramp: Vec<f64> = (0..60).map(f64::from).collect();
+/// let last = |series: Vec<Option<f64>>| series.last().copied().flatten().unwrap();
+/// let (ema, dema, tema) = (last(ema(&ramp, 10)), last(calculate_dema(&ramp, 10)), last(calculate_tema(&ramp, 10)));
+/// assert!(ema < dema && dema <= tema && tema <= 59.0 + 1e-9);
+/// ```
+pub fn calculate_dema(closes: &[f64], period: usize) -> Vec<Option<f64>> {
+    let ema1 = ema(closes, period);
+    let ema2 = on_defined(&ema1, |values| ema(values, period));
+    ema1.iter()
+        .zip(ema2.iter())
+        .map(|(e1, e2)| Some(2.0 * (*e1)? - (*e2)?))
+        .collect()
+}
+
+/// Triple EMA, `3 * EMA - 3 * EMA(EMA) + EMA(EMA(EMA))`, aligned with the input series.
+pub fn calculate_tema(closes: &[f64], period: usize) -> Vec<Option<f64>> {
+    let ema1 = ema(closes, period);
+    let ema2 = on_defined(&ema1, |values| ema(values, period));
+    let ema3 = on_defined(&ema2, |values| ema(values, period));
+    (0..closes.len())
+        .map(|i| Some(3.0 * ema1[i]? - 3.0 * ema2[i]? + ema3[i]?))
+        .collect()
+}
+
+/// Volume weighted moving average aligned with the input series.
+/// Windows without any volume have no value.
+pub fn vwma(closes: &[f64], volumes: &[f64], period: usize) -> Vec<Option<f64>> {
+    let len = closes.len().min(volumes.len());
+    let mut out = vec![None; closes.len()];
+    if period == 0 || len < period {
+        return out;
+    }
+    for (i, slot) in out.iter_mut().enumerate().take(len).skip(period - 1) {
+        let window = i + 1 - period..=i;
+        let volume: f64 = volumes[window.clone()].iter().sum();
+        if volume > 0.0 {
+            let weighted: f64 = window.map(|j| closes[j] * volumes[j]).sum();
+            *slot = Some(weighted / volume);
+        }
+    }
+    out
+}
+
+/// Linearly weighted moving average aligned with the input series; the
+/// newest value of each window weighs `period`, the oldest 1.
+pub fn wma(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut out = vec![None; values.len()];
+    if period == 0 || values.len() < period {
+        return out;
+    }
+    let weight_sum = (period * (period + 1)) as f64 / 2.0;
+    for (i, slot) in out.iter_mut().enumerate().skip(period - 1) {
+        let weighted: f64 = values[i + 1 - period..=i]
+            .iter()
+            .enumerate()
+            .map(|(w, v)| (w + 1) as f64 * v)
+            .sum();
+        *slot = Some(weighted / weight_sum);
+    }
+    out
+}
+
+/// Hull moving average, `WMA(2 * WMA(n / 2) - WMA(n), sqrt(n))`, aligned
+/// with the input series.
+///
+/// The first `period + sqrt(period) - 2` values are warm-up. Follows a step
+/// change much faster than an SMA of the same period:
+///
+/// ```
+/// use botmarley::bot::indicators::moving_averages::{calculate_hma, sma};
+/// let step: Vec<f64> = (0..80).map(|i| if i < 40 { 100.0 } else { 110.0 }).collect();
+/// let hma = calculate_hma(&step, 16);
+/// // sqrt(16) = 4 extra candles of smoothing after the WMA(16) warm-up
+/// assert_eq!(hma.iter().position(Option::is_some), Some(16 + 4 - 2));
+/// let reaches = |series: &[Option<f64>]| series.iter().position(|v| v.is_some_and(|v| v >= 109.0)).unwrap();
+/// assert!(reaches(&hma) + 5 < reaches(&sma(&step, 16)));
+/// ```
+pub fn calculate_hma(closes: &[f64], period: usize) -> Vec<Option<f64>> {
+    let half = wma(closes, (period / 2).max(1));
+    let full = wma(closes, period);
+    let raw: Vec<Option<f64>> = half
+        .iter()
+        .zip(full.iter())
+        .map(|(h, f)| Some(2.0 * (*h)? - (*f)?))
+        .collect();
+    let smoothing = ((period as f64).sqrt().round() as usize).max(1);
+    on_defined(&raw, |values| wma(values, smoothing))
+}
+
+/// Moving average flavour, `sma|ema|dema|tema|vwma|hma`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MaType {
+    Sma,
+    Ema,
+    Dema,
+    Tema,
+    Vwma,
+    Hma,
+}
+
+impl std::str::FromStr for MaType {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "sma" => Ok(MaType::Sma),
+            "ema" => Ok(MaType::Ema),
+            "dema" => Ok(MaType::Dema),
+            "tema" => Ok(MaType::Tema),
+            "vwma" => Ok(MaType::Vwma),
+            "hma" => Ok(MaType::Hma),
+            other => Err(color_eyre::eyre::eyre!("Unknown moving average type '{}' (use sma, ema, dema, tema, vwma or hma)", other)),
+        }
+    }
+}
+
+impl MaType {
+    pub fn label(self) -> &'static str {
+        match self {
+            MaType::Sma => "SMA",
+            MaType::Ema => "EMA",
+            MaType::Dema => "DEMA",
+            MaType::Tema => "TEMA",
+            MaType::Vwma => "VWMA",
+            MaType::Hma => "HMA",
+        }
+    }
+}
+
+/// The selected moving average of the closes; `volumes` is only used by VWMA.
+pub fn moving_average(ma_type: MaType, closes: &[f64], volumes: &[f64], period: usize) -> Vec<Option<f64>> {
+    match ma_type {
+        MaType::Sma => sma(closes, period),
+        MaType::Ema => ema(closes, period),
+        MaType::Dema => calculate_dema(closes, period),
+        MaType::Tema => calculate_tema(closes, period),
+        MaType::Vwma => vwma(closes, volumes, period),
+        MaType::Hma => calculate_hma(closes, period),
+    }
+}
+
+/// Latest value labelled with its type, e.g. `TEMA (20): 101.2345`. HMA,
+/// read mostly for its turns, also gets the slope: `HMA (20): 101.2345, rising`.
+pub fn moving_average_summary(ma_type: MaType, closes: &[f64], volumes: &[f64], period: usize) -> String {
+    let values = moving_average(ma_type, closes, volumes, period);
+    let mut defined = values.iter().rev().flatten();
+    match (defined.next(), defined.next()) {
+        (Some(last), Some(prev)) if ma_type == MaType::Hma => {
+            let slope = match last.partial_cmp(prev) {
+                Some(std::cmp::Ordering::Greater) => "rising",
+                Some(std::cmp::Ordering::Less) => "falling",
+                _ => "flat",
+            };
+            format!("{} ({}): {:.4}, {}", ma_type.label(), period, last, slope)
+        }
+        (Some(value), _) => format!("{} ({}): {:.4}", ma_type.label(), period, value),
+        (None, _) => format!("{} ({}): not enough data", ma_type.label(), period),
+    }
+}
+
+/// Runs `f` over the defined tail of an aligned series and re-pads the result,
+/// so chained averages (EMA of an EMA, SMA of an oscillator...) keep their
+/// kline alignment.
+pub fn on_defined<F>(series: &[Option<f64>], f: F) -> Vec<Option<f64>>
+where
+    F: Fn(&[f64]) -> Vec<Option<f64>>,
+{
+    let start = series
+        .iter()
+        .position(Option::is_some)
+        .unwrap_or(series.len());
+    let tail: Vec<f64> = series[start..].iter().map(|v| v.unwrap_or(0.0)).collect();
+
+    let mut out = vec![None; start];
+    out.extend(f(&tail));
+    out
+}
+
+/// Last defined value of an aligned series.
+pub fn last_defined(series: &[Option<f64>]) -> Option<f64> {
+    series.iter().rev().find_map(|v| *v)
+}
+
+/// EMA periods of the trend ribbon, fastest first.
+pub const RIBBON_PERIODS: [usize; 3] = [9, 21, 50];
+
+/// Distills the EMA ribbon into a score from -3 to +3.
+///
+/// Each adjacent pair of price > EMA9 > EMA21 > EMA50 adds one point when
+/// ordered bullishly and takes one away when ordered bearishly, so a tangled
+/// ribbon lands near 0. A fully ordered ribbon whose slowest EMA still slopes
+/// the other way loses a point. Returns 0 until EMA50 is warmed up.
+pub fn ema_ribbon_score(closes: &[f64]) -> i32 {
+    let ribbon: Vec<Vec<Option<f64>>> = RIBBON_PERIODS.iter().map(|p| ema(closes, *p)).collect();
+    ribbon_score_at(closes, &ribbon, closes.len().saturating_sub(1))
+}
+
+/// `ema_ribbon_score` of the closes up to candle `index`, with the ribbon
+/// EMAs (in `RIBBON_PERIODS` order) already computed over a longer history.
+pub fn ribbon_score_at(closes: &[f64], ribbon: &[Vec<Option<f64>>], index: usize) -> i32 {
+    let Some(&price) = series_until(closes, index).last() else {
+        return 0;
+    };
+    let Some(latest) = ribbon.iter().map(|line| last_defined(series_until(line, index))).collect::<Option<Vec<f64>>>() else {
+        return 0;
+    };
+
+    let mut chain = vec![price];
+    chain.extend(latest);
+    let mut score: i32 = chain
+        .windows(2)
+        .map(|w| match w[0].partial_cmp(&w[1]) {
+            Some(std::cmp::Ordering::Greater) => 1,
+            Some(std::cmp::Ordering::Less) => -1,
+            _ => 0,
+        })
+        .sum();
+
+    if score.abs() == 3
+        && let Some(slowest) = ribbon.last()
+        && let [.., Some(prev), Some(last)] = series_until(slowest, index)
+        && (last - prev).signum() as i32 != score.signum()
+    {
+        score -= score.signum();
+    }
+    score
+}
+
+/// Human readable label for an `ema_ribbon_score`.
+pub fn ribbon_label(score: i32) -> &'static str {
+    match score {
+        3 => "strong uptrend",
+        1..=2 => "uptrend",
+        -2..=-1 => "downtrend",
+        -3 => "strong downtrend",
+        _ => "no clear trend",
+    }
+}
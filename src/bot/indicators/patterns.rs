@@ -0,0 +1,300 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::bot::indicators::IndicatorResult;
+use crate::bot::klines::{Klines, KlinesOHLC};
+
+/// Patterns completed within this many of the latest candles are reported
+pub const RECENT_CANDLES: usize = 10;
+
+/// Classic candlestick formations.
+pub struct CandlestickPatterns {
+    pub klines: Arc<Mutex<Klines>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PatternKind {
+    Doji,
+    Hammer,
+    ShootingStar,
+    BullishEngulfing,
+    BearishEngulfing,
+    MorningStar,
+    EveningStar,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PatternBias {
+    Bullish,
+    Bearish,
+    /// Indecision, e.g. a doji
+    Neutral,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pattern {
+    /// Candle completing the pattern
+    pub index: usize,
+    pub kind: PatternKind,
+    pub bias: PatternBias,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternsResult {
+    /// Patterns completed on the last `RECENT_CANDLES` candles, oldest first
+    pub patterns: Vec<Pattern>,
+    pub total_klines: usize,
+}
+
+impl PatternKind {
+    pub fn bias(self) -> PatternBias {
+        match self {
+            PatternKind::Doji => PatternBias::Neutral,
+            PatternKind::Hammer | PatternKind::BullishEngulfing | PatternKind::MorningStar => PatternBias::Bullish,
+            PatternKind::ShootingStar | PatternKind::BearishEngulfing | PatternKind::EveningStar => PatternBias::Bearish,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PatternKind::Doji => "Doji",
+            PatternKind::Hammer => "Hammer",
+            PatternKind::ShootingStar => "Shooting star",
+            PatternKind::BullishEngulfing => "Bullish engulfing",
+            PatternKind::BearishEngulfing => "Bearish engulfing",
+            PatternKind::MorningStar => "Morning star",
+            PatternKind::EveningStar => "Evening star",
+        }
+    }
+}
+
+/// Body and shadow sizes of one candle.
+struct Candle {
+    open: f64,
+    close: f64,
+    body: f64,
+    range: f64,
+    upper_shadow: f64,
+    lower_shadow: f64,
+}
+
+impl Candle {
+    fn at(ohlc: &KlinesOHLC, i: usize) -> Self {
+        let (open, close, high, low) = (ohlc.open[i], ohlc.close[i], ohlc.high[i], ohlc.low[i]);
+        Candle {
+            open,
+            close,
+            body: (close - open).abs(),
+            range: high - low,
+            upper_shadow: high - open.max(close),
+            lower_shadow: open.min(close) - low,
+        }
+    }
+
+    fn is_bullish(&self) -> bool {
+        self.close > self.open
+    }
+
+    fn is_bearish(&self) -> bool {
+        self.close < self.open
+    }
+
+    fn is_doji(&self) -> bool {
+        self.range > 0.0 && self.body <= self.range * 0.1
+    }
+
+    fn midpoint(&self) -> f64 {
+        (self.open + self.close) / 2.0
+    }
+}
+
+/// Closes falling (rising) over the three candles before `i`.
+fn prior_trend(close: &[f64], i: usize) -> Option<PatternBias> {
+    if i < 3 {
+        return None;
+    }
+    if close[i - 1] < close[i - 3] {
+        Some(PatternBias::Bearish)
+    } else if close[i - 1] > close[i - 3] {
+        Some(PatternBias::Bullish)
+    } else {
+        None
+    }
+}
+
+/// Single candle patterns: doji, hammer (after a decline) and shooting star (after a rise).
+fn single_candle(ohlc: &KlinesOHLC, i: usize) -> Option<PatternKind> {
+    let candle = Candle::at(ohlc, i);
+    if candle.is_doji() {
+        return Some(PatternKind::Doji);
+    }
+    if candle.body == 0.0 {
+        return None;
+    }
+    let trend = prior_trend(&ohlc.close, i);
+    if candle.lower_shadow >= 2.0 * candle.body
+        && candle.upper_shadow <= candle.body
+        && trend == Some(PatternBias::Bearish)
+    {
+        Some(PatternKind::Hammer)
+    } else if candle.upper_shadow >= 2.0 * candle.body
+        && candle.lower_shadow <= candle.body
+        && trend == Some(PatternBias::Bullish)
+    {
+        Some(PatternKind::ShootingStar)
+    } else {
+        None
+    }
+}
+
+/// The candle's body fully covers the opposite colored body before it.
+fn engulfing(ohlc: &KlinesOHLC, i: usize) -> Option<PatternKind> {
+    let (prev, curr) = (Candle::at(ohlc, i - 1), Candle::at(ohlc, i));
+    if curr.body <= prev.body {
+        return None;
+    }
+    if prev.is_bearish() && curr.is_bullish() && curr.open <= prev.close && curr.close >= prev.open {
+        Some(PatternKind::BullishEngulfing)
+    } else if prev.is_bullish() && curr.is_bearish() && curr.open >= prev.close && curr.close <= prev.open {
+        Some(PatternKind::BearishEngulfing)
+    } else {
+        None
+    }
+}
+
+/// Long candle, small body, then a candle closing past the first one's midpoint.
+fn star(ohlc: &KlinesOHLC, i: usize) -> Option<PatternKind> {
+    let (first, middle, last) = (Candle::at(ohlc, i - 2), Candle::at(ohlc, i - 1), Candle::at(ohlc, i));
+    let long_first = first.range > 0.0 && first.body >= first.range * 0.5;
+    if !long_first || middle.body > first.body * 0.3 {
+        return None;
+    }
+    if first.is_bearish() && last.is_bullish() && last.close > first.midpoint() {
+        Some(PatternKind::MorningStar)
+    } else if first.is_bullish() && last.is_bearish() && last.close < first.midpoint() {
+        Some(PatternKind::EveningStar)
+    } else {
+        None
+    }
+}
+
+/// Every pattern in the series, ordered by the candle completing it.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::patterns::{detect_candlestick_patterns, PatternKind};
+/// use botmarley::bot::klines::KlinesOHLC;
+/// // (open, high, low, close) rows
+/// let kinds = |rows: &[(f64, f64, f64, f64)]| {
+///     let ohlc = KlinesOHLC {
+///         time: (0..rows.len() as i64).collect(),
+///         open: rows.iter().map(|r| r.0).collect(),
+///         high: rows.iter().map(|r| r.1).collect(),
+///         low: rows.iter().map(|r| r.2).collect(),
+///         close: rows.iter().map(|r| r.3).collect(),
+///         volume: vec![1.0; rows.len()],
+///         count: rows.len(),
+///     };
+///     detect_candlestick_patterns(&ohlc).into_iter().map(|p| (p.index, p.kind)).collect::<Vec<_>>()
+/// };
+/// // body 0.1 of a 4 wide range
+/// assert_eq!(kinds(&[(100.0, 102.0, 98.0, 100.1)]), vec![(0, PatternKind::Doji)]);
+/// // three falling closes, then a long lower shadow closing near the high
+/// let hammer = [(111.0, 112.0, 109.5, 110.0), (109.0, 110.0, 107.5, 108.0), (107.0, 108.0, 105.5, 106.0), (104.0, 105.2, 101.0, 105.0)];
+/// assert_eq!(kinds(&hammer), vec![(3, PatternKind::Hammer)]);
+/// // the same candle without the decline is no hammer
+/// assert!(kinds(&hammer[3..]).is_empty());
+/// assert_eq!(kinds(&[(101.0, 101.5, 99.5, 100.0), (99.8, 102.5, 99.5, 102.0)]), vec![(1, PatternKind::BullishEngulfing)]);
+/// assert_eq!(kinds(&[(100.0, 101.5, 99.5, 101.0), (101.2, 101.5, 98.5, 99.0)]), vec![(1, PatternKind::BearishEngulfing)]);
+/// // a bigger body that doesn't cover the previous one isn't engulfing
+/// assert!(kinds(&[(101.0, 101.5, 99.5, 100.0), (100.5, 103.5, 100.0, 103.0)]).is_empty());
+/// ```
+pub fn detect_candlestick_patterns(ohlc: &KlinesOHLC) -> Vec<Pattern> {
+    let len = ohlc.open.len().min(ohlc.close.len()).min(ohlc.high.len()).min(ohlc.low.len());
+    let mut patterns = Vec::new();
+    for i in 0..len {
+        let found = [
+            single_candle(ohlc, i),
+            (i >= 1).then(|| engulfing(ohlc, i)).flatten(),
+            (i >= 2).then(|| star(ohlc, i)).flatten(),
+        ];
+        patterns.extend(found.into_iter().flatten().map(|kind| Pattern {
+            index: i,
+            kind,
+            bias: kind.bias(),
+        }));
+    }
+    patterns
+}
+
+impl PatternsResult {
+    pub fn from_ohlc(ohlc: &KlinesOHLC) -> Self {
+        let total_klines = ohlc.close.len();
+        let recent_from = total_klines.saturating_sub(RECENT_CANDLES);
+        PatternsResult {
+            patterns: detect_candlestick_patterns(ohlc)
+                .into_iter()
+                .filter(|p| p.index >= recent_from)
+                .collect(),
+            total_klines,
+        }
+    }
+
+    /// The result `from_ohlc` gives for the history up to candle `index`,
+    /// read from the patterns of a longer history. Detection only looks
+    /// back, so no pattern past `index` can change what came before.
+    pub fn at(patterns: &[Pattern], index: usize) -> Self {
+        let recent_from = (index + 1).saturating_sub(RECENT_CANDLES);
+        let start = patterns.partition_point(|p| p.index < recent_from);
+        let end = patterns.partition_point(|p| p.index <= index);
+        PatternsResult {
+            patterns: patterns[start..end].to_vec(),
+            total_klines: index + 1,
+        }
+    }
+
+    fn count(&self, bias: PatternBias) -> usize {
+        self.patterns.iter().filter(|p| p.bias == bias).count()
+    }
+
+    pub fn indicator_result(&self, symbol: &str) -> IndicatorResult {
+        let (bullish, bearish) = (self.count(PatternBias::Bullish), self.count(PatternBias::Bearish));
+        let signal = match bullish.cmp(&bearish) {
+            std::cmp::Ordering::Greater => "bullish",
+            std::cmp::Ordering::Less => "bearish",
+            std::cmp::Ordering::Equal => "neutral",
+        };
+        IndicatorResult::new("candlestick_patterns", symbol)
+            .with_value("bullish", Some(bullish as f64))
+            .with_value("bearish", Some(bearish as f64))
+            .with_value("neutral", Some(self.count(PatternBias::Neutral) as f64))
+            .with_signal(signal)
+    }
+
+    pub fn summary(&self) -> String {
+        if self.patterns.is_empty() {
+            return format!("Candlestick patterns: none in the last {} candles", RECENT_CANDLES);
+        }
+        let mut lines = vec![format!("Candlestick patterns (last {} candles):", RECENT_CANDLES)];
+        for pattern in &self.patterns {
+            let ago = self.total_klines - 1 - pattern.index;
+            lines.push(format!(
+                "  {} ({:?}), {} candle(s) ago",
+                pattern.kind.label(),
+                pattern.bias,
+                ago
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+impl CandlestickPatterns {
+    pub async fn calculate(&mut self) -> color_eyre::Result<PatternsResult> {
+        let ohlc = self.klines.lock().await.get_ohlc().await?;
+        Ok(PatternsResult::from_ohlc(&ohlc))
+    }
+}
@@ -0,0 +1,117 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::bot::indicators::{series_until, IndicatorResult};
+use crate::bot::indicators::atr::calculate_atr;
+use crate::bot::indicators::moving_averages::{last_defined, sma};
+use crate::bot::klines::{Klines, KlinesOHLC};
+
+/// Readings beyond +-3 ATR from the average are treated as breakouts
+pub const PGO_BREAKOUT: f64 = 3.0;
+
+/// Pretty Good Oscillator: distance of the close from its SMA, in ATRs.
+pub struct Pgo {
+    pub klines: Arc<Mutex<Klines>>,
+    pub period: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PgoResult {
+    pub period: usize,
+    pub values: Vec<Option<f64>>,
+}
+
+/// `(close - sma) / atr`, aligned with the klines. Candles with a zero ATR
+/// (flat market) have no reading.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::pgo::calculate_pgo;
+/// use botmarley::bot::klines::KlinesOHLC;
+/// let ohlc = |close: Vec<f64>, range: f64| KlinesOHLC {
+///     time: (0..close.len() as i64).collect(),
+///     open: close.clone(),
+///     high: close.iter().map(|c| c + range).collect(),
+///     low: close.iter().map(|c| c - range).collect(),
+///     volume: vec![1.0; close.len()],
+///     count: close.len(),
+///     close,
+/// };
+/// // true ranges 2, 2, 5 (gap from 100 up to a 105 high): ATR(2) is 2 then 2 + (5 - 2) / 2 = 3.5
+/// // SMA(2) of the last two closes is 102, so the close is (104 - 102) / 3.5 ATRs above it
+/// let values = calculate_pgo(&ohlc(vec![100.0, 100.0, 104.0], 1.0), 2);
+/// assert_eq!(values[0], None);
+/// assert_eq!(values[1], Some(0.0));
+/// assert!((values[2].unwrap() - 2.0 / 3.5).abs() < 1e-12);
+/// // no range at all: zero ATR, no reading instead of a division by zero
+/// assert!(calculate_pgo(&ohlc(vec![100.0; 5], 0.0), 2).iter().all(Option::is_none));
+/// ```
+pub fn calculate_pgo(ohlc: &KlinesOHLC, period: usize) -> Vec<Option<f64>> {
+    let average = sma(&ohlc.close, period);
+    let atr = calculate_atr(&ohlc.high, &ohlc.low, &ohlc.close, period);
+
+    ohlc.close
+        .iter()
+        .zip(average.iter().zip(atr.iter()))
+        .map(|(close, (avg, atr))| match (avg, atr) {
+            (Some(avg), Some(atr)) if *atr > 0.0 => Some((close - avg) / atr),
+            _ => None,
+        })
+        .collect()
+}
+
+impl PgoResult {
+    fn bias(value: f64) -> &'static str {
+        if value > PGO_BREAKOUT {
+            "bullish"
+        } else if value < -PGO_BREAKOUT {
+            "bearish"
+        } else {
+            "neutral"
+        }
+    }
+
+    pub fn indicator_result(&self, symbol: &str) -> IndicatorResult {
+        self.indicator_result_at(symbol, self.values.len().saturating_sub(1))
+    }
+
+    pub fn indicator_result_at(&self, symbol: &str, index: usize) -> IndicatorResult {
+        let current = last_defined(series_until(&self.values, index));
+        let result = IndicatorResult::new("pgo", symbol).with_value("pgo", current);
+        match current {
+            Some(v) => result.with_signal(Self::bias(v)),
+            None => result,
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        self.summary_at(self.values.len().saturating_sub(1))
+    }
+
+    pub fn summary_at(&self, index: usize) -> String {
+        let Some(v) = last_defined(series_until(&self.values, index)) else {
+            return format!("PGO ({}): not enough data", self.period);
+        };
+        let reading = if v > PGO_BREAKOUT {
+            "strong bullish breakout"
+        } else if v < -PGO_BREAKOUT {
+            "strong bearish breakout"
+        } else {
+            "within normal range"
+        };
+        format!("PGO ({}): {:+.2} ATR from SMA, {}", self.period, v, reading)
+    }
+}
+
+impl Pgo {
+    pub async fn calculate(&mut self) -> color_eyre::Result<PgoResult> {
+        let ohlc = self.klines.lock().await.get_ohlc().await?;
+        Ok(PgoResult {
+            period: self.period,
+            values: calculate_pgo(&ohlc, self.period),
+        })
+    }
+}
@@ -0,0 +1,123 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::bot::indicators::{series_until, IndicatorResult};
+use crate::bot::indicators::moving_averages::{last_defined, sma};
+use crate::bot::klines::{Klines, KlinesOHLC};
+
+/// Qstick: moving average of `close - open`, above zero while buyers
+/// dominate the candles.
+pub struct Qstick {
+    pub klines: Arc<Mutex<Klines>>,
+    pub period: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QstickCross {
+    /// Crossed above zero
+    Bullish,
+    /// Crossed below zero
+    Bearish,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QstickResult {
+    pub period: usize,
+    pub values: Vec<Option<f64>>,
+    /// Zero-line cross on the latest candle
+    pub cross: Option<QstickCross>,
+}
+
+/// SMA of the candle bodies, aligned with the candles.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::qstick::calculate_qstick;
+/// let open = [10.0, 11.0, 12.0, 13.0, 14.0];
+/// let close = [11.0, 13.0, 9.0, 13.0, 12.0];
+/// // bodies +1, +2, -3, 0, -2
+/// let qstick = calculate_qstick(&open, &close, 3);
+/// assert_eq!(qstick[..2], [None, None]);
+/// assert_eq!(qstick[2], Some(0.0));
+/// assert!((qstick[3].unwrap() - -1.0 / 3.0).abs() < 1e-9);
+/// assert!((qstick[4].unwrap() - -5.0 / 3.0).abs() < 1e-9);
+/// ```
+pub fn calculate_qstick(open: &[f64], close: &[f64], period: usize) -> Vec<Option<f64>> {
+    let bodies: Vec<f64> = close.iter().zip(open).map(|(c, o)| c - o).collect();
+    let mut values = sma(&bodies, period);
+    values.resize(close.len(), None);
+    values
+}
+
+/// Zero-line cross between the last two defined values.
+pub fn detect_cross(values: &[Option<f64>]) -> Option<QstickCross> {
+    let mut defined = values.iter().rev().flatten();
+    let (current, previous) = (*defined.next()?, *defined.next()?);
+    if previous <= 0.0 && current > 0.0 {
+        Some(QstickCross::Bullish)
+    } else if previous >= 0.0 && current < 0.0 {
+        Some(QstickCross::Bearish)
+    } else {
+        None
+    }
+}
+
+impl QstickResult {
+    pub fn from_ohlc(ohlc: &KlinesOHLC, period: usize) -> Self {
+        let values = calculate_qstick(&ohlc.open, &ohlc.close, period);
+        QstickResult {
+            period,
+            cross: detect_cross(&values),
+            values,
+        }
+    }
+
+    pub fn indicator_result(&self, symbol: &str) -> IndicatorResult {
+        self.indicator_result_at(symbol, self.values.len().saturating_sub(1))
+    }
+
+    pub fn indicator_result_at(&self, symbol: &str, index: usize) -> IndicatorResult {
+        let current = last_defined(series_until(&self.values, index));
+        let result = IndicatorResult::new("qstick", symbol).with_value("qstick", current);
+        match current {
+            Some(v) if v > 0.0 => result.with_signal("bullish"),
+            Some(v) if v < 0.0 => result.with_signal("bearish"),
+            Some(_) => result.with_signal("neutral"),
+            None => result,
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        self.summary_at(self.values.len().saturating_sub(1))
+    }
+
+    pub fn summary_at(&self, index: usize) -> String {
+        let values = series_until(&self.values, index);
+        let Some(qstick) = last_defined(values) else {
+            return format!("Qstick ({}): not enough data", self.period);
+        };
+        let pressure = if qstick > 0.0 {
+            "buyers dominate"
+        } else if qstick < 0.0 {
+            "sellers dominate"
+        } else {
+            "balanced"
+        };
+        let cross = match detect_cross(values) {
+            Some(QstickCross::Bullish) => ", just crossed above zero",
+            Some(QstickCross::Bearish) => ", just crossed below zero",
+            None => "",
+        };
+        format!("Qstick ({}): {:+.4} ({}{})", self.period, qstick, pressure, cross)
+    }
+}
+
+impl Qstick {
+    pub async fn calculate(&mut self) -> color_eyre::Result<QstickResult> {
+        let ohlc = self.klines.lock().await.get_ohlc().await?;
+        Ok(QstickResult::from_ohlc(&ohlc, self.period))
+    }
+}
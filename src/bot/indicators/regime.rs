@@ -0,0 +1,183 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::bot::indicators::{series_until, IndicatorResult};
+use crate::bot::indicators::adx::{calculate_dmi, Dmi};
+use crate::bot::indicators::atr::calculate_atr;
+use crate::bot::indicators::choppiness::{calculate_choppiness, CHOP_RANGING};
+use crate::bot::indicators::moving_averages::last_defined;
+use crate::bot::klines::{Klines, KlinesOHLC};
+
+pub const REGIME_PERIOD: usize = 14;
+/// ADX at or above this means a trend is in place
+pub const ADX_TRENDING: f64 = 25.0;
+/// ATR as percent of the close above which a trendless market is volatile
+pub const VOLATILE_ATR_PCT: f64 = 1.0;
+
+/// High level market label combining ADX, choppiness and ATR percent.
+pub struct MarketRegimeClassifier {
+    pub klines: Arc<Mutex<Klines>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Regime {
+    TrendingUp,
+    TrendingDown,
+    Ranging,
+    Volatile,
+}
+
+impl std::fmt::Display for Regime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            Regime::TrendingUp => "trending up",
+            Regime::TrendingDown => "trending down",
+            Regime::Ranging => "ranging",
+            Regime::Volatile => "volatile",
+        };
+        f.write_str(label)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegimeResult {
+    /// `None` until every input has warmed up
+    pub regime: Option<Regime>,
+    pub adx: Option<f64>,
+    pub plus_di: Option<f64>,
+    pub minus_di: Option<f64>,
+    pub choppiness: Option<f64>,
+    pub atr_pct: Option<f64>,
+}
+
+/// A strong ADX outside a choppy market is a trend, its direction given by
+/// the dominant DI. Without a trend, wide candles make the market volatile,
+/// otherwise it's ranging.
+pub fn classify(adx: f64, plus_di: f64, minus_di: f64, choppiness: f64, atr_pct: f64) -> Regime {
+    if adx >= ADX_TRENDING && choppiness < CHOP_RANGING {
+        if plus_di >= minus_di { Regime::TrendingUp } else { Regime::TrendingDown }
+    } else if atr_pct >= VOLATILE_ATR_PCT {
+        Regime::Volatile
+    } else {
+        Regime::Ranging
+    }
+}
+
+/// The DMI, choppiness and ATR series behind `RegimeResult`, computed once
+/// so any candle of the history can be classified.
+#[derive(Debug, Clone)]
+pub struct RegimeSeries {
+    pub dmi: Dmi,
+    pub choppiness: Vec<Option<f64>>,
+    pub atr: Vec<Option<f64>>,
+    pub close: Vec<f64>,
+}
+
+impl RegimeSeries {
+    pub fn from_ohlc(ohlc: &KlinesOHLC) -> Self {
+        RegimeSeries {
+            dmi: calculate_dmi(&ohlc.high, &ohlc.low, &ohlc.close, REGIME_PERIOD),
+            choppiness: calculate_choppiness(ohlc, REGIME_PERIOD),
+            atr: calculate_atr(&ohlc.high, &ohlc.low, &ohlc.close, REGIME_PERIOD),
+            close: ohlc.close.clone(),
+        }
+    }
+
+    /// Classify candle `index` from the values known at that point.
+    pub fn at(&self, index: usize) -> RegimeResult {
+        let adx = last_defined(series_until(&self.dmi.adx, index));
+        let plus_di = last_defined(series_until(&self.dmi.plus_di, index));
+        let minus_di = last_defined(series_until(&self.dmi.minus_di, index));
+        let choppiness = last_defined(series_until(&self.choppiness, index));
+        let atr_pct = match (last_defined(series_until(&self.atr, index)), series_until(&self.close, index).last()) {
+            (Some(atr), Some(close)) if *close > 0.0 => Some(atr / close * 100.0),
+            _ => None,
+        };
+
+        let regime = match (adx, plus_di, minus_di, choppiness, atr_pct) {
+            (Some(adx), Some(plus), Some(minus), Some(chop), Some(atr_pct)) => Some(classify(adx, plus, minus, chop, atr_pct)),
+            _ => None,
+        };
+        RegimeResult {
+            regime,
+            adx,
+            plus_di,
+            minus_di,
+            choppiness,
+            atr_pct,
+        }
+    }
+}
+
+impl RegimeResult {
+    /// Classify the latest candle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use botmarley::bot::indicators::regime::{Regime, RegimeResult};
+    /// use botmarley::bot::klines::KlinesOHLC;
+    /// let ohlc = |closes: Vec<f64>, half_range: f64| KlinesOHLC {
+    ///     time: (0..closes.len() as i64).collect(),
+    ///     open: closes.clone(),
+    ///     high: closes.iter().map(|c| c + half_range).collect(),
+    ///     low: closes.iter().map(|c| c - half_range).collect(),
+    ///     volume: vec![1.0; closes.len()],
+    ///     count: closes.len(),
+    ///     close: closes,
+    /// };
+    /// let trend: Vec<f64> = (0..60).map(|i| 100.0 + i as f64).collect();
+    /// let range: Vec<f64> = (0..60).map(|i| if i % 2 == 0 { 100.0 } else { 100.1 }).collect();
+    /// let chop: Vec<f64> = (0..60).map(|i| if i % 2 == 0 { 100.0 } else { 104.0 }).collect();
+    /// assert_eq!(RegimeResult::from_ohlc(&ohlc(trend, 0.5)).regime, Some(Regime::TrendingUp));
+    /// assert_eq!(RegimeResult::from_ohlc(&ohlc(range, 0.05)).regime, Some(Regime::Ranging));
+    /// assert_eq!(RegimeResult::from_ohlc(&ohlc(chop, 1.0)).regime, Some(Regime::Volatile));
+    /// ```
+    pub fn from_ohlc(ohlc: &KlinesOHLC) -> Self {
+        RegimeSeries::from_ohlc(ohlc).at(ohlc.close.len().saturating_sub(1))
+    }
+
+    pub fn indicator_result(&self, symbol: &str) -> IndicatorResult {
+        let result = IndicatorResult::new("regime", symbol)
+            .with_value("adx", self.adx)
+            .with_value("choppiness", self.choppiness)
+            .with_value("atr_pct", self.atr_pct);
+        match self.regime {
+            Some(Regime::TrendingUp) => result.with_signal("bullish"),
+            Some(Regime::TrendingDown) => result.with_signal("bearish"),
+            Some(Regime::Ranging) => result.with_signal("ranging"),
+            Some(Regime::Volatile) => result.with_signal("volatile"),
+            None => result,
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let (Some(regime), Some(adx), Some(chop), Some(atr_pct)) = (self.regime, self.adx, self.choppiness, self.atr_pct) else {
+            return "Market regime: not enough data".to_string();
+        };
+        let approach = match regime {
+            Regime::TrendingUp | Regime::TrendingDown => "follow the trend, avoid fading it",
+            Regime::Ranging => "trade the range, fade the extremes",
+            Regime::Volatile => "reduce size, wait for structure",
+        };
+        format!(
+            "Market regime: {} (ADX {:.1}, choppiness {:.1}, ATR {:.2}%), {}",
+            regime, adx, chop, atr_pct, approach
+        )
+    }
+}
+
+impl MarketRegimeClassifier {
+    pub async fn calculate(&mut self) -> color_eyre::Result<RegimeResult> {
+        let ohlc = self.klines.lock().await.get_ohlc().await?;
+        Ok(RegimeResult::from_ohlc(&ohlc))
+    }
+}
+
+/// Regime of the stored klines of `symbol`.
+pub async fn classify_regime(symbol: &str) -> color_eyre::Result<RegimeResult> {
+    let ohlc = Klines::new(symbol.into()).get_ohlc().await?;
+    Ok(RegimeResult::from_ohlc(&ohlc))
+}
@@ -0,0 +1,181 @@
+use serde::{Deserialize, Serialize};
+
+use crate::bot::indicators::{option_at_kline, IndicatorResult};
+
+/// Candles on each side a swing high/low has to exceed
+pub const DIVERGENCE_SWING_STRENGTH: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DivergenceKind {
+    /// Lower low in price, higher low in RSI
+    Bullish,
+    /// Higher high in price, lower high in RSI
+    Bearish,
+}
+
+impl std::fmt::Display for DivergenceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            DivergenceKind::Bullish => "bullish",
+            DivergenceKind::Bearish => "bearish",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RsiDivergence {
+    pub kind: DivergenceKind,
+    /// Candles between the second swing and the latest close
+    pub candles_ago: usize,
+}
+
+/// Indices of the swing highs (`highs`) or lows of `values`: points beyond
+/// every value `strength` candles to either side.
+fn swing_points(values: &[f64], strength: usize, highs: bool) -> Vec<usize> {
+    if strength == 0 || values.len() <= 2 * strength {
+        return Vec::new();
+    }
+    (strength..values.len() - strength)
+        .filter(|&i| {
+            (i - strength..=i + strength)
+                .filter(|&j| j != i)
+                .all(|j| if highs { values[j] < values[i] } else { values[j] > values[i] })
+        })
+        .collect()
+}
+
+/// Divergence between the last two swing highs or lows of `closes` and the
+/// RSI at the same candles. `rsi` may be aligned with the closes or trimmed
+/// at the front. The more recent one wins when both kinds are present.
+pub fn detect_rsi_divergence(closes: &[f64], rsi: &[Option<f64>], strength: usize) -> Option<RsiDivergence> {
+    let index = closes.len().checked_sub(1)?;
+    let swings = [swing_points(closes, strength, true), swing_points(closes, strength, false)];
+    divergence_at(closes, rsi, &swings, strength, index)
+}
+
+/// `detect_rsi_divergence` over the closes up to `index`, given the swing
+/// highs and lows of the whole series. A swing is only known `strength`
+/// candles after it.
+fn divergence_at(closes: &[f64], rsi: &[Option<f64>], [highs, lows]: &[Vec<usize>; 2], strength: usize, index: usize) -> Option<RsiDivergence> {
+    let rsi_at = |i: usize| option_at_kline(rsi, closes.len(), i);
+    let last_pair = |swings: &[usize]| -> Option<(usize, usize)> {
+        let known = &swings[..swings.partition_point(|&i| i + strength <= index)];
+        let mut defined = known.iter().rev().copied().filter(|&i| rsi_at(i).is_some());
+        let second = defined.next()?;
+        Some((defined.next()?, second))
+    };
+
+    let bearish = last_pair(highs).and_then(|(first, second)| {
+        (closes[second] > closes[first] && rsi_at(second)? < rsi_at(first)?).then_some((DivergenceKind::Bearish, second))
+    });
+    let bullish = last_pair(lows).and_then(|(first, second)| {
+        (closes[second] < closes[first] && rsi_at(second)? > rsi_at(first)?).then_some((DivergenceKind::Bullish, second))
+    });
+
+    [bearish, bullish]
+        .into_iter()
+        .flatten()
+        .max_by_key(|(_, swing)| *swing)
+        .map(|(kind, swing)| RsiDivergence {
+            kind,
+            candles_ago: index - swing,
+        })
+}
+
+/// RSI with the swing points of its closes, found once so the reading of
+/// `rsi_indicator_result` can be taken at any candle of the history.
+#[derive(Debug, Clone)]
+pub struct RsiSeries {
+    pub closes: Vec<f64>,
+    /// Aligned with the closes or trimmed at the front
+    pub rsi: Vec<Option<f64>>,
+    pub swings: [Vec<usize>; 2],
+}
+
+impl RsiSeries {
+    pub fn new(closes: &[f64], rsi: Vec<Option<f64>>) -> Self {
+        RsiSeries {
+            closes: closes.to_vec(),
+            rsi,
+            swings: [
+                swing_points(closes, DIVERGENCE_SWING_STRENGTH, true),
+                swing_points(closes, DIVERGENCE_SWING_STRENGTH, false),
+            ],
+        }
+    }
+
+    pub fn indicator_result_at(&self, symbol: &str, index: usize) -> IndicatorResult {
+        let current = option_at_kline(&self.rsi, self.closes.len(), index);
+        rsi_reading(symbol, current, || {
+            divergence_at(&self.closes, &self.rsi, &self.swings, DIVERGENCE_SWING_STRENGTH, index)
+        })
+    }
+}
+
+/// Prompt line of the latest RSI value, followed by a warning when price
+/// and RSI diverge. `None` without a current RSI value.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::rsi::format_rsi;
+/// // price makes a higher high at index 6, RSI a lower one
+/// let closes = [1.0, 2.0, 5.0, 2.0, 1.0, 2.0, 6.0, 2.0, 1.0, 1.5];
+/// let mut rsi = vec![Some(50.0); closes.len()];
+/// rsi[2] = Some(70.0);
+/// rsi[6] = Some(60.0);
+/// let text = format_rsi(&closes, &rsi, 14).unwrap();
+/// assert!(text.starts_with("RSI (14): 50.00"));
+/// assert!(text.contains("⚠️ bearish divergence detected 3 candles ago"), "{}", text);
+///
+/// rsi[6] = Some(75.0);
+/// assert_eq!(format_rsi(&closes, &rsi, 14).unwrap(), "RSI (14): 50.00");
+/// assert_eq!(format_rsi(&closes, &[None], 14), None);
+/// ```
+pub fn format_rsi(closes: &[f64], rsi: &[Option<f64>], period: usize) -> Option<String> {
+    let current = rsi.last().copied().flatten()?;
+    let mut text = format!("RSI ({}): {:.2}", period, current);
+    if let Some(divergence) = detect_rsi_divergence(closes, rsi, DIVERGENCE_SWING_STRENGTH) {
+        let swing = match divergence.kind {
+            DivergenceKind::Bearish => "price made a higher high, RSI a lower high",
+            DivergenceKind::Bullish => "price made a lower low, RSI a higher low",
+        };
+        text.push_str(&format!(
+            "\n⚠️ {} divergence detected {} candles ago ({})",
+            divergence.kind, divergence.candles_ago, swing
+        ));
+    }
+    Some(text)
+}
+
+/// Structured RSI reading: a divergence sets the direction, otherwise
+/// oversold (< 30) is bullish and overbought (> 70) bearish. No signal
+/// without a current RSI value.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::rsi::rsi_indicator_result;
+/// let closes = [1.0, 2.0, 3.0];
+/// let signal = |rsi: f64| rsi_indicator_result(&closes, &[None, Some(50.0), Some(rsi)], "BTC/USDC").signal;
+/// assert_eq!(signal(25.0).as_deref(), Some("bullish"));
+/// assert_eq!(signal(75.0).as_deref(), Some("bearish"));
+/// assert_eq!(signal(50.0).as_deref(), Some("neutral"));
+/// assert_eq!(rsi_indicator_result(&closes, &[], "BTC/USDC").signal, None);
+/// ```
+pub fn rsi_indicator_result(closes: &[f64], rsi: &[Option<f64>], symbol: &str) -> IndicatorResult {
+    let current = rsi.last().copied().flatten();
+    rsi_reading(symbol, current, || detect_rsi_divergence(closes, rsi, DIVERGENCE_SWING_STRENGTH))
+}
+
+fn rsi_reading(symbol: &str, current: Option<f64>, divergence: impl FnOnce() -> Option<RsiDivergence>) -> IndicatorResult {
+    let result = IndicatorResult::new("rsi", symbol).with_value("rsi", current);
+    let Some(current) = current else { return result };
+    let signal = match divergence() {
+        Some(divergence) => divergence.kind.to_string(),
+        None if current < 30.0 => "bullish".to_string(),
+        None if current > 70.0 => "bearish".to_string(),
+        None => "neutral".to_string(),
+    };
+    result.with_signal(&signal)
+}
@@ -0,0 +1,181 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::bot::indicators::{series_until, IndicatorResult};
+use crate::bot::indicators::moving_averages::{last_defined, on_defined, sma};
+use crate::bot::klines::{Klines, KlinesOHLC};
+
+/// Relative Vigor Index: where candles close within their range, smoothed.
+pub struct Rvi {
+    pub klines: Arc<Mutex<Klines>>,
+    pub period: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RviCross {
+    /// RVI crossed above its signal line
+    Bullish,
+    /// RVI crossed below its signal line
+    Bearish,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RviResult {
+    pub period: usize,
+    pub rvi: Vec<Option<f64>>,
+    pub signal: Vec<Option<f64>>,
+    /// Signal-line crossover on the latest candle
+    pub cross: Option<RviCross>,
+}
+
+/// `(x[i] + 2*x[i-1] + 2*x[i-2] + x[i-3]) / 6`, aligned with the input.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::rvi::symmetric_weighted;
+/// let values = [Some(1.0), Some(2.0), Some(3.0), Some(4.0), Some(5.0)];
+/// // (4 + 2*3 + 2*2 + 1) / 6 and (5 + 2*4 + 2*3 + 2) / 6
+/// assert_eq!(symmetric_weighted(&values), vec![None, None, None, Some(2.5), Some(3.5)]);
+/// // a gap anywhere in the 4 candle window has no reading
+/// let gapped = [None, Some(2.0), Some(3.0), Some(4.0), Some(5.0)];
+/// assert_eq!(symmetric_weighted(&gapped)[3], None);
+/// ```
+pub fn symmetric_weighted(values: &[Option<f64>]) -> Vec<Option<f64>> {
+    (0..values.len())
+        .map(|i| {
+            if i < 3 {
+                return None;
+            }
+            Some((values[i]? + 2.0 * values[i - 1]? + 2.0 * values[i - 2]? + values[i - 3]?) / 6.0)
+        })
+        .collect()
+}
+
+/// RVI line and its signal line, both aligned with the klines.
+/// Candles where the smoothed range is zero have no reading.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::rvi::calculate_rvi;
+/// use botmarley::bot::klines::KlinesOHLC;
+/// // every candle opens on its low and closes on its high: full vigor
+/// let low: Vec<f64> = (0..20).map(|i| 100.0 + i as f64).collect();
+/// let ohlc = KlinesOHLC {
+///     time: (0..20).collect(),
+///     open: low.clone(),
+///     high: low.iter().map(|l| l + 2.0).collect(),
+///     close: low.iter().map(|l| l + 2.0).collect(),
+///     volume: vec![1.0; 20],
+///     low,
+///     count: 20,
+/// };
+/// let (rvi, signal) = calculate_rvi(&ohlc, 10);
+/// // 3 candles for the weighting, 9 more for the SMA
+/// assert_eq!(rvi[11], None);
+/// assert_eq!(rvi[12], Some(1.0));
+/// assert_eq!(signal[14], None);
+/// assert_eq!(signal[15], Some(1.0));
+/// ```
+pub fn calculate_rvi(ohlc: &KlinesOHLC, period: usize) -> (Vec<Option<f64>>, Vec<Option<f64>>) {
+    let close_open: Vec<Option<f64>> = ohlc.close.iter().zip(ohlc.open.iter()).map(|(c, o)| Some(c - o)).collect();
+    let high_low: Vec<Option<f64>> = ohlc.high.iter().zip(ohlc.low.iter()).map(|(h, l)| Some(h - l)).collect();
+
+    let numerator = on_defined(&symmetric_weighted(&close_open), |values| sma(values, period));
+    let denominator = on_defined(&symmetric_weighted(&high_low), |values| sma(values, period));
+
+    let rvi: Vec<Option<f64>> = numerator
+        .iter()
+        .zip(denominator.iter())
+        .map(|(num, den)| match (num, den) {
+            (Some(num), Some(den)) if *den != 0.0 => Some(num / den),
+            _ => None,
+        })
+        .collect();
+    let signal = symmetric_weighted(&rvi);
+    (rvi, signal)
+}
+
+/// Crossover between the last two candles.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::rvi::{detect_rvi_cross, RviCross};
+/// let signal = [Some(0.2), Some(0.2)];
+/// assert_eq!(detect_rvi_cross(&[Some(0.1), Some(0.3)], &signal), Some(RviCross::Bullish));
+/// assert_eq!(detect_rvi_cross(&[Some(0.3), Some(0.1)], &signal), Some(RviCross::Bearish));
+/// // staying above is not a cross
+/// assert_eq!(detect_rvi_cross(&[Some(0.3), Some(0.4)], &signal), None);
+/// assert_eq!(detect_rvi_cross(&[None, Some(0.3)], &signal), None);
+/// ```
+pub fn detect_rvi_cross(rvi: &[Option<f64>], signal: &[Option<f64>]) -> Option<RviCross> {
+    match (rvi, signal) {
+        ([.., Some(r0), Some(r1)], [.., Some(s0), Some(s1)]) => {
+            if r0 <= s0 && r1 > s1 {
+                Some(RviCross::Bullish)
+            } else if r0 >= s0 && r1 < s1 {
+                Some(RviCross::Bearish)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+impl RviResult {
+    pub fn from_ohlc(ohlc: &KlinesOHLC, period: usize) -> Self {
+        let (rvi, signal) = calculate_rvi(ohlc, period);
+        RviResult {
+            period,
+            cross: detect_rvi_cross(&rvi, &signal),
+            rvi,
+            signal,
+        }
+    }
+
+    pub fn indicator_result(&self, symbol: &str) -> IndicatorResult {
+        self.indicator_result_at(symbol, self.rvi.len().saturating_sub(1))
+    }
+
+    pub fn indicator_result_at(&self, symbol: &str, index: usize) -> IndicatorResult {
+        let (rvi, signal) = (series_until(&self.rvi, index), series_until(&self.signal, index));
+        let result = IndicatorResult::new("rvi", symbol)
+            .with_value("rvi", last_defined(rvi))
+            .with_value("signal", last_defined(signal));
+        match detect_rvi_cross(rvi, signal) {
+            Some(RviCross::Bullish) => result.with_signal("bullish"),
+            Some(RviCross::Bearish) => result.with_signal("bearish"),
+            None => result.with_signal("neutral"),
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        self.summary_at(self.rvi.len().saturating_sub(1))
+    }
+
+    pub fn summary_at(&self, index: usize) -> String {
+        let (rvi, signal) = (series_until(&self.rvi, index), series_until(&self.signal, index));
+        let (Some(current), Some(current_signal)) = (last_defined(rvi), last_defined(signal)) else {
+            return format!("RVI ({}): not enough data", self.period);
+        };
+        let mut text = format!("RVI ({}): {:+.3}, Signal: {:+.3}", self.period, current, current_signal);
+        match detect_rvi_cross(rvi, signal) {
+            Some(RviCross::Bullish) => text.push_str("\nRVI crossed above its signal line (bullish)"),
+            Some(RviCross::Bearish) => text.push_str("\nRVI crossed below its signal line (bearish)"),
+            None => {}
+        }
+        text
+    }
+}
+
+impl Rvi {
+    pub async fn calculate(&mut self) -> color_eyre::Result<RviResult> {
+        let ohlc = self.klines.lock().await.get_ohlc().await?;
+        Ok(RviResult::from_ohlc(&ohlc, self.period))
+    }
+}
@@ -0,0 +1,169 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::bot::indicators::{series_until, IndicatorResult};
+use crate::bot::indicators::moving_averages::{ema, last_defined, on_defined};
+use crate::bot::klines::Klines;
+
+/// Crossing above this from below is bullish
+pub const STC_OVERSOLD: f64 = 25.0;
+/// Crossing below this from above is bearish
+pub const STC_OVERBOUGHT: f64 = 75.0;
+
+/// Schaff Trend Cycle: a double stochastic of the MACD line.
+pub struct Stc {
+    pub klines: Arc<Mutex<Klines>>,
+    pub fast: usize,
+    pub slow: usize,
+    pub cycle: usize,
+    pub d1: usize,
+    pub d2: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StcCross {
+    /// Crossed above `STC_OVERSOLD`
+    Bullish,
+    /// Crossed below `STC_OVERBOUGHT`
+    Bearish,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StcResult {
+    pub cycle: usize,
+    pub values: Vec<Option<f64>>,
+    /// Cross on the latest candle
+    pub cross: Option<StcCross>,
+}
+
+/// Stochastic %K of each value over the last `cycle` values. A flat window
+/// repeats the previous reading (50 at the start) instead of dividing by 0.
+fn stochastic(values: &[f64], cycle: usize) -> Vec<Option<f64>> {
+    let mut out = vec![None; values.len()];
+    if cycle == 0 || values.len() < cycle {
+        return out;
+    }
+    let mut prev = 50.0;
+    for i in cycle - 1..values.len() {
+        let window = &values[i + 1 - cycle..=i];
+        let low = window.iter().cloned().fold(f64::MAX, f64::min);
+        let high = window.iter().cloned().fold(f64::MIN, f64::max);
+        if high > low {
+            prev = (values[i] - low) / (high - low) * 100.0;
+        }
+        out[i] = Some(prev);
+    }
+    out
+}
+
+/// STC aligned with the closes, in the 0..100 range.
+///
+/// MACD (`fast`/`slow` EMAs) goes through a `cycle` stochastic smoothed with
+/// an EMA of `d1`, then a second stochastic smoothed with an EMA of `d2`.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::indicators::stc::calculate_stc;
+/// use botmarley::bot::indicators::moving_averages::ema;
+/// let closes: Vec<f64> = (0..300).map(|i| 100.0 + 10.0 * (i as f64 / 15.0).sin()).collect();
+/// let stc = calculate_stc(&closes, 23, 50, 10, 3, 3);
+/// assert!(stc.iter().flatten().all(|v| (0.0..=100.0).contains(v)));
+///
+/// // after the trough, STC turns up through 25 well before MACD crosses zero
+/// let macd: Vec<Option<f64>> = ema(&closes, 23).iter().zip(ema(&closes, 50))
+///     .map(|(f, s)| Some((*f)? - s?)).collect();
+/// let first_cross = |series: &[Option<f64>], level: f64| (150..300)
+///     .find(|&i| matches!((series[i - 1], series[i]), (Some(a), Some(b)) if a <= level && b > level))
+///     .unwrap();
+/// assert!(first_cross(&stc, 25.0) + 10 < first_cross(&macd, 0.0));
+/// ```
+pub fn calculate_stc(closes: &[f64], fast: usize, slow: usize, cycle: usize, d1: usize, d2: usize) -> Vec<Option<f64>> {
+    let fast_ema = ema(closes, fast);
+    let slow_ema = ema(closes, slow);
+    let macd: Vec<Option<f64>> = fast_ema
+        .iter()
+        .zip(slow_ema.iter())
+        .map(|(f, s)| Some((*f)? - (*s)?))
+        .collect();
+
+    let smoothed = on_defined(&macd, |values| {
+        let k = stochastic(values, cycle);
+        on_defined(&k, |k| ema(k, d1.max(1)))
+    });
+    on_defined(&smoothed, |values| {
+        let k = stochastic(values, cycle);
+        on_defined(&k, |k| ema(k, d2.max(1)))
+    })
+    .into_iter()
+    .map(|v| v.map(|v| v.clamp(0.0, 100.0)))
+    .collect()
+}
+
+/// Cross between the last two defined values.
+pub fn detect_cross(values: &[Option<f64>]) -> Option<StcCross> {
+    let mut defined = values.iter().rev().flatten();
+    let (current, previous) = (*defined.next()?, *defined.next()?);
+    if previous <= STC_OVERSOLD && current > STC_OVERSOLD {
+        Some(StcCross::Bullish)
+    } else if previous >= STC_OVERBOUGHT && current < STC_OVERBOUGHT {
+        Some(StcCross::Bearish)
+    } else {
+        None
+    }
+}
+
+impl StcResult {
+    pub fn from_close(closes: &[f64], fast: usize, slow: usize, cycle: usize, d1: usize, d2: usize) -> Self {
+        let values = calculate_stc(closes, fast, slow, cycle, d1, d2);
+        StcResult {
+            cycle,
+            cross: detect_cross(&values),
+            values,
+        }
+    }
+
+    pub fn indicator_result(&self, symbol: &str) -> IndicatorResult {
+        self.indicator_result_at(symbol, self.values.len().saturating_sub(1))
+    }
+
+    pub fn indicator_result_at(&self, symbol: &str, index: usize) -> IndicatorResult {
+        let values = series_until(&self.values, index);
+        let current = last_defined(values);
+        let result = IndicatorResult::new("stc", symbol).with_value("stc", current);
+        match (detect_cross(values), current) {
+            (Some(StcCross::Bullish), _) => result.with_signal("bullish"),
+            (Some(StcCross::Bearish), _) => result.with_signal("bearish"),
+            (None, Some(_)) => result.with_signal("neutral"),
+            (None, None) => result,
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        self.summary_at(self.values.len().saturating_sub(1))
+    }
+
+    pub fn summary_at(&self, index: usize) -> String {
+        let values = series_until(&self.values, index);
+        let Some(stc) = last_defined(values) else {
+            return format!("Schaff Trend Cycle ({}): not enough data", self.cycle);
+        };
+        let reading = match detect_cross(values) {
+            Some(StcCross::Bullish) => format!("crossed above {}, bullish", STC_OVERSOLD),
+            Some(StcCross::Bearish) => format!("crossed below {}, bearish", STC_OVERBOUGHT),
+            None if stc >= STC_OVERBOUGHT => "upper zone".to_string(),
+            None if stc <= STC_OVERSOLD => "lower zone".to_string(),
+            None => "mid range".to_string(),
+        };
+        format!("Schaff Trend Cycle ({}): {:.1} ({})", self.cycle, stc, reading)
+    }
+}
+
+impl Stc {
+    pub async fn calculate(&mut self) -> color_eyre::Result<StcResult> {
+        let close = self.klines.lock().await.get_ohlc().await?.close;
+        Ok(StcResult::from_close(&close, self.fast, self.slow, self.cycle, self.d1, self.d2))
+    }
+}
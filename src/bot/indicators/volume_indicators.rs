@@ -0,0 +1,71 @@
+use crate::binance::data_collector::KlineData;
+
+/// Candles compared when looking for a divergence between A/D and price
+pub const DIVERGENCE_CANDLES: usize = 10;
+
+/// Close location value, where the close sits in the candle range from -1
+/// (at the low) to +1 (at the high). Zero-range candles count as 0.
+pub fn close_location_value(high: f64, low: f64, close: f64) -> f64 {
+    let range = high - low;
+    if range <= 0.0 { 0.0 } else { ((close - low) - (high - close)) / range }
+}
+
+/// Accumulation/Distribution line: running sum of `clv * volume`, one value
+/// per kline.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::binance::data_collector::KlineData;
+/// use botmarley::bot::indicators::volume_indicators::calculate_ad_line;
+/// let kline = |high, low, close, volume| KlineData {
+///     open_time: 0, close_time: 0, symbol: "BTCUSDC".into(),
+///     open: close, high, low, close, volume,
+///     quote_asset_volume: 0.0, number_of_trades: 0,
+///     taker_buy_base_asset_volume: 0.0, taker_buy_quote_asset_volume: 0.0,
+/// };
+/// let klines = [
+///     kline(10.0, 8.0, 10.0, 100.0), // close at the high: +100
+///     kline(10.0, 8.0, 8.0, 100.0),  // close at the low: -100
+///     kline(10.0, 8.0, 9.5, 200.0),  // clv 0.5: +100
+///     kline(9.0, 9.0, 9.0, 500.0),   // flat candle: 0
+/// ];
+/// assert_eq!(calculate_ad_line(&klines), vec![100.0, 0.0, 100.0, 100.0]);
+/// ```
+pub fn calculate_ad_line(klines: &[KlineData]) -> Vec<f64> {
+    klines
+        .iter()
+        .scan(0.0, |ad, k| {
+            *ad += close_location_value(k.high, k.low, k.close) * k.volume;
+            Some(*ad)
+        })
+        .collect()
+}
+
+/// Volume based analysis for the LLM context.
+pub fn format_volume_analysis(klines: &[KlineData]) -> String {
+    let ad = calculate_ad_line(klines);
+    let (Some(current), Some(last)) = (ad.last(), klines.last()) else {
+        return "A/D Line: not enough data".to_string();
+    };
+    let mut text = format!("A/D Line: {:.2}", current);
+
+    if klines.len() > DIVERGENCE_CANDLES {
+        let past = klines.len() - 1 - DIVERGENCE_CANDLES;
+        let ad_change = current - ad[past];
+        let price_change = last.close - klines[past].close;
+        let note = if ad_change > 0.0 && price_change < 0.0 {
+            "rising while price falls (bullish divergence)"
+        } else if ad_change < 0.0 && price_change > 0.0 {
+            "falling while price rises (bearish divergence)"
+        } else if ad_change > 0.0 {
+            "rising (accumulation)"
+        } else if ad_change < 0.0 {
+            "falling (distribution)"
+        } else {
+            "flat"
+        };
+        text.push_str(&format!(", {} over the last {} candles", note, DIVERGENCE_CANDLES));
+    }
+    text
+}
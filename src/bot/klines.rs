@@ -1,10 +1,12 @@
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::binance::DataCollector;
 use crate::config::{self, CONFIG, Config};
 use crate::logging::init_logger;
 use crate::symbol::Symbol;
+use crate::utils::interval::{KLINE_INTERVAL, interval_candles};
 use color_eyre::eyre::{Ok, WrapErr};
 use color_eyre::Section;
 use financial_indicators::macd::MACD;
@@ -16,8 +18,9 @@ use financial_indicators::ema::exponential_moving_average;
 use financial_indicators::atr::average_true_range;
 use financial_indicators::bollinger::bollinger_bands;
 use serde::{Deserialize, Serialize};
-use tracing::instrument;
+use tracing::{instrument, warn};
 use crate::binance::data_collector::KlineData;
+use crate::bot::indicators::{option_at_kline, value_at_kline};
 
 
 #[derive(Debug,Clone,PartialEq,Serialize, Deserialize)]
@@ -45,6 +48,77 @@ pub struct Klines{
     collector:DataCollector
 
 }
+
+impl From<&[KlineData]> for KlinesOHLC {
+    fn from(klines: &[KlineData]) -> Self {
+        KlinesOHLC {
+            time: klines.iter().map(|k| k.open_time).collect(),
+            open: klines.iter().map(|k| k.open).collect(),
+            close: klines.iter().map(|k| k.close).collect(),
+            high: klines.iter().map(|k| k.high).collect(),
+            low: klines.iter().map(|k| k.low).collect(),
+            volume: klines.iter().map(|k| k.volume).collect(),
+            count: klines.len(),
+        }
+    }
+}
+
+/// Compact CSV block of the candles, one `time,o,h,l,c,v` row each, for
+/// prompts where exact numbers beat prose.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::binance::data_collector::KlineData;
+/// use botmarley::bot::klines::format_raw_ohlc;
+/// let kline = KlineData {
+///     open_time: 1_700_000_000_000,
+///     close_time: 1_700_000_299_999,
+///     symbol: "BTCUSDC".parse().unwrap(),
+///     open: 36500.5,
+///     high: 36600.0,
+///     low: 36450.25,
+///     close: 36580.0,
+///     volume: 12.5,
+///     quote_asset_volume: 0.0,
+///     number_of_trades: 0,
+///     taker_buy_base_asset_volume: 0.0,
+///     taker_buy_quote_asset_volume: 0.0,
+/// };
+/// assert_eq!(
+///     format_raw_ohlc(&[kline]),
+///     "time,o,h,l,c,v\n2023-11-14 22:13,36500.5,36600,36450.25,36580,12.5\n"
+/// );
+/// assert_eq!(format_raw_ohlc(&[]), "time,o,h,l,c,v\n");
+/// ```
+pub fn format_raw_ohlc(klines: &[KlineData]) -> String {
+    let mut block = String::from("time,o,h,l,c,v\n");
+    for k in klines {
+        let time = chrono::DateTime::<chrono::Utc>::from_timestamp_millis(k.open_time)
+            .unwrap_or_default()
+            .format("%Y-%m-%d %H:%M");
+        block.push_str(&format!("{},{},{},{},{},{}\n", time, k.open, k.high, k.low, k.close, k.volume));
+    }
+    block
+}
+
+/// `true` when a candle closing at `latest_close_time_ms` is at most `max_staleness_secs` old at `now_ms`
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::klines::is_data_fresh;
+/// let closed_at = 1_700_000_000_000;
+/// // exactly 15 minutes old is still fresh, one ms more is not
+/// assert!(is_data_fresh(closed_at, closed_at + 900_000, 900));
+/// assert!(!is_data_fresh(closed_at, closed_at + 900_001, 900));
+/// // a candle that closes in the future (clock skew) counts as fresh
+/// assert!(is_data_fresh(closed_at, closed_at - 1_000, 0));
+/// ```
+pub fn is_data_fresh(latest_close_time_ms:i64, now_ms:i64, max_staleness_secs:u64)->bool{
+    now_ms-latest_close_time_ms <= (max_staleness_secs as i64)*1000
+}
+
 impl Klines{
     #[instrument]
 
@@ -52,6 +126,11 @@ impl Klines{
         let config=(&*CONFIG).clone();
 
     let collector = DataCollector::new((&config).clone()).expect("unable to get collector");
+        Klines::with_collector(symbol, collector)
+    }
+
+    /// Read the klines through `collector` instead of one built from `CONFIG`.
+    pub fn with_collector(symbol:Symbol, collector:DataCollector)->Klines{
         Klines { symbol,  collector, ohcl:None,klines:None }
     }
 #[instrument(skip(self),level="debug")]
@@ -70,36 +149,71 @@ impl Klines{
             return Ok(ohcl_data.clone());
         }
     let klines=self.get_klinedata().await?;
-    let mut time: Vec<i64>=Vec::new();
-    let mut close:Vec<f64>=Vec::new();
-    let mut low:Vec<f64>=Vec::new();
-    let mut high:Vec<f64>=Vec::new();
-    let mut volume:Vec<f64>=Vec::new();
-    let mut open:Vec<f64>=Vec::new();
-    for kline in klines.iter() {
-      
-        close.push(kline.close);
-        low.push(kline.low);
-        high.push(kline.high);
-        open.push(kline.open);
-        volume.push(kline.volume);
-        time.push(kline.open_time);
-    }
-    let ohcl=KlinesOHLC{
-        time,
-        close,
-        open,
-        low,
-        high,
-        volume,
-        count: klines.iter().count()
-    };
+    let ohcl=KlinesOHLC::from(klines.as_slice());
     self.ohcl=Some(ohcl.clone());
 
 
     Ok(ohcl)
     }
 
+    /// Check that the latest stored candle closed within `max_staleness_secs` of now.
+    /// Logs a warning and returns `false` when the data is too old to act on.
+    pub async fn check_freshness(&mut self, max_staleness_secs: u64)-> color_eyre::Result<bool>{
+        self.check_freshness_at(max_staleness_secs, chrono::Utc::now().timestamp_millis()).await
+    }
+
+    /// `check_freshness` at `now` (ms).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use botmarley::binance::DataCollector;
+    /// use botmarley::bot::klines::Klines;
+    /// use botmarley::config::Config;
+    /// use polars::prelude::*;
+    /// let dir = std::env::temp_dir().join(format!("botmarley_fresh_{}", std::process::id()));
+    /// let config = Config { data_dir: dir.to_str().unwrap().to_string(), ..Default::default() };
+    /// let collector = DataCollector::new(Arc::new(config)).unwrap();
+    /// let rt = tokio::runtime::Runtime::new().unwrap();
+    /// let mut klines = Klines::with_collector("BTCUSDC".parse().unwrap(), collector.clone());
+    /// // nothing stored yet
+    /// assert!(!rt.block_on(klines.check_freshness_at(900, 0)).unwrap());
+    ///
+    /// let df = df![
+    ///     "open_time" => [0i64], "close_time" => [299_999i64], "symbol" => ["BTCUSDC"],
+    ///     "open" => [1.0], "high" => [1.5], "low" => [0.5], "close" => [1.2], "volume" => [10.0],
+    ///     "quote_asset_volume" => [12.0], "number_of_trades" => [3i64],
+    ///     "taker_buy_base_asset_volume" => [5.0], "taker_buy_quote_asset_volume" => [6.0],
+    /// ].unwrap();
+    /// collector.save_to_arrow(df, "BTCUSDC").unwrap();
+    /// assert!(rt.block_on(klines.check_freshness_at(900, 299_999 + 900_000)).unwrap());
+    /// assert!(!rt.block_on(klines.check_freshness_at(900, 299_999 + 900_001)).unwrap());
+    /// std::fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn check_freshness_at(&mut self, max_staleness_secs: u64, now: i64)-> color_eyre::Result<bool>{
+        // only the last candle is needed, don't load the history for it
+        let latest=match &self.klines{
+            Some(klines)=>klines.last().cloned(),
+            None=>self.collector.latest_candle(&self.symbol.to_string()).await?,
+        };
+        let Some(latest_close_time)=latest.map(|k| k.close_time) else {
+            warn!(symbol=%self.symbol, "No klines available, treating data as stale");
+            return Ok(false);
+        };
+        if !is_data_fresh(latest_close_time, now, max_staleness_secs){
+            warn!(
+                symbol=%self.symbol,
+                age_secs=(now-latest_close_time)/1000,
+                max_staleness_secs,
+                "Kline data is stale, refusing to trade on it"
+            );
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
 
 
 
@@ -190,9 +304,12 @@ async fn get_klines_with_metadata(symbol:String, config:Config) -> color_eyre::R
         println!("Sample BB Upper: {:?}", &bb.0[0..3.min(bb.0.len())]);
     }
 
-    // Create KlinesWithIndicators for each kline
+    // Create KlinesWithIndicators for each kline. The indicators only cover the
+    // first `data_points` klines and drop their warm-up, so look every value up
+    // by kline index instead of assuming the arrays line up.
+    let total = close.len();
     let mut klines_with_indicators: Vec<KlinesWithIndicators> = Vec::new();
-    
+
     for (i, kline) in klines.iter().enumerate() {
         let kwi = KlinesWithIndicators {
             open: kline.open,
@@ -200,51 +317,35 @@ async fn get_klines_with_metadata(symbol:String, config:Config) -> color_eyre::R
             low: kline.low,
             close: kline.close,
             volume: kline.volume,
-            macd: if i < macd_values.len() {
-                // Create a new MACD instance with the same values
-                MACD {
-                    macd: macd_values[i].macd,
-                    signal: macd_values[i].signal,
-                    histogram: macd_values[i].histogram
-                }
-            } else {
-                // Create a default MACD with zero values
-                MACD { macd: 0.0, signal: 0.0, histogram: 0.0 }
-            },
-            mfi: if i < mfi.len() {
-                mfi[i].unwrap_or(0.0)
-            } else {
-                0.0
-            },
-            rsi: if i < rsi.len() {
-                rsi[i].unwrap_or(0.0)
-            } else {
-                0.0
-            },
-            kdj: if i < kdj_values.len() {
-                // Create a new KDJ instance with the same values
-                KDJ {
-                    k: kdj_values[i].k,
-                    d: kdj_values[i].d,
-                    j: kdj_values[i].j
-                }
-            } else {
-                // Create a default KDJ with zero values
-                KDJ { k: 0.0, d: 0.0, j: 0.0 }
-            },
+            macd: value_at_kline(&macd_values, total, i).map(|m| MACD {
+                macd: m.macd,
+                signal: m.signal,
+                histogram: m.histogram,
+            }),
+            mfi: option_at_kline(&mfi, total, i),
+            rsi: option_at_kline(&rsi, total, i),
+            kdj: value_at_kline(&kdj_values, total, i).map(|kdj| KDJ {
+                k: kdj.k,
+                d: kdj.d,
+                j: kdj.j,
+            }),
             bollinger_bands: vec![
-                if i < bb.0.len() { bb.0[i].unwrap_or(0.0) } else { 0.0 }, // upper band
-                if i < bb.1.len() { bb.1[i].unwrap_or(0.0) } else { 0.0 }, // middle band
-                if i < bb.2.len() { bb.2[i].unwrap_or(0.0) } else { 0.0 }, // lower band
+                option_at_kline(&bb.0, total, i), // upper band
+                option_at_kline(&bb.1, total, i), // middle band
+                option_at_kline(&bb.2, total, i), // lower band
             ],
         };
-        
+
         klines_with_indicators.push(kwi);
     }
 
     dbg!(&klines_with_indicators);
     
     // Map KlinesWithIndicators to KlinesWithMetadata
+    let candles_30m = interval_candles(KLINE_INTERVAL, Duration::from_secs(30 * 60));
+    let candles_1h = interval_candles(KLINE_INTERVAL, Duration::from_secs(60 * 60));
+    let candles_2h = interval_candles(KLINE_INTERVAL, Duration::from_secs(2 * 60 * 60));
+    let candles_8h = interval_candles(KLINE_INTERVAL, Duration::from_secs(8 * 60 * 60));
     let klines_with_metadata: Vec<KlinesWithMetadata> = klines_with_indicators
         .into_iter()
         .enumerate()
@@ -254,40 +355,39 @@ async fn get_klines_with_metadata(symbol:String, config:Config) -> color_eyre::R
             let target_decrease = current_price * 0.95; // 5% decrease threshold
             
             // Calculate predictions based on maximum/minimum prices reached within time windows
-            // 30m = 6 klines ahead (5m intervals), 1h = 12 klines, 2h = 24 klines
             
-            // Check if price increases by 2% at any point within 30 minutes (6 klines)
+            // Check if price increases by 2% at any point within 30 minutes
             let price_will_increase_2percent_in_30m = klines
                 .iter()
                 .skip(index + 1)
-                .take(6)
+                .take(candles_30m)
                 .any(|future_kline| future_kline.high >= target_increase);
                 
-            // Check if price increases by 2% at any point within 1 hour (12 klines)
+            // Check if price increases by 2% at any point within 1 hour
             let price_will_increase_2percent_in_1h = klines
                 .iter()
                 .skip(index + 1)
-                .take(12)
+                .take(candles_1h)
                 .any(|future_kline| future_kline.high >= target_increase);
                 
-            // Check if price increases by 2% at any point within 2 hours (24 klines)
+            // Check if price increases by 2% at any point within 2 hours
             let price_will_increase_2percent_in_2h = klines
                 .iter()
                 .skip(index + 1)
-                .take(24)
+                .take(candles_2h)
                 .any(|future_kline| future_kline.high >= target_increase);
-                            // Check if price increases by 2% at any point within 2 hours (24 klines)
+            // Check if price increases by 2% at any point within 8 hours
             let price_will_increase_2percent_in_8h = klines
                 .iter()
                 .skip(index + 1)
-                .take(96)
+                .take(candles_8h)
                 .any(|future_kline| future_kline.high >= target_increase);
                 
-            // Check if price drops by 5% at any point within 2 hours (24 klines)
+            // Check if price drops by 5% at any point within 2 hours
             let price_will_drop_over_5percent_in_2h = klines
                 .iter()
                 .skip(index + 1)
-                .take(24)
+                .take(candles_2h)
                 .any(|future_kline| future_kline.low <= target_decrease);
             
             KlinesWithMetadata {
@@ -312,11 +412,12 @@ pub struct KlinesWithIndicators {
     high: f64,
     low: f64,
     volume: f64,
-    macd: MACD,
-    mfi: f64,
-    rsi: f64,
-    kdj: KDJ,
-    bollinger_bands: Vec<f64>,
+    /// `None` during the indicator warm-up
+    macd: Option<MACD>,
+    mfi: Option<f64>,
+    rsi: Option<f64>,
+    kdj: Option<KDJ>,
+    bollinger_bands: Vec<Option<f64>>,
 }
 
 impl std::fmt::Debug for KlinesWithIndicators {
@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+use color_eyre::eyre::eyre;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use crate::config::Config;
+
+/// The probe gives up after this long
+pub const LLM_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Deserialize)]
+struct ModelList {
+    data: Vec<Model>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Model {
+    id: String,
+}
+
+/// Ping the OpenAI compatible endpoint by listing its models, so a wrong
+/// URL or key fails at startup with a hint instead of deep inside the
+/// first analysis. A configured model missing from the list only warns,
+/// some servers list a subset.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::llm_check::check_llm_endpoint;
+/// use botmarley::config::Config;
+/// // nothing listens on the discard port
+/// let config = Config { openai_base_url: "http://127.0.0.1:9".to_string(), ..Default::default() };
+/// let rt = tokio::runtime::Runtime::new().unwrap();
+/// let message = rt.block_on(check_llm_endpoint(&config)).unwrap_err().to_string();
+/// assert!(message.contains("http://127.0.0.1:9/v1/models"), "{}", message);
+/// assert!(message.contains("OPENAI_BASE_URL"));
+/// ```
+pub async fn check_llm_endpoint(config: &Config) -> color_eyre::Result<()> {
+    let url = format!("{}/v1/models", config.openai_base_url.trim_end_matches('/'));
+    let response = Client::new()
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", config.openai_api_key))
+        .timeout(LLM_CHECK_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| eyre!("LLM endpoint {} is unreachable ({}). Check OPENAI_BASE_URL and that the server is running", url, e))?;
+
+    let status = response.status();
+    if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+        return Err(eyre!("LLM endpoint {} rejected the credentials ({}). Check OPENAI_API_KEY", url, status));
+    }
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(eyre!(
+            "LLM endpoint {} answered {}: {}. Check OPENAI_BASE_URL, requests go to <OPENAI_BASE_URL>/v1/...",
+            url,
+            status,
+            body.chars().take(200).collect::<String>()
+        ));
+    }
+
+    match response.json::<ModelList>().await {
+        Ok(models) if models.data.iter().any(|m| m.id == config.openai_model) => {
+            info!(model = %config.openai_model, "LLM endpoint is reachable");
+        }
+        Ok(models) => {
+            let available: Vec<&str> = models.data.iter().map(|m| m.id.as_str()).take(10).collect();
+            warn!(model = %config.openai_model, ?available, "LLM endpoint is reachable but doesn't list OPENAI_MODEL");
+        }
+        Err(e) => warn!("LLM endpoint is reachable but its model list could not be parsed: {}", e),
+    }
+    Ok(())
+}
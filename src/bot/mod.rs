@@ -2,3 +2,10 @@ pub mod klines;
 pub mod system;
 pub mod prompt;
 pub mod indicators;
+pub mod alerts;
+pub mod backtest;
+pub mod correlation;
+pub mod dataset;
+pub mod llm_check;
+pub mod response_format;
+pub mod orders;
@@ -0,0 +1,94 @@
+use tracing::warn;
+
+/// Why an order can't be executed, naming the offending field so the model
+/// can be asked to correct it. `None` for a valid order. Holds only need a
+/// valid action and a traded pair.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::orders::order_problem;
+/// let traded = |pair: &str| pair == "BTCUSDC";
+/// assert_eq!(order_problem("buy", "BTCUSDC", 0.1, 0.8, traded), None);
+/// assert_eq!(order_problem("hold", "BTCUSDC", 0.0, 0.0, traded), None);
+/// assert_eq!(
+///     order_problem("buy", "DOGEUSDC", 0.1, 0.8, traded).unwrap(),
+///     "pair 'DOGEUSDC' is not one of the traded pairs"
+/// );
+/// assert_eq!(
+///     order_problem("sell", "BTCUSDC", 0.0, 0.8, traded).unwrap(),
+///     "amount must be greater than 0 for a sell (got 0)"
+/// );
+/// assert!(order_problem("buy", "BTCUSDC", f64::NAN, 0.8, traded).unwrap().starts_with("amount"));
+/// assert_eq!(
+///     order_problem("buy", "BTCUSDC", 0.1, 0.0, traded).unwrap(),
+///     "confidence must be above 0 and at most 1 (got 0)"
+/// );
+/// assert!(order_problem("buy", "BTCUSDC", 0.1, 1.5, traded).unwrap().starts_with("confidence"));
+/// assert!(order_problem("short", "BTCUSDC", 0.1, 0.8, traded).unwrap().starts_with("action 'short'"));
+/// ```
+pub fn order_problem(action: &str, pair: &str, amount: f64, confidence: f64, is_traded: impl Fn(&str) -> bool) -> Option<String> {
+    if !matches!(action, "buy" | "sell" | "hold") {
+        return Some(format!("action '{}' must be buy, sell or hold", action));
+    }
+    if !is_traded(pair) {
+        return Some(format!("pair '{}' is not one of the traded pairs", pair));
+    }
+    if action == "hold" {
+        return None;
+    }
+    if !amount.is_finite() || amount <= 0.0 {
+        return Some(format!("amount must be greater than 0 for a {} (got {})", action, amount));
+    }
+    if !(confidence > 0.0 && confidence <= 1.0) {
+        return Some(format!("confidence must be above 0 and at most 1 (got {})", confidence));
+    }
+    None
+}
+
+/// Follow-up prompt for an answer whose orders were rejected: the original
+/// `user_message` plus one line per problem, e.g. from `order_problem`.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::orders::{corrective_message, order_problem};
+/// let traded = |pair: &str| pair == "BTCUSDC";
+/// // a zero-amount buy is rejected rather than recorded
+/// let problem = order_problem("buy", "BTCUSDC", 0.0, 0.8, traded).unwrap();
+/// let message = corrective_message("Analyze BTCUSDC.", &[format!("buy BTCUSDC: {}", problem)]);
+/// assert!(message.starts_with("Analyze BTCUSDC.\n\nYour previous answer was rejected and nothing was executed:"));
+/// assert!(message.contains("\n- buy BTCUSDC: amount must be greater than 0 for a buy (got 0)\n"));
+/// assert!(message.ends_with("Answer again with every field valid.\n"));
+/// ```
+pub fn corrective_message(user_message: &str, problems: &[String]) -> String {
+    format!(
+        "{}\n\nYour previous answer was rejected and nothing was executed:\n- {}\nAnswer again with every field valid.\n",
+        user_message,
+        problems.join("\n- ")
+    )
+}
+
+/// Puts the sells of a batch first, so the cash they free up is available
+/// to its buys, then keeps at most `max_orders` of them. Sells and buys
+/// otherwise keep the model's order.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::orders::order_batch;
+/// let batch = vec![("buy", "ETHUSDC"), ("sell", "BTCUSDC")];
+/// assert_eq!(order_batch(batch, 2, |order| order.0), [("sell", "BTCUSDC"), ("buy", "ETHUSDC")]);
+/// // a sell past the cap isn't dropped in favour of the buys
+/// let batch = vec![("buy", "ETHUSDC"), ("buy", "SOLUSDC"), ("sell", "BTCUSDC")];
+/// assert_eq!(order_batch(batch, 2, |order| order.0), [("sell", "BTCUSDC"), ("buy", "ETHUSDC")]);
+/// assert!(order_batch(vec![("hold", "BTCUSDC")], 0, |order| order.0).is_empty());
+/// ```
+pub fn order_batch<T>(mut orders: Vec<T>, max_orders: usize, action: impl Fn(&T) -> &str) -> Vec<T> {
+    orders.sort_by_key(|order| action(order) != "sell");
+    if orders.len() > max_orders {
+        warn!("LLM returned {} orders, only the first {} are executed", orders.len(), max_orders);
+        orders.truncate(max_orders);
+    }
+    orders
+}
@@ -1,4 +1,57 @@
+use serde::{Deserialize, Serialize};
 
+pub fn get_prompt(symbol:String){}
 
+/// Previous decision for a symbol, to show the model how its last call has
+/// played out so far
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LastDecision {
+    /// "buy", "sell" or "hold"
+    pub action: String,
+    pub price_at_decision: f64,
+    /// When the decision was made, in ms
+    pub timestamp: i64,
+}
 
-pub fn get_prompt(symbol:String){}
\ No newline at end of file
+impl LastDecision {
+    /// Percent move from `price_at_decision` to `current_price`
+    pub fn price_change_pct(&self, current_price: f64) -> f64 {
+        if self.price_at_decision == 0.0 {
+            return 0.0;
+        }
+        (current_price - self.price_at_decision) / self.price_at_decision * 100.0
+    }
+
+    /// One line for the prompt.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use botmarley::bot::prompt::LastDecision;
+    /// let last = LastDecision { action: "buy".to_string(), price_at_decision: 64_000.0, timestamp: 0 };
+    /// assert!((last.price_change_pct(64_640.0) - 1.0).abs() < 1e-9);
+    /// assert_eq!(
+    ///     last.summary(64_640.0, 45 * 60 * 1000),
+    ///     "Since your last decision (BUY at $64000.0000, 45 min ago): price +1.00%, in your favour so far"
+    /// );
+    /// let sell = LastDecision { action: "sell".to_string(), ..last };
+    /// assert!(sell.summary(64_640.0, 45 * 60 * 1000).ends_with("against you so far"));
+    /// ```
+    pub fn summary(&self, current_price: f64, now_ms: i64) -> String {
+        let change = self.price_change_pct(current_price);
+        let verdict = match self.action.as_str() {
+            "buy" if change > 0.0 => ", in your favour so far",
+            "sell" if change < 0.0 => ", in your favour so far",
+            "buy" | "sell" if change != 0.0 => ", against you so far",
+            _ => "",
+        };
+        format!(
+            "Since your last decision ({} at ${:.4}, {} min ago): price {:+.2}%{}",
+            self.action.to_uppercase(),
+            self.price_at_decision,
+            (now_ms - self.timestamp).max(0) / 60_000,
+            change,
+            verdict
+        )
+    }
+}
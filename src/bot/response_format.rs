@@ -0,0 +1,88 @@
+use color_eyre::eyre::WrapErr;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// How structured output is requested from the LLM, `JSON_SCHEMA_MODE=strict|loose|none`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JsonSchemaMode {
+    /// `json_schema` response format with `strict: true`, as OpenAI supports it
+    #[default]
+    Strict,
+    /// `json_schema` without strict mode, for servers that reject `strict`
+    Loose,
+    /// No response format, the schema goes into the prompt and the JSON is
+    /// extracted from whatever the model answers
+    None,
+}
+
+impl JsonSchemaMode {
+    /// `strict` flag of the `json_schema` response format, `None` when no
+    /// response format should be sent
+    pub fn strict(self) -> Option<bool> {
+        match self {
+            JsonSchemaMode::Strict => Some(true),
+            JsonSchemaMode::Loose => Some(false),
+            JsonSchemaMode::None => None,
+        }
+    }
+}
+
+/// System prompt addition describing the expected answer when no response
+/// format is sent
+pub fn schema_instruction(schema: &serde_json::Value) -> String {
+    format!("Respond only with a single JSON object matching this JSON schema:\n{}", schema)
+}
+
+/// First JSON object in `content`, skipping prose and code fences around it.
+/// Braces inside strings are ignored; without a complete object the whole
+/// text is returned so the parse error shows what the model sent.
+pub fn extract_json(content: &str) -> &str {
+    let Some(start) = content.find('{') else {
+        return content;
+    };
+    let (mut depth, mut in_string, mut escaped) = (0usize, false, false);
+    for (i, c) in content[start..].char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '{' if !in_string => depth += 1,
+            '}' if !in_string => {
+                depth -= 1;
+                if depth == 0 {
+                    return &content[start..=start + i];
+                }
+            }
+            _ => {}
+        }
+    }
+    content
+}
+
+/// Parse an LLM answer produced under `mode`. Schema modes expect bare JSON,
+/// `None` goes through `extract_json` first. Unknown fields are ignored
+/// unless `T` denies them.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::response_format::{parse_response, JsonSchemaMode};
+/// #[derive(serde::Deserialize)]
+/// struct Decision { action: String, confidence: f64 }
+///
+/// let decision: Decision = parse_response(r#"{"action":"buy","confidence":0.8}"#, JsonSchemaMode::Loose).unwrap();
+/// assert_eq!((decision.action.as_str(), decision.confidence), ("buy", 0.8));
+///
+/// let answer = "Sure, here it is:\n```json\n{\"action\":\"sell\",\"confidence\":0.6,\"note\":\"{not json}\"}\n```";
+/// let decision: Decision = parse_response(answer, JsonSchemaMode::None).unwrap();
+/// assert_eq!((decision.action.as_str(), decision.confidence), ("sell", 0.6));
+/// assert!(parse_response::<Decision>(answer, JsonSchemaMode::Strict).is_err());
+/// ```
+pub fn parse_response<T: DeserializeOwned>(content: &str, mode: JsonSchemaMode) -> color_eyre::Result<T> {
+    let json = match mode {
+        JsonSchemaMode::None => extract_json(content),
+        JsonSchemaMode::Strict | JsonSchemaMode::Loose => content,
+    };
+    serde_json::from_str(json).wrap_err_with(|| format!("LLM answer is not the expected JSON ({:?} schema mode)", mode))
+}
@@ -1,25 +1,138 @@
+use serde::{Deserialize, Serialize};
 
+/// Trading style the system prompt is composed for, `STRATEGY_PROFILE=scalper|swing|conservative`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase", try_from = "String")]
+pub enum StrategyProfile {
+    Scalper,
+    #[default]
+    Swing,
+    Conservative,
+}
 
+pub const SCALPER_PROFILE: &[&str] = &[
+    "You will look for short momentum bursts and buy asset when price starts to move up.",
+    "You will take profit quickly, sell assets as soon as about 2% profit is made.",
+    "Cut losing positions early, do not wait for them to recover.",
+];
 
+pub const SWING_PROFILE: &[&str] = &[
+    "You will find dip and identify that price will rise soon and buy asset.",
+    "You will sell assets when at least 2.5% profit is made or to prevent from protfolio value loss.",
+];
 
+pub const CONSERVATIVE_PROFILE: &[&str] = &[
+    "Your first goal is capital preservation, keeping most of the portfolio in USDC is fine.",
+    "Buy only when several indicators agree that price will rise, otherwise hold.",
+    "Sell assets at the first signs of a downtrend to protect portfolio value.",
+];
 
-pub fn get_system_message()->String{
-let init_line="You are a profecsional crypto trader on binance.".to_string();
-let goal01_line="You will find dip and identify that price will rise soon and buy asset.".to_string();
-let goal02_line="You will sell assets when at least 2.5% profit is made or to prevent from protfolio value loss.".to_string();
+pub const SCALPER_RESTRICTIONS: &[&str] = &[
+    "Never put more than 30% of the portfolio into a single trade.",
+    "Sell a position as soon as it is 1% below the buy price.",
+    "Do not keep a position open for more than a few hours.",
+];
+
+pub const SWING_RESTRICTIONS: &[&str] = &[
+    "Never put more than 50% of the portfolio into a single asset.",
+    "Sell a position once it is 5% below the buy price.",
+    "Holding a position for several days is fine while the trend is intact.",
+];
+
+pub const CONSERVATIVE_RESTRICTIONS: &[&str] = &[
+    "Never keep more than 20% of the portfolio outside USDC.",
+    "Sell a position once it is 2% below the buy price.",
+    "Do not buy while volatility is high or against the main trend.",
+];
 
+/// Case-insensitive, so `STRATEGY_PROFILE=Scalper` works too.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::system::StrategyProfile;
+/// assert_eq!("scalper".parse::<StrategyProfile>().unwrap(), StrategyProfile::Scalper);
+/// assert_eq!(" SWING ".parse::<StrategyProfile>().unwrap(), StrategyProfile::Swing);
+/// assert_eq!("Conservative".parse::<StrategyProfile>().unwrap(), StrategyProfile::Conservative);
+/// assert!("yolo".parse::<StrategyProfile>().unwrap_err().to_string().contains("scalper, swing or conservative"));
+///
+/// // the same parsing applies to the env variable
+/// let config: botmarley::config::Config =
+///     envy::from_iter([("STRATEGY_PROFILE".to_string(), "Conservative".to_string())]).unwrap();
+/// assert_eq!(config.strategy_profile, StrategyProfile::Conservative);
+/// ```
+impl std::str::FromStr for StrategyProfile {
+    type Err = color_eyre::Report;
 
-let lines=[
-init_line,
-goal01_line,
-goal02_line,
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "scalper" => Ok(StrategyProfile::Scalper),
+            "swing" => Ok(StrategyProfile::Swing),
+            "conservative" => Ok(StrategyProfile::Conservative),
+            other => Err(color_eyre::eyre::eyre!("Unknown strategy profile '{}' (use scalper, swing or conservative)", other)),
+        }
+    }
+}
+
+impl TryFrom<String> for StrategyProfile {
+    type Error = color_eyre::Report;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl StrategyProfile {
+    pub fn goal_lines(self) -> &'static [&'static str] {
+        match self {
+            StrategyProfile::Scalper => SCALPER_PROFILE,
+            StrategyProfile::Swing => SWING_PROFILE,
+            StrategyProfile::Conservative => CONSERVATIVE_PROFILE,
+        }
+    }
+
+    /// Risk and position rules of the profile
+    pub fn restrictions(self) -> &'static [&'static str] {
+        match self {
+            StrategyProfile::Scalper => SCALPER_RESTRICTIONS,
+            StrategyProfile::Swing => SWING_RESTRICTIONS,
+            StrategyProfile::Conservative => CONSERVATIVE_RESTRICTIONS,
+        }
+    }
+}
+
+/// System prompt with the goal lines and restrictions of `profile`.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::bot::system::{get_system_message, StrategyProfile};
+/// let profiles = [StrategyProfile::Scalper, StrategyProfile::Swing, StrategyProfile::Conservative];
+/// for profile in profiles {
+///     let message = get_system_message(profile);
+///     for other in profiles {
+///         // each prompt carries its own goals and rules and none of the other profiles'
+///         let goals = other.goal_lines().iter().all(|line| message.contains(line));
+///         assert_eq!(goals, other == profile, "{:?} goals in the {:?} prompt", other, profile);
+///         let rules = other.restrictions().iter().any(|line| message.contains(line));
+///         assert_eq!(rules, other == profile, "{:?} restrictions in the {:?} prompt", other, profile);
+///     }
+/// }
+/// ```
+pub fn get_system_message(profile: StrategyProfile)->String{
+let init_line="You are a profecsional crypto trader on binance.".to_string();
+
+let mut lines=vec![init_line];
+lines.extend(profile.goal_lines().iter().map(|line| line.to_string()));
+lines.extend(profile.restrictions().iter().map(|line| line.to_string()));
+lines.extend([
 "Use additional information provided by user to make decision".to_string(),"".to_string(),
 "USDC is a stable coin that should be used to maintain portfolio value and protect it from loss".to_string(),
 "BTC price can affect most of other crypto prices".to_string(),
 "assets like MITO, ZEN, TUT, EIGEN, ETHFI, FLOKI, ALCH seems not to be affected by BTC price but it not a rule, just observation".to_string(),
 "use at least 10 steps in thinking to think step by step and reason".to_string(),
 "".to_string(),
-];
+]);
 lines.join("\n")
-    
-}
\ No newline at end of file
+
+}
@@ -0,0 +1,108 @@
+use color_eyre::eyre::eyre;
+
+use crate::config::Config;
+use crate::symbol::Symbol;
+
+pub const DEFAULT_SYMBOL: &str = "BTCUSDC";
+
+pub const USAGE: &str = "Usage: botmarley [--symbol <SYMBOL> | --all-pairs] [--once | --watch]";
+
+/// Which symbols a run analyzes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    Symbol(String),
+    /// Every pair of `ALLOWED_PAIRS`
+    AllPairs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RunMode {
+    /// Analyze once and exit
+    #[default]
+    Once,
+    /// Re-run every `COLLECTION_INTERVAL_SECS` until Ctrl+C
+    Watch,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliArgs {
+    pub target: Target,
+    pub mode: RunMode,
+}
+
+impl Default for CliArgs {
+    fn default() -> Self {
+        CliArgs {
+            target: Target::Symbol(DEFAULT_SYMBOL.to_string()),
+            mode: RunMode::Once,
+        }
+    }
+}
+
+impl CliArgs {
+    /// Parse the arguments after the program name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use botmarley::cli::{CliArgs, RunMode, Target};
+    /// let args = |s: &str| CliArgs::parse(s.split_whitespace().map(String::from));
+    /// assert_eq!(args("").unwrap(), CliArgs::default());
+    /// assert_eq!(
+    ///     args("--symbol ethusdc --watch").unwrap(),
+    ///     CliArgs { target: Target::Symbol("ETHUSDC".into()), mode: RunMode::Watch }
+    /// );
+    /// assert_eq!(args("--all-pairs --once").unwrap().target, Target::AllPairs);
+    /// assert_eq!(args("--symbol eth_btc").unwrap().target, Target::Symbol("ETHBTC".into()));
+    /// assert!(args("--symbol").is_err());
+    /// assert!(args("--symbol ETHXYZ").is_err());
+    /// assert!(args("--symbol ETHUSDC --all-pairs").is_err());
+    /// assert!(args("--once --watch").is_err());
+    /// assert!(args("--verbose").is_err());
+    /// ```
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> color_eyre::Result<Self> {
+        let mut symbol = None;
+        let mut all_pairs = false;
+        let mut mode = None;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--symbol" => {
+                    let value = args.next().ok_or_else(|| eyre!("--symbol needs a value\n{}", USAGE))?;
+                    let parsed: Symbol = value.parse().map_err(|e| eyre!("{}\n{}", e, USAGE))?;
+                    symbol = Some(parsed.to_string());
+                }
+                "--all-pairs" => all_pairs = true,
+                "--once" | "--watch" => {
+                    let parsed = if arg == "--once" { RunMode::Once } else { RunMode::Watch };
+                    if mode.is_some_and(|m| m != parsed) {
+                        return Err(eyre!("--once and --watch are exclusive\n{}", USAGE));
+                    }
+                    mode = Some(parsed);
+                }
+                other => return Err(eyre!("Unknown argument {}\n{}", other, USAGE)),
+            }
+        }
+
+        let target = match (symbol, all_pairs) {
+            (Some(_), true) => return Err(eyre!("--symbol and --all-pairs are exclusive\n{}", USAGE)),
+            (Some(symbol), false) => Target::Symbol(symbol),
+            (None, true) => Target::AllPairs,
+            (None, false) => Target::Symbol(DEFAULT_SYMBOL.to_string()),
+        };
+        Ok(CliArgs {
+            target,
+            mode: mode.unwrap_or_default(),
+        })
+    }
+
+    /// Symbols to analyze on each run, an error for a configured pair
+    /// `Symbol` can't represent.
+    pub fn symbols(&self, config: &Config) -> color_eyre::Result<Vec<Symbol>> {
+        match &self.target {
+            Target::Symbol(symbol) => Ok(vec![symbol.parse()?]),
+            Target::AllPairs => config.pairs().iter().map(|pair| pair.parse()).collect(),
+        }
+    }
+}
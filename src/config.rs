@@ -1,10 +1,21 @@
 
 
-use std::{cell::LazyCell, sync::{Arc, LazyLock}};
+use std::{cell::LazyCell, path::Path, sync::{Arc, LazyLock}};
 
 use serde::{Deserialize, Serialize};
 use smart_default::SmartDefault;
+use tracing::warn;
 
+use crate::bot::response_format::JsonSchemaMode;
+use crate::bot::system::StrategyProfile;
+use crate::symbol::QUOTE_ASSETS;
+use crate::utils::no_trade::{parse_windows, TimeWindow};
+
+
+pub const BINANCE_API_URL: &str = "https://api.binance.com";
+pub const BINANCE_TESTNET_API_URL: &str = "https://testnet.binance.vision";
+pub const BINANCE_FUTURES_API_URL: &str = "https://fapi.binance.com";
+pub const BINANCE_FUTURES_TESTNET_API_URL: &str = "https://testnet.binancefuture.com";
 
 pub static CONFIG:LazyLock<Arc<Config>>=LazyLock::new(|| {
     dotenv::dotenv()
@@ -17,15 +28,23 @@ pub static CONFIG:LazyLock<Arc<Config>>=LazyLock::new(|| {
 #[serde(default)]
 pub struct Config{
     #[default = "noop"]
+    #[serde(skip_serializing)]
  pub   binance_api_key:String,
     #[default = "noop"]
+    #[serde(skip_serializing)]
  pub    binance_secret_key:String,
     #[default = "http://localhost:1234"]
   pub  openai_base_url:String,
     #[default = "noop"]
+    #[serde(skip_serializing)]
 pub    openai_api_key:String,
     #[default = "openai/gpt-oss-20b"]
  pub   openai_model:String,
+   /// Ping the LLM endpoint before a backtest starts and fail fast when it's unreachable
+   #[default = false]
+   pub startup_llm_check:bool,
+   /// Structured output mode, `loose` or `none` for local servers that reject strict `json_schema`
+   pub json_schema_mode:JsonSchemaMode,
     #[default = 2]
   pub  max_active_orders:usize,
     #[default = 50]
@@ -34,10 +53,104 @@ pub    max_trade_value:usize,
  pub   web_ui_port:usize,
     #[default = "BTC_USDC,ETH_USDC"]
     pub allowed_pairs: String,
+    /// Base assets paired with `quote_asset` on top of `allowed_pairs`, e.g. `BTC,ETH,SOL`
+    #[default = ""]
+    pub base_assets: String,
+    /// Quote asset of the `base_assets` pairs
+    #[default = "USDC"]
+    pub quote_asset: String,
     #[default = "5m"]
    pub trading_interval:String,
    #[default = 30]
    pub bot_max_turns:usize,
+   /// Refuse to act when the latest candle closed longer ago than this
+   #[default = 900]
+   pub max_data_staleness_secs:u64,
+   /// How often the background scheduler refreshes the Arrow files
+   #[default = 300]
+   pub collection_interval_secs:u64,
+   /// Backfills write to the Arrow file after this many 1000 kline requests
+   #[default = 10]
+   pub backfill_flush_batches:usize,
+   /// Arrow kline files, testnet data goes to a `testnet` subdirectory
+   #[default = "data"]
+   pub data_dir:String,
+   /// Backtest HTML and JSON reports
+   #[default = "reports"]
+   pub reports_dir:String,
+   /// Hourly rotated JSON log files
+   #[default = "logs"]
+   pub logs_dir:String,
+   /// Simulated slippage in percent, applied against every backtest fill; 0 keeps fills at the quoted price
+   #[default = 0.0]
+   pub slippage_pct:f64,
+   /// Minimum profit in percent a sell has to make over the average buy price
+   #[default = 2.5]
+   pub min_profit_pct:f64,
+   /// Exchange fee in percent, charged on each side of a trade
+   #[default = 0.1]
+   pub fee_pct:f64,
+   /// Largest share of the portfolio value, in percent, a single asset may reach through a buy
+   #[default = 100.0]
+   pub max_asset_allocation_pct:f64,
+   /// Positions open longer than this many hours get an exit reminder in the prompt, 0 disables it
+   #[default = 0.0]
+   pub max_holding_hours:f64,
+   /// Positions more than this percent below their average buy price get a partial exit suggestion, 0 disables it
+   #[default = 0.0]
+   pub partial_exit_drawdown_pct:f64,
+   /// Share of the position (0..1) a partial exit sells
+   #[default = 0.5]
+   pub partial_exit_fraction:f64,
+   /// Sell the partial exit right away instead of only suggesting it
+   #[default = false]
+   pub partial_exit_auto:bool,
+   /// Alert rules separated by `;`, e.g. `BTCUSDC RSI < 25; ETHUSDC PRICE > 4000`
+   #[default = ""]
+   pub alerts:String,
+   /// Discord compatible webhook the alerts are posted to, empty to only log them
+   #[default = ""]
+   pub alert_webhook_url:String,
+   /// Candles listed in the "recent" sections of the backtest prompts
+   #[default = 5]
+   pub context_candle_count:usize,
+   /// Also append the recent candles as a raw `time,o,h,l,c,v` block to prompts
+   #[default = false]
+   pub include_raw_ohlc:bool,
+   /// Starting cash of the backtest portfolio simulations
+   #[default = 1000.0]
+   pub initial_portfolio_value:f64,
+   /// Currency the simulated portfolio is held and reported in
+   #[default = "USDC"]
+   pub portfolio_currency:String,
+   /// Trading style the system prompt is built for
+   pub strategy_profile:StrategyProfile,
+   /// Talk to the Binance spot testnet instead of production
+   #[default = false]
+   pub use_testnet:bool,
+   /// While this file exists every action is forced to HOLD, `<data_dir>/HALT` when unset, empty to disable
+   pub kill_switch_path:Option<String>,
+   /// Add perpetual funding rate and open interest to the analysis context
+   #[default = false]
+   pub enable_futures_context:bool,
+   /// Prompts longer than this many characters lose their least important sections, 0 for no limit
+   #[default = 100_000]
+   pub max_context_chars:usize,
+   /// Backtest decisions are cached here by prompt and replayed on later runs, empty to disable
+   #[default = ""]
+   pub decision_cache_path:String,
+   /// Orders the LLM may return in one analysis, above 1 it answers with a batch
+   #[default = 1]
+   pub max_orders_per_run:usize,
+   /// Adjust the model's confidence with the hit-rate of earlier decisions at the same confidence
+   #[default = false]
+   pub calibrate_confidence:bool,
+   /// Comma separated UTC windows in which every decision is forced to HOLD, see `no_trade_time_windows`
+   #[default = ""]
+   pub no_trade_windows:String,
+   /// Directory receiving `<symbol>.jsonl` records of scored backtest decisions, empty disables it
+   #[default = ""]
+   pub dataset_dir:String,
   
    pub backtest_start_date:String
 
@@ -47,7 +160,49 @@ impl Config{
     pub fn load()->Self{
         envy::from_env::<Config>().unwrap_or_default()
     }
-    pub fn pairs_parts(&self)->Vec<(String,String)>{
+    /// REST base URL, the spot testnet when `use_testnet` is set
+    ///
+    /// ```
+    /// use botmarley::config::Config;
+    /// let config = Config { use_testnet: true, ..Default::default() };
+    /// assert_eq!(config.binance_api_url(), "https://testnet.binance.vision");
+    /// assert_eq!(Config::default().binance_api_url(), "https://api.binance.com");
+    /// ```
+    pub fn binance_api_url(&self)->&'static str{
+        if self.use_testnet { BINANCE_TESTNET_API_URL } else { BINANCE_API_URL }
+    }
+    /// USD-M futures REST base URL, following `use_testnet` like the spot one
+    pub fn binance_futures_api_url(&self)->&'static str{
+        if self.use_testnet { BINANCE_FUTURES_TESTNET_API_URL } else { BINANCE_FUTURES_API_URL }
+    }
+    /// Parsed `no_trade_windows`, e.g. `Wed 18-20,Mon-Fri 13-14,2024-12-24..2024-12-26`
+    pub fn no_trade_time_windows(&self)->color_eyre::Result<Vec<TimeWindow>>{
+        parse_windows(&self.no_trade_windows)
+    }
+    /// The kill-switch file, `kill_switch_path` or `HALT` in `data_dir` when it's unset
+    ///
+    /// ```
+    /// use botmarley::config::Config;
+    /// let config = Config { data_dir: "instance_a".to_string(), ..Default::default() };
+    /// assert_eq!(config.kill_switch_file(), std::path::Path::new("instance_a").join("HALT").to_str().unwrap());
+    /// let config = Config { kill_switch_path: Some("/tmp/STOP".to_string()), ..config };
+    /// assert_eq!(config.kill_switch_file(), "/tmp/STOP");
+    /// // set but empty disables the switch
+    /// let config = Config { kill_switch_path: Some(String::new()), ..config };
+    /// assert_eq!(config.kill_switch_file(), "");
+    /// let config: Config = envy::from_iter([("KILL_SWITCH_PATH".to_string(), String::new())]).unwrap();
+    /// assert_eq!(config.kill_switch_file(), "");
+    /// let config: Config = envy::from_iter(Vec::<(String, String)>::new()).unwrap();
+    /// assert_eq!(config.kill_switch_file(), std::path::Path::new("data").join("HALT").to_str().unwrap());
+    /// ```
+    pub fn kill_switch_file(&self)->String{
+        match &self.kill_switch_path {
+            Some(path) => path.clone(),
+            None => Path::new(&self.data_dir).join("HALT").to_string_lossy().into_owned(),
+        }
+    }
+    /// `allowed_pairs` as `(base, quote)`
+    pub fn explicit_pairs_parts(&self)->Vec<(String,String)>{
         let pairs:Vec<&str>=self.allowed_pairs.split(',').collect();
         let pairs_parts:Vec<(String,String)> = pairs.into_iter().filter_map(|p| {
             let parts = p.split('_').collect::<Vec<&str>>();
@@ -59,6 +214,57 @@ impl Config{
         }).collect();
         pairs_parts
     }
+    /// Every `base_assets` entry paired with `quote_asset`, unchecked against the exchange.
+    /// Empty, with a warning, when `quote_asset` isn't one of `QUOTE_ASSETS`.
+    pub fn expanded_pairs_parts(&self)->Vec<(String,String)>{
+        let quote = self.quote_asset.trim().to_uppercase();
+        if quote.is_empty() || self.base_assets.trim().is_empty() {
+            return Vec::new();
+        }
+        if !QUOTE_ASSETS.contains(&quote.as_str()) {
+            warn!("QUOTE_ASSET {} is not supported (use one of {}), ignoring BASE_ASSETS", quote, QUOTE_ASSETS.join(", "));
+            return Vec::new();
+        }
+        self.base_assets
+            .split(',')
+            .map(|base| base.trim().to_uppercase())
+            .filter(|base| !base.is_empty() && *base != quote)
+            .map(|base| (base, quote.clone()))
+            .collect()
+    }
+    /// Explicit pairs followed by the expanded ones they don't already list
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use botmarley::config::Config;
+    /// let config = Config {
+    ///     allowed_pairs: "ETH_USDC,BTC_USDT".to_string(),
+    ///     base_assets: "btc, ETH,SOL".to_string(),
+    ///     quote_asset: "USDC".to_string(),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(config.pairs(), ["ETHUSDC", "BTCUSDT", "BTCUSDC", "SOLUSDC"]);
+    ///
+    /// // every expanded pair is a usable symbol, whatever the supported quote
+    /// use botmarley::symbol::Symbol;
+    /// let config = Config { allowed_pairs: String::new(), base_assets: "BTC,ETH".to_string(), quote_asset: "usdt".to_string(), ..Default::default() };
+    /// let symbols: Vec<Symbol> = config.pairs().iter().map(|pair| pair.parse().unwrap()).collect();
+    /// assert_eq!(symbols.iter().map(Symbol::to_string).collect::<Vec<_>>(), ["BTCUSDT", "ETHUSDT"]);
+    /// assert!(symbols.iter().all(|symbol| symbol.get_quote() == "USDT"));
+    /// // an unsupported quote expands to nothing
+    /// let config = Config { allowed_pairs: String::new(), base_assets: "BTC".to_string(), quote_asset: "EUR".to_string(), ..Default::default() };
+    /// assert!(config.pairs().is_empty());
+    /// ```
+    pub fn pairs_parts(&self)->Vec<(String,String)>{
+        let mut pairs_parts = self.explicit_pairs_parts();
+        for pair in self.expanded_pairs_parts() {
+            if !pairs_parts.contains(&pair) {
+                pairs_parts.push(pair);
+            }
+        }
+        pairs_parts
+    }
     pub fn pairs(&self)->Vec<String>{
         self.pairs_parts().into_iter().map(|(l,r) | format!("{l}{r}")).collect()
     }
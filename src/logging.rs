@@ -216,7 +216,10 @@ impl io::Write for LocalTimeFileAppender {
 pub fn init_logger(){
 
 // Initialize tracing with custom JSON file logging (hourly rotation with local time)
-let file_appender = LocalTimeFileAppender::new("logs", "botmarley")
+// .env is loaded here as well, binaries may init logging before reading it
+let _ = dotenv::dotenv();
+let logs_dir = crate::config::Config::load().logs_dir;
+let file_appender = LocalTimeFileAppender::new(&logs_dir, "botmarley")
 .wrap_err("Failed to create log file appender")
 .with_suggestion(|| "Make sure the logs directory is writable").expect("unable to init file appender");
 let (non_blocking, _guard) = tracing_appender::non_blocking(file_appender);
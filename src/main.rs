@@ -3,10 +3,11 @@ use std::sync::Arc;
 
 // use botmarley::{binance::DataCollector, config::CONFIG};
 // use botmarley::config::Config;
-use botmarley::{bot::{indicators::macd::Macd, klines}, logging::init_logger, symbol::{self, Symbol}};
+use botmarley::{binance::{DataCollectionScheduler, DataCollector}, cli::{CliArgs, RunMode}, bot::{alerts::AlertMonitor, indicators::{agreement_summary, directional_signals, ensemble::compute_ensemble_signal, macd::Macd, regime::RegimeResult, tally_signals}, klines}, config::CONFIG, logging::init_logger, utils::{kill_switch::trading_halted, no_trade::hold_reason, session::{current_session, session_volatility}}, symbol::{self, Symbol}};
 use color_eyre::eyre::Ok;
 use tokio::sync::Mutex;
-use tracing::{info, instrument};
+use tokio::time::{interval, Duration, MissedTickBehavior};
+use tracing::{error, info, instrument, warn};
 // use color_eyre::eyre::WrapErr;
 // use color_eyre::Section;
 
@@ -17,12 +18,57 @@ async fn main() -> color_eyre::Result<()> {
     // let config = (&*CONFIG).clone();
     init_logger();
 
+    let args = CliArgs::parse(std::env::args().skip(1))?;
+    let symbols = args.symbols(&CONFIG)?;
+
     tracing::info!("🚀  Starting BotMarley");
     // botmarley::binance::main_collector_runner::run_data_collector_init().await?;
     // botmarley::binance::main_collector_runner::run_data_collector_collect().await?;
 
-    let symbol:Symbol="BTCUSDC".into();
- let _btcbot=bot_run(symbol).await?;
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    let scheduler = DataCollectionScheduler::new(CONFIG.clone())?;
+    // only block startup on a collection when the stored candles are too old to trade on
+    if scheduler.needs_refresh(&symbols, CONFIG.max_data_staleness_secs).await? {
+        scheduler.tick().await;
+    }
+    let alert_monitor = AlertMonitor::new(&CONFIG)?;
+    let alerts = alert_monitor.has_rules().then(|| alert_monitor.spawn(shutdown_rx.clone()));
+    let mut background_collection = None;
+
+    match args.mode {
+        RunMode::Once => {
+            for symbol in &symbols {
+                bot_run(symbol.clone()).await?;
+            }
+        }
+        RunMode::Watch => {
+            background_collection = Some(scheduler.spawn(shutdown_rx));
+            let every = Duration::from_secs(CONFIG.collection_interval_secs.max(1));
+            info!(symbols=?symbols, every_secs=every.as_secs(), "👀 Watching (Ctrl+C to stop)");
+            let mut ticker = interval(every);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {
+                        for symbol in &symbols {
+                            if let Err(e) = bot_run(symbol.clone()).await {
+                                error!(symbol=%symbol, "Analysis failed: {:?}", e);
+                            }
+                        }
+                    }
+                    _ = tokio::signal::ctrl_c() => break,
+                }
+            }
+        }
+    }
+
+    let _ = shutdown_tx.send(true);
+    if let Some(scheduler) = background_collection {
+        scheduler.await?;
+    }
+    if let Some(alerts) = alerts {
+        alerts.await?;
+    }
 
 
     tracing::info!("🏁 Stoping BotMarley. Bye");
@@ -32,15 +78,40 @@ async fn main() -> color_eyre::Result<()> {
 
 #[instrument]
 async fn bot_run(symbol:Symbol)->color_eyre::Result<()>{
+    if trading_halted(&CONFIG.kill_switch_file()){
+        return Ok(());
+    }
+    if let Some(reason) = hold_reason(&CONFIG.no_trade_time_windows()?, chrono::Utc::now()){
+        warn!("⏸️  {}: forcing HOLD", reason);
+        return Ok(());
+    }
 
 //    let mut klines=botmarley::bot::klines::Klines::new(symbol);
+   let symbol_name=symbol.to_string();
+   let ensemble=compute_ensemble_signal(symbol.clone()).await?;
    let  klines=Arc::new(Mutex::new(botmarley::bot::klines::Klines::new(symbol)));
+    if !klines.lock().await.check_freshness(CONFIG.max_data_staleness_secs).await?{
+        return Ok(());
+    }
     // let klinedata=klines.lock().await.get_klinedata().await?;
 
     let ohcl_data=klines.lock().await.get_ohlc().await?;
 
     let ohcl_count=ohcl_data.count;
     tracing::info!( ohcl_count=ohcl_count);
+    let agreement=tally_signals(&directional_signals(&ohcl_data, &symbol_name));
+    info!("{}", agreement_summary(agreement));
+    info!("{}", RegimeResult::from_ohlc(&ohcl_data).summary());
+    info!("{}", ensemble.summary());
+    if CONFIG.enable_futures_context {
+        let futures = DataCollector::new(CONFIG.clone())?.get_futures_context(&symbol_name).await;
+        if let Err(e) = futures.inspect(|futures| info!("{}", futures.summary())) {
+            warn!("Futures context unavailable for {}: {}", symbol_name, e);
+        }
+    }
+    let session=current_session();
+    let session_range=session_volatility(&ohcl_data.time, &ohcl_data.high, &ohcl_data.low, &ohcl_data.close, session);
+    info!(session=%session, session_range_pct=?session_range, "Active trading session");
 
     let mut macd=Macd{
         klines,
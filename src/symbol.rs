@@ -25,15 +25,16 @@ pub struct Symbol {
      base:String,
      quote:String,
 }
+/// Quote assets a symbol can be split on, the valid `QUOTE_ASSET` values
+pub const QUOTE_ASSETS: [&str; 6] = ["USDC", "USDT", "FDUSD", "BTC", "ETH", "BNB"];
+
 impl Symbol {
     pub fn new(base:String, quote:String)->Self{
         let base=base.to_uppercase();
         let quote=quote.to_uppercase();
-        let quote=match quote.as_str(){
-            "BTC"=>"BTC".to_string(),
-            "USDC"=>"USDC".to_string(),
-            _=> panic!("QUOTE NOT ALLOWED")
-        };
+        if !QUOTE_ASSETS.contains(&quote.as_str()){
+            panic!("QUOTE NOT ALLOWED");
+        }
         Self{
             base,quote
         }
@@ -46,18 +47,38 @@ impl Symbol {
     }
 }
 
-impl From<String> for Symbol{
-fn from(value: String) -> Self {
-    let value=value.to_uppercase().replace("_", "");
+/// Split a pair like `ETHUSDC` or `eth_btc` on its quote asset.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::symbol::Symbol;
+/// let symbol: Symbol = "eth_usdc".parse().unwrap();
+/// assert_eq!((symbol.get_base().as_str(), symbol.get_quote().as_str()), ("ETH", "USDC"));
+/// assert_eq!("SOLBTC".parse::<Symbol>().unwrap().to_string(), "SOLBTC");
+/// assert_eq!("ethusdt".parse::<Symbol>().unwrap().get_quote(), "USDT");
+/// assert!("ETHXYZ".parse::<Symbol>().is_err());
+/// assert!("USDC".parse::<Symbol>().is_err());
+/// ```
+impl FromStr for Symbol {
+    type Err = color_eyre::Report;
 
-   if value.ends_with("BTC"){
-    return Symbol { base: value.rsplit_once("BTC").unwrap().0.to_string(), quote: "BTC".into() }
-   }
- if value.ends_with("USDC"){
-    return Symbol { base: value.rsplit_once("USDC").unwrap().0.to_string(), quote: "USDC".into() }
-   }
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.to_uppercase().replace('_', "");
+        QUOTE_ASSETS
+            .iter()
+            .find_map(|quote| {
+                let base = value.strip_suffix(quote)?;
+                (!base.is_empty()).then(|| Symbol { base: base.to_string(), quote: quote.to_string() })
+            })
+            .ok_or_else(|| color_eyre::eyre::eyre!("Cannot convert {} to a symbol, the quote asset must be one of {}", value, QUOTE_ASSETS.join(", ")))
+    }
+}
 
-panic!("cannot convert to symbol");
+/// Panics on an unsupported quote asset, parse untrusted input with `FromStr`
+impl From<String> for Symbol{
+fn from(value: String) -> Self {
+    value.parse().unwrap_or_else(|e| panic!("{}", e))
 }
 }
 impl From<&str> for Symbol{
@@ -66,8 +87,8 @@ impl From<&str> for Symbol{
     }
 }
 
-impl ToString for Symbol{
-    fn to_string(&self) -> String {
-        format!("{}{}",self.base,self.quote)
+impl std::fmt::Display for Symbol{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.base, self.quote)
     }
 }
\ No newline at end of file
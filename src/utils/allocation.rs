@@ -0,0 +1,27 @@
+/// Share of the portfolio, in percent, an asset would have after buying
+/// `trade_value` more of it.
+///
+/// A buy only swaps cash for the asset, so the portfolio value is unchanged.
+/// Returns 0 for an empty portfolio.
+pub fn allocation_after_buy_pct(asset_value: f64, trade_value: f64, portfolio_value: f64) -> f64 {
+    if portfolio_value <= 0.0 {
+        return 0.0;
+    }
+    (asset_value + trade_value) / portfolio_value * 100.0
+}
+
+/// `true` when a buy would push the asset over `max_pct` of the portfolio.
+/// A cap of 100% or more never rejects.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::utils::allocation::exceeds_allocation_cap;
+/// // $10k portfolio already holding $3k of BTC, 40% cap
+/// assert!(exceeds_allocation_cap(3_000.0, 1_500.0, 10_000.0, 40.0));
+/// assert!(!exceeds_allocation_cap(3_000.0, 1_000.0, 10_000.0, 40.0));
+/// assert!(!exceeds_allocation_cap(3_000.0, 7_000.0, 10_000.0, 100.0));
+/// ```
+pub fn exceeds_allocation_cap(asset_value: f64, trade_value: f64, portfolio_value: f64, max_pct: f64) -> bool {
+    max_pct < 100.0 && allocation_after_buy_pct(asset_value, trade_value, portfolio_value) > max_pct
+}
@@ -0,0 +1,36 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+use color_eyre::eyre::WrapErr;
+
+/// Replace `path` with `contents` without ever leaving a half written file:
+/// the data goes to `<path>.tmp` first and is renamed over the target.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::utils::atomic_write::write_atomic;
+/// let path = std::env::temp_dir().join(format!("botmarley_atomic_{}.json", std::process::id()));
+/// write_atomic(&path, "{}").unwrap();
+/// write_atomic(&path, "{\"a\":1}").unwrap();
+/// assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\"a\":1}");
+/// assert!(!path.with_extension("json.tmp").exists());
+/// std::fs::remove_file(&path).unwrap();
+/// ```
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> color_eyre::Result<()> {
+    if let Some(dir) = path.parent()
+        && !dir.as_os_str().is_empty()
+    {
+        fs::create_dir_all(dir)?;
+    }
+    let mut tmp_name = path.as_os_str().to_owned();
+    tmp_name.push(".tmp");
+    let tmp = Path::new(&tmp_name);
+
+    let mut file = File::create(tmp).wrap_err_with(|| format!("Failed to create {}", tmp.display()))?;
+    file.write_all(contents.as_ref())
+        .and_then(|_| file.sync_all())
+        .wrap_err_with(|| format!("Failed to write {}", tmp.display()))?;
+    fs::rename(tmp, path).wrap_err_with(|| format!("Failed to replace {}", path.display()))
+}
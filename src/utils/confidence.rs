@@ -0,0 +1,38 @@
+/// Confidence buckets, decisions within the same tenth are compared
+pub const CONFIDENCE_BUCKETS: usize = 10;
+/// Weight of the raw confidence, in decisions: a bucket with this many scored
+/// decisions is trusted as much as the model's own number
+pub const CALIBRATION_PRIOR: f64 = 5.0;
+
+fn bucket(confidence: f64) -> usize {
+    ((confidence.clamp(0.0, 1.0) * CONFIDENCE_BUCKETS as f64) as usize).min(CONFIDENCE_BUCKETS - 1)
+}
+
+/// Pull a self-reported confidence toward the hit-rate of past decisions
+/// reported with a similar confidence.
+///
+/// `history` holds `(reported confidence, was correct)` of scored decisions.
+/// The hit-rate of the matching bucket is blended with `raw` weighted by
+/// `CALIBRATION_PRIOR`, so a few lucky or unlucky decisions barely move it
+/// and an empty bucket returns `raw` unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::utils::confidence::calibrate_confidence;
+/// // the model says 90% but was right half of the time at that level
+/// let history: Vec<(f64, bool)> = (0..40).map(|i| (0.9, i % 2 == 0)).collect();
+/// let calibrated = calibrate_confidence(0.92, &history);
+/// assert!(calibrated < 0.6 && calibrated > 0.5);
+/// // other buckets don't count
+/// assert!((calibrate_confidence(0.55, &history) - 0.55).abs() < 1e-9);
+/// assert!((calibrate_confidence(0.92, &[]) - 0.92).abs() < 1e-9);
+/// ```
+pub fn calibrate_confidence(raw: f64, history: &[(f64, bool)]) -> f64 {
+    let target = bucket(raw);
+    let (count, hits) = history
+        .iter()
+        .filter(|(confidence, _)| bucket(*confidence) == target)
+        .fold((0.0, 0.0), |(count, hits), (_, correct)| (count + 1.0, hits + if *correct { 1.0 } else { 0.0 }));
+    (hits + CALIBRATION_PRIOR * raw) / (count + CALIBRATION_PRIOR)
+}
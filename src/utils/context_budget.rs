@@ -0,0 +1,60 @@
+use tracing::warn;
+
+/// Drop whole sections of an LLM prompt, least important first, until it
+/// fits in `max_chars` characters. `0` disables the limit.
+///
+/// A section runs from one of the `droppable` headers to the next header of
+/// either list, so `boundaries` should hold every header that must survive
+/// (current price, decision instructions...). Droppable headers are tried in
+/// order, each occurrence first to last, and every dropped section leaves a
+/// one-line note. A prompt still over budget after that is returned as is.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::utils::context_budget::fit_context;
+/// let prompt = format!("Price: 100\n\nRaw OHLCV:\n{}\nPlease decide.", "1,2,3,4\n".repeat(10));
+/// assert_eq!(fit_context(&prompt, 0, &["\nRaw OHLCV"], &["\nPlease"]), prompt);
+/// let fitted = fit_context(&prompt, 80, &["\nRaw OHLCV"], &["\nPlease"]);
+/// assert!(fitted.chars().count() <= 80);
+/// assert!(fitted.starts_with("Price: 100\n") && fitted.ends_with("\nPlease decide."));
+/// assert!(!fitted.contains("1,2,3,4"));
+/// ```
+pub fn fit_context(message: &str, max_chars: usize, droppable: &[&str], boundaries: &[&str]) -> String {
+    let mut message = message.to_string();
+    let original_chars = message.chars().count();
+    if max_chars == 0 || original_chars <= max_chars {
+        return message;
+    }
+
+    let mut dropped = Vec::new();
+    for header in droppable {
+        let mut from = 0;
+        while message.chars().count() > max_chars {
+            let Some(start) = message[from..].find(header).map(|i| i + from) else {
+                break;
+            };
+            let body = start + header.len();
+            let end = droppable
+                .iter()
+                .chain(boundaries)
+                .filter_map(|next| message[body..].find(next).map(|i| i + body))
+                .min()
+                .unwrap_or(message.len());
+
+            let title = message[start..end].trim_start().lines().next().unwrap_or_default().trim_end_matches(':').to_string();
+            let note = format!("\n[{} omitted to fit the context]\n", title);
+            message.replace_range(start..end, &note);
+            from = start + note.len();
+            dropped.push(title);
+        }
+    }
+
+    let chars = message.chars().count();
+    if chars > max_chars {
+        warn!(original_chars, chars, max_chars, dropped=?dropped, "Prompt is still over the context budget after truncation");
+    } else {
+        warn!(original_chars, chars, max_chars, dropped=?dropped, "Prompt truncated to fit the context budget");
+    }
+    message
+}
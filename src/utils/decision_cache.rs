@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::fs;
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use color_eyre::eyre::WrapErr;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::utils::atomic_write::write_atomic;
+
+/// New entries are written out at most this often, and on `flush`
+pub const FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// FNV-1a, stable across Rust versions and runs unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8], mut hash: u64) -> u64 {
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// LLM decisions of earlier backtest runs keyed by the prompt that produced
+/// them, so re-running a backtest with unchanged prompts costs no API calls.
+///
+/// An empty path disables the cache: nothing is read, stored or written.
+/// New entries are batched and written atomically every `FLUSH_INTERVAL`;
+/// call `flush` once the run is done.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::utils::decision_cache::DecisionCache;
+/// let path = std::env::temp_dir().join(format!("botmarley_cache_{}.json", std::process::id()));
+/// let path = path.to_str().unwrap();
+/// let key = DecisionCache::key("model", "system", "user");
+/// let rt = tokio::runtime::Runtime::new().unwrap();
+/// let mut api_calls = 0;
+///
+/// let mut first = DecisionCache::load(path).unwrap();
+/// let decision: String = rt
+///     .block_on(first.get_or_fetch(&key, || { api_calls += 1; async { Ok("buy".to_string()) } }))
+///     .unwrap();
+/// let _: String = rt
+///     .block_on(first.get_or_fetch("other", || { api_calls += 1; async { Ok("hold".to_string()) } }))
+///     .unwrap();
+/// assert_eq!((decision.as_str(), api_calls), ("buy", 2));
+/// // both misses are written together, once
+/// assert!(!std::path::Path::new(path).exists());
+/// first.flush().unwrap();
+///
+/// let mut second = DecisionCache::load(path).unwrap();
+/// let decision: String = rt
+///     .block_on(second.get_or_fetch(&key, || { api_calls += 1; async { Ok("sell".to_string()) } }))
+///     .unwrap();
+/// assert_eq!((decision.as_str(), api_calls, second.hits), ("buy", 2, 1));
+/// std::fs::remove_file(path).unwrap();
+/// ```
+#[derive(Debug, Default)]
+pub struct DecisionCache {
+    path: Option<PathBuf>,
+    entries: HashMap<String, serde_json::Value>,
+    /// Entries added since the last write
+    dirty: bool,
+    last_flush: Option<Instant>,
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl DecisionCache {
+    pub fn load(path: &str) -> color_eyre::Result<Self> {
+        if path.is_empty() {
+            return Ok(Self::default());
+        }
+        let path = PathBuf::from(path);
+        let entries = if path.exists() {
+            let text = fs::read_to_string(&path)
+                .wrap_err_with(|| format!("Failed to read decision cache {}", path.display()))?;
+            serde_json::from_str(&text)
+                .wrap_err_with(|| format!("Failed to parse decision cache {}", path.display()))?
+        } else {
+            HashMap::new()
+        };
+        Ok(Self {
+            path: Some(path),
+            entries,
+            last_flush: Some(Instant::now()),
+            ..Self::default()
+        })
+    }
+
+    /// Cache key of a request; any change to the model or either message is a miss.
+    pub fn key(model: &str, system_message: &str, user_message: &str) -> String {
+        let mut hash = 0xcbf29ce484222325;
+        for part in [model, system_message, user_message] {
+            hash = fnv1a(part.as_bytes(), hash);
+            // separator, so moving text between parts changes the key
+            hash = fnv1a(&[0xff], hash);
+        }
+        format!("{:016x}", hash)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Cached value of `key`, or the result of `fetch`, which is then stored.
+    /// Errors from `fetch` are returned and not cached.
+    pub async fn get_or_fetch<T, F, Fut>(&mut self, key: &str, fetch: F) -> color_eyre::Result<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = color_eyre::Result<T>>,
+    {
+        if let Some(value) = self.entries.get(key)
+            && let Ok(cached) = serde_json::from_value(value.clone())
+        {
+            self.hits += 1;
+            return Ok(cached);
+        }
+
+        self.misses += 1;
+        let value = fetch().await?;
+        if self.path.is_some() {
+            self.entries.insert(key.to_string(), serde_json::to_value(&value)?);
+            self.dirty = true;
+            // periodic flushes keep what an interrupted run paid for
+            if self.last_flush.is_some_and(|at| at.elapsed() >= FLUSH_INTERVAL) {
+                self.flush()?;
+            }
+        }
+        Ok(value)
+    }
+
+    /// Write pending entries, a no-op when nothing changed
+    pub fn flush(&mut self) -> color_eyre::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if self.dirty {
+            write_atomic(path, serde_json::to_string(&self.entries)?)
+                .wrap_err_with(|| format!("Failed to write decision cache {}", path.display()))?;
+            self.dirty = false;
+        }
+        self.last_flush = Some(Instant::now());
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::{eyre, WrapErr};
+
+/// Advisory lock guarding writes to a shared file across processes.
+///
+/// The lock is an OS file lock on `<file>.lock` next to the target, held
+/// until the guard is dropped. The operating system releases it when the
+/// process dies, so a killed collector never leaves a stale lock behind;
+/// the `.lock` file itself stays and is reused.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::utils::file_lock::FileLock;
+/// let target = std::env::temp_dir().join(format!("botmarley_doc_{}.arrow", std::process::id()));
+/// let lock_file = target.with_extension("arrow.lock");
+///
+/// // a lock file left by a killed process doesn't block anyone
+/// std::fs::write(&lock_file, "12345\n").unwrap();
+/// let first = FileLock::acquire(&target).unwrap();
+/// // contention: a second guard fails while the first is alive
+/// let error = FileLock::acquire(&target).unwrap_err().to_string();
+/// assert!(error.contains("locked by another process"), "{}", error);
+/// // release on drop
+/// drop(first);
+/// assert!(FileLock::acquire(&target).is_ok());
+/// std::fs::remove_file(&lock_file).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct FileLock {
+    path: PathBuf,
+    file: File,
+}
+
+impl FileLock {
+    pub fn acquire(target: &Path) -> color_eyre::Result<Self> {
+        let mut name = target.as_os_str().to_owned();
+        name.push(".lock");
+        let path = PathBuf::from(name);
+
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .wrap_err_with(|| format!("Failed to open lock file {}", path.display()))?;
+        match file.try_lock() {
+            Ok(()) => Ok(Self { path, file }),
+            Err(TryLockError::WouldBlock) => Err(eyre!("{} is locked by another process", target.display())),
+            Err(TryLockError::Error(e)) => Err(e).wrap_err_with(|| format!("Failed to lock {}", path.display())),
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        if let Err(e) = self.file.unlock() {
+            tracing::warn!("Failed to unlock {}: {}", self.path.display(), e);
+        }
+    }
+}
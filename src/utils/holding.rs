@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+/// Amounts at or below this count as a closed position
+const DUST: f64 = 1e-12;
+
+/// Open time of the current position, from one pair's `(timestamp ms,
+/// action, amount)` trades in time order: the first buy since the held
+/// amount was last zero. `None` when nothing is held.
+pub fn position_opened_at<'a>(trades: impl IntoIterator<Item = (i64, &'a str, f64)>) -> Option<i64> {
+    let mut held = 0.0;
+    let mut opened_at = None;
+    for (timestamp, action, amount) in trades {
+        match action {
+            "buy" => {
+                if held <= DUST {
+                    opened_at = Some(timestamp);
+                }
+                held += amount;
+            }
+            "sell" => {
+                held -= amount;
+                if held <= DUST {
+                    held = 0.0;
+                    opened_at = None;
+                }
+            }
+            _ => {}
+        }
+    }
+    opened_at
+}
+
+/// How long the current position has been open at `now_ms`.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use botmarley::utils::holding::holding_duration;
+/// let hour = 60 * 60 * 1000;
+/// // bought at 0, added at 2h, partly sold at 3h: still open since 0
+/// let trades = [(0, "buy", 1.0), (2 * hour, "buy", 1.0), (3 * hour, "sell", 1.5)];
+/// assert_eq!(holding_duration(trades, 30 * hour), Some(Duration::from_secs(30 * 3600)));
+/// // closed at 4h and re-opened at 10h
+/// let trades = [(0, "buy", 1.0), (4 * hour, "sell", 1.0), (10 * hour, "buy", 0.5)];
+/// assert_eq!(holding_duration(trades, 12 * hour), Some(Duration::from_secs(2 * 3600)));
+/// assert_eq!(holding_duration([(0, "buy", 1.0), (hour, "sell", 1.0)], 2 * hour), None);
+/// ```
+pub fn holding_duration<'a>(trades: impl IntoIterator<Item = (i64, &'a str, f64)>, now_ms: i64) -> Option<Duration> {
+    let opened_at = position_opened_at(trades)?;
+    Some(Duration::from_millis((now_ms - opened_at).max(0) as u64))
+}
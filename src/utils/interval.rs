@@ -0,0 +1,65 @@
+use std::time::Duration;
+
+/// Interval of the stored klines
+pub const KLINE_INTERVAL: &str = "5m";
+
+/// Length of a Binance kline interval (`1s`, `5m`, `4h`, `1d`, `1w`...).
+/// Months (`1M`) have no fixed length and are `None`.
+pub fn interval_duration(interval: &str) -> Option<Duration> {
+    let unit_at = interval.len().checked_sub(1)?;
+    let count: u64 = interval[..unit_at].parse().ok().filter(|n| *n > 0)?;
+    let unit_secs = match &interval[unit_at..] {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return None,
+    };
+    Some(Duration::from_secs(count * unit_secs))
+}
+
+/// Candles of `interval` needed to cover `duration`, rounded up.
+///
+/// # Panics
+///
+/// If `interval` is not a fixed length interval, see `interval_duration`.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use botmarley::utils::interval::interval_candles;
+/// let (m30, h1, h2) = (Duration::from_secs(30 * 60), Duration::from_secs(3600), Duration::from_secs(7200));
+/// assert_eq!([m30, h1, h2].map(|d| interval_candles("1m", d)), [30, 60, 120]);
+/// assert_eq!([m30, h1, h2].map(|d| interval_candles("5m", d)), [6, 12, 24]);
+/// // half an hour still needs a whole 1h candle
+/// assert_eq!([m30, h1, h2].map(|d| interval_candles("1h", d)), [1, 1, 2]);
+/// ```
+pub fn interval_candles(interval: &str, duration: Duration) -> usize {
+    let candle = interval_duration(interval)
+        .unwrap_or_else(|| panic!("Unsupported kline interval '{}'", interval))
+        .as_millis();
+    duration.as_millis().div_ceil(candle) as usize
+}
+
+/// Time covered by `candles` candles of `interval`.
+///
+/// # Panics
+///
+/// If `interval` is not a fixed length interval, see `interval_duration`.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use botmarley::utils::interval::candles_to_duration;
+/// assert_eq!(candles_to_duration("5m", 12), Duration::from_secs(3600));
+/// assert_eq!(candles_to_duration("1m", 30), Duration::from_secs(30 * 60));
+/// assert_eq!(candles_to_duration("1h", 2), Duration::from_secs(7200));
+/// ```
+pub fn candles_to_duration(interval: &str, candles: usize) -> Duration {
+    interval_duration(interval)
+        .unwrap_or_else(|| panic!("Unsupported kline interval '{}'", interval))
+        * candles as u32
+}
@@ -0,0 +1,103 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use chrono::DateTime;
+
+/// One decision of a backtest, as it appears in the journal
+#[derive(Debug, Clone)]
+pub struct JournalEntry {
+    /// Open time of the decision candle, in milliseconds
+    pub timestamp: i64,
+    pub action: String,
+    /// Price the decision was made at
+    pub price: f64,
+    /// Price when the outcome was scored, `None` while it's unknown
+    pub exit_price: Option<f64>,
+    /// P&L in percent at `exit_price`
+    pub profit_loss_pct: Option<f64>,
+    pub confidence: f64,
+    pub reasoning: String,
+}
+
+/// Markdown trade journal of `entries`, one `## YYYY-MM-DD` (UTC) section per
+/// day in time order. Buys and sells are listed with their entry, exit, P&L,
+/// confidence and reasoning; holds are only counted.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::utils::journal::{journal_markdown, JournalEntry};
+/// let entry = |timestamp: i64, action: &str, exit_price: Option<f64>, profit_loss_pct: Option<f64>| JournalEntry {
+///     timestamp,
+///     action: action.to_string(),
+///     price: 100.0,
+///     exit_price,
+///     profit_loss_pct,
+///     confidence: 0.75,
+///     reasoning: format!("{} reason", action),
+/// };
+/// let journal = journal_markdown("BTCUSDC journal", &[
+///     entry(1_700_006_400_000, "sell", None, None),
+///     entry(1_700_000_000_000, "buy", Some(102.0), Some(2.0)),
+///     entry(1_700_000_300_000, "hold", Some(101.0), Some(0.0)),
+/// ]);
+/// assert!(journal.starts_with("# BTCUSDC journal\n"));
+/// let day_1 = journal.find("## 2023-11-14").unwrap();
+/// let day_2 = journal.find("## 2023-11-15").unwrap();
+/// assert!(day_1 < journal.find("BUY at 100.0000").unwrap());
+/// assert!(journal.contains("exit 102.0000, P&L +2.00%, confidence 75%"));
+/// assert!(journal.contains("> buy reason"));
+/// assert!(journal.contains("1 trade, 1 hold, total P&L +2.00%"));
+/// assert!(day_2 < journal.find("SELL at 100.0000, exit pending, confidence 75%").unwrap());
+/// assert!(!journal.contains("hold reason"));
+/// ```
+pub fn journal_markdown(title: &str, entries: &[JournalEntry]) -> String {
+    let mut days: BTreeMap<String, Vec<&JournalEntry>> = BTreeMap::new();
+    for entry in entries {
+        let day = DateTime::from_timestamp_millis(entry.timestamp)
+            .map(|time| time.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| "unknown date".to_string());
+        days.entry(day).or_default().push(entry);
+    }
+
+    let mut out = format!("# {}\n", title);
+    for (day, mut day_entries) in days {
+        day_entries.sort_by_key(|entry| entry.timestamp);
+        let (trades, holds): (Vec<&JournalEntry>, Vec<&JournalEntry>) =
+            day_entries.into_iter().partition(|entry| entry.action != "hold");
+        let total: f64 = trades.iter().filter_map(|entry| entry.profit_loss_pct).sum();
+
+        let _ = writeln!(out, "\n## {}\n", day);
+        let _ = writeln!(
+            out,
+            "{} trade{}, {} hold{}, total P&L {:+.2}%",
+            trades.len(),
+            if trades.len() == 1 { "" } else { "s" },
+            holds.len(),
+            if holds.len() == 1 { "" } else { "s" },
+            total
+        );
+        for entry in trades {
+            let time = DateTime::from_timestamp_millis(entry.timestamp)
+                .map(|time| time.format("%H:%M").to_string())
+                .unwrap_or_default();
+            let outcome = match (entry.exit_price, entry.profit_loss_pct) {
+                (Some(exit), Some(pl)) => format!("exit {:.4}, P&L {:+.2}%", exit, pl),
+                _ => "exit pending".to_string(),
+            };
+            let _ = writeln!(
+                out,
+                "\n### {} {} at {:.4}, {}, confidence {:.0}%\n",
+                time,
+                entry.action.to_uppercase(),
+                entry.price,
+                outcome,
+                entry.confidence * 100.0
+            );
+            for line in entry.reasoning.lines() {
+                let _ = writeln!(out, "> {}", line);
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,30 @@
+use std::path::Path;
+
+use tracing::warn;
+
+/// `true` while the kill-switch file exists; every action must then be HOLD.
+/// An empty path disables the switch.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::utils::kill_switch::kill_switch_engaged;
+/// let halt = std::env::temp_dir().join(format!("botmarley_halt_{}", std::process::id()));
+/// assert!(!kill_switch_engaged(halt.to_str().unwrap()));
+/// std::fs::write(&halt, "").unwrap();
+/// assert!(kill_switch_engaged(halt.to_str().unwrap()));
+/// std::fs::remove_file(&halt).unwrap();
+/// assert!(!kill_switch_engaged(halt.to_str().unwrap()));
+/// ```
+pub fn kill_switch_engaged(path: &str) -> bool {
+    !path.is_empty() && Path::new(path).exists()
+}
+
+/// Check the switch before acting, logging a prominent warning when it's engaged.
+pub fn trading_halted(path: &str) -> bool {
+    let halted = kill_switch_engaged(path);
+    if halted {
+        warn!("🛑 KILL SWITCH ENGAGED ({} exists): forcing HOLD, remove the file to resume trading", path);
+    }
+    halted
+}
@@ -0,0 +1,62 @@
+use color_eyre::eyre::eyre;
+use serde::{Deserialize, Serialize};
+
+/// One limit sell of a take-profit ladder.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LadderOrder {
+    pub profit_pct: f64,
+    pub price: f64,
+    pub quantity: f64,
+}
+
+/// Limit sells scaling out of a position, one per `(profit_pct, size_fraction)`
+/// level, sorted by price.
+///
+/// Fractions must be positive and sum to at most 1.0. When they sum to 1.0
+/// the last order takes the remainder so the full position is covered
+/// despite float rounding.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::utils::ladder::compute_take_profit_ladder;
+/// let orders = compute_take_profit_ladder(100.0, 3.0, vec![(2.0, 0.5), (4.0, 0.25), (6.0, 0.25)]).unwrap();
+/// let prices: Vec<f64> = orders.iter().map(|o| o.price).collect();
+/// assert_eq!(prices, vec![102.0, 104.0, 106.0]);
+/// assert_eq!(orders.iter().map(|o| o.quantity).sum::<f64>(), 3.0);
+/// assert!(compute_take_profit_ladder(100.0, 3.0, vec![(2.0, 0.8), (4.0, 0.8)]).is_err());
+/// ```
+pub fn compute_take_profit_ladder(
+    entry_price: f64,
+    position_size: f64,
+    mut levels: Vec<(f64, f64)>,
+) -> color_eyre::Result<Vec<LadderOrder>> {
+    if entry_price <= 0.0 || position_size <= 0.0 {
+        return Err(eyre!("Entry price and position size must be positive"));
+    }
+    if let Some((pct, fraction)) = levels.iter().find(|(pct, fraction)| *pct <= 0.0 || *fraction <= 0.0) {
+        return Err(eyre!("Invalid ladder level ({}%, {}): profit and fraction must be positive", pct, fraction));
+    }
+    let total_fraction: f64 = levels.iter().map(|(_, fraction)| fraction).sum();
+    if total_fraction > 1.0 + 1e-9 {
+        return Err(eyre!("Ladder fractions sum to {:.4}, more than the whole position", total_fraction));
+    }
+
+    levels.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut orders: Vec<LadderOrder> = levels
+        .iter()
+        .map(|(profit_pct, fraction)| LadderOrder {
+            profit_pct: *profit_pct,
+            price: entry_price * (1.0 + profit_pct / 100.0),
+            quantity: position_size * fraction,
+        })
+        .collect();
+
+    if (total_fraction - 1.0).abs() <= 1e-9 {
+        let allocated: f64 = orders.iter().rev().skip(1).map(|o| o.quantity).sum();
+        if let Some(last) = orders.last_mut() {
+            last.quantity = position_size - allocated;
+        }
+    }
+    Ok(orders)
+}
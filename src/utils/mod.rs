@@ -1,2 +1,21 @@
+pub mod allocation;
+pub mod atomic_write;
+pub mod confidence;
+pub mod context_budget;
 pub mod date_to_timestamp;
-pub mod to_precision;
\ No newline at end of file
+pub mod decision_cache;
+pub mod file_lock;
+pub mod holding;
+pub mod interval;
+pub mod journal;
+pub mod kill_switch;
+pub mod ladder;
+pub mod no_trade;
+pub mod partial_exit;
+pub mod profit_guard;
+pub mod report_math;
+pub mod risk;
+pub mod session;
+pub mod slippage;
+pub mod to_precision;
+pub mod walk_forward;
@@ -0,0 +1,139 @@
+use chrono::{DateTime, Datelike, NaiveDate, Timelike, Utc, Weekday};
+use color_eyre::eyre::{eyre, WrapErr};
+
+/// UTC period in which the bot must not trade, e.g. around CPI or FOMC
+/// releases or a known thin-liquidity window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeWindow {
+    /// Hours `start_hour..end_hour` on every day from `first` to `last`
+    /// (wrapping, so `Sat-Mon` is the weekend plus Monday). An end before the
+    /// start runs past midnight, an end of 24 up to it.
+    Weekly { first: Weekday, last: Weekday, start_hour: u32, end_hour: u32 },
+    /// Whole days `from..=to`
+    Dates { from: NaiveDate, to: NaiveDate },
+}
+
+impl TimeWindow {
+    pub fn contains(&self, time: DateTime<Utc>) -> bool {
+        match self {
+            TimeWindow::Weekly { first, last, start_hour, end_hour } => {
+                let hour = time.hour();
+                // hours after midnight belong to the window opened the day before
+                let (day, in_hours) = if start_hour < end_hour {
+                    (time.weekday(), (*start_hour..*end_hour).contains(&hour))
+                } else if hour >= *start_hour {
+                    (time.weekday(), true)
+                } else {
+                    (time.weekday().pred(), hour < *end_hour)
+                };
+                let span = (last.num_days_from_monday() + 7 - first.num_days_from_monday()) % 7;
+                let offset = (day.num_days_from_monday() + 7 - first.num_days_from_monday()) % 7;
+                in_hours && offset <= span
+            }
+            TimeWindow::Dates { from, to } => (*from..=*to).contains(&time.date_naive()),
+        }
+    }
+}
+
+impl std::fmt::Display for TimeWindow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeWindow::Weekly { first, last, start_hour, end_hour } if first == last => {
+                write!(f, "{} {:02}:00-{:02}:00 UTC", first, start_hour, end_hour)
+            }
+            TimeWindow::Weekly { first, last, start_hour, end_hour } => {
+                write!(f, "{}-{} {:02}:00-{:02}:00 UTC", first, last, start_hour, end_hour)
+            }
+            TimeWindow::Dates { from, to } if from == to => write!(f, "{}", from),
+            TimeWindow::Dates { from, to } => write!(f, "{} to {}", from, to),
+        }
+    }
+}
+
+fn parse_hour(s: &str) -> color_eyre::Result<u32> {
+    match s.trim().parse() {
+        Ok(hour) if hour <= 24 => Ok(hour),
+        _ => Err(eyre!("'{}' is not an hour from 0 to 24", s)),
+    }
+}
+
+impl std::str::FromStr for TimeWindow {
+    type Err = color_eyre::Report;
+
+    /// `Wed 18-20`, `Mon-Fri 13-14`, `* 0-2` (every day), `Sun 0-24` (the
+    /// whole day), `2024-12-18` or `2024-12-18..2024-12-19`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some((days, hours)) = s.split_once(' ') {
+            let weekday = |d: &str| {
+                d.parse::<Weekday>()
+                    .map_err(|_| eyre!("'{}' in no-trade window '{}' is not a weekday like Mon", d, s))
+            };
+            let (first, last) = match days.split_once('-') {
+                _ if days == "*" => (Weekday::Mon, Weekday::Sun),
+                Some((first, last)) => (weekday(first)?, weekday(last)?),
+                None => (weekday(days)?, weekday(days)?),
+            };
+            let (start, end) = hours
+                .split_once('-')
+                .ok_or_else(|| eyre!("No-trade window '{}' needs an hour range like 18-20", s))?;
+            // 24 is midnight: the start of the next day, the end of this one
+            let (start_hour, end_hour) = (parse_hour(start)? % 24, parse_hour(end)?);
+            if start_hour == end_hour {
+                return Err(eyre!("No-trade window '{}' has an empty hour range", s));
+            }
+            return Ok(TimeWindow::Weekly { first, last, start_hour, end_hour });
+        }
+
+        let (from, to) = s.split_once("..").unwrap_or((s, s));
+        let parse_date = |d: &str| {
+            NaiveDate::parse_from_str(d.trim(), "%Y-%m-%d").wrap_err_with(|| format!("Invalid date in no-trade window '{}'", s))
+        };
+        let (from, to) = (parse_date(from)?, parse_date(to)?);
+        if to < from {
+            return Err(eyre!("No-trade window '{}' ends before it starts", s));
+        }
+        Ok(TimeWindow::Dates { from, to })
+    }
+}
+
+/// Parse a comma separated list of windows, see `TimeWindow::from_str`.
+/// An empty list never blocks.
+pub fn parse_windows(spec: &str) -> color_eyre::Result<Vec<TimeWindow>> {
+    spec.split(',')
+        .filter(|item| !item.trim().is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+/// Why a decision at `time` has to be a HOLD, `None` outside every window.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::utils::no_trade::{hold_reason, parse_windows};
+/// use chrono::{TimeZone, Utc};
+/// let windows = parse_windows("Wed 18-20, Fri-Mon 22-2, 2024-12-24..2024-12-26").unwrap();
+/// // Wednesday 2024-12-18, FOMC
+/// let fomc = Utc.with_ymd_and_hms(2024, 12, 18, 19, 0, 0).unwrap();
+/// assert_eq!(hold_reason(&windows, fomc).unwrap(), "no-trade window Wed 18:00-20:00 UTC");
+/// assert!(hold_reason(&windows, Utc.with_ymd_and_hms(2024, 12, 18, 20, 0, 0).unwrap()).is_none());
+/// // 01:00 on Tuesday still belongs to Monday night
+/// assert!(hold_reason(&windows, Utc.with_ymd_and_hms(2024, 12, 17, 1, 0, 0).unwrap()).is_some());
+/// assert!(hold_reason(&windows, Utc.with_ymd_and_hms(2024, 12, 17, 23, 0, 0).unwrap()).is_none());
+/// assert!(hold_reason(&windows, Utc.with_ymd_and_hms(2024, 12, 25, 12, 0, 0).unwrap()).is_some());
+/// assert!(parse_windows("Wed 18").is_err());
+///
+/// // a whole day is 0-24
+/// let sunday = parse_windows("Sun 0-24").unwrap();
+/// assert!(hold_reason(&sunday, Utc.with_ymd_and_hms(2024, 12, 22, 0, 0, 0).unwrap()).is_some());
+/// assert_eq!(hold_reason(&sunday, Utc.with_ymd_and_hms(2024, 12, 22, 23, 59, 0).unwrap()).unwrap(), "no-trade window Sun 00:00-24:00 UTC");
+/// assert!(hold_reason(&sunday, Utc.with_ymd_and_hms(2024, 12, 23, 0, 0, 0).unwrap()).is_none());
+/// assert!(parse_windows("Sun 5-5").is_err());
+/// ```
+pub fn hold_reason(windows: &[TimeWindow], time: DateTime<Utc>) -> Option<String> {
+    windows
+        .iter()
+        .find(|window| window.contains(time))
+        .map(|window| format!("no-trade window {}", window))
+}
@@ -0,0 +1,30 @@
+/// Unrealized P&L of a position bought at `avg_buy_price`, in percent
+pub fn unrealized_pnl_pct(avg_buy_price: f64, price: f64) -> f64 {
+    if avg_buy_price <= 0.0 {
+        return 0.0;
+    }
+    (price - avg_buy_price) / avg_buy_price * 100.0
+}
+
+/// Amount of `held` to sell once the position is more than `drawdown_pct`
+/// below its average buy price: `fraction` (0..1) of it. `None` while the
+/// loss is within the limit or when `drawdown_pct` is 0 (disabled).
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::utils::partial_exit::partial_exit_amount;
+/// // 8% under water with a 5% limit: sell a third
+/// let amount = partial_exit_amount(3.0, 100.0, 92.0, 5.0, 1.0 / 3.0).unwrap();
+/// assert!((amount - 1.0).abs() < 1e-9);
+/// assert_eq!(partial_exit_amount(3.0, 100.0, 96.0, 5.0, 0.5), None);
+/// assert_eq!(partial_exit_amount(3.0, 100.0, 50.0, 0.0, 0.5), None);
+/// assert_eq!(partial_exit_amount(0.0, 100.0, 50.0, 5.0, 0.5), None);
+/// ```
+pub fn partial_exit_amount(held: f64, avg_buy_price: f64, price: f64, drawdown_pct: f64, fraction: f64) -> Option<f64> {
+    if drawdown_pct <= 0.0 || held <= 0.0 || -unrealized_pnl_pct(avg_buy_price, price) <= drawdown_pct {
+        return None;
+    }
+    let amount = held * fraction.clamp(0.0, 1.0);
+    (amount > 0.0).then_some(amount)
+}
@@ -0,0 +1,17 @@
+/// Lowest sell price that still clears the minimum profit after fees.
+///
+/// Fees are paid on both the buy and the sell, so `fee_pct` (per side) is
+/// counted twice. All percentages are plain percent values.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::utils::profit_guard::min_sell_price;
+/// // 2% profit + 2 * 0.1% fees on a 100.0 entry
+/// assert!((min_sell_price(100.0, 2.0, 0.1) - 102.2).abs() < 1e-9);
+/// // no profit target: just the fee-adjusted breakeven
+/// assert!((min_sell_price(100.0, 0.0, 0.1) - 100.2).abs() < 1e-9);
+/// ```
+pub fn min_sell_price(avg_buy_price: f64, min_profit_pct: f64, fee_pct: f64) -> f64 {
+    avg_buy_price * (1.0 + (min_profit_pct + 2.0 * fee_pct) / 100.0)
+}
@@ -0,0 +1,232 @@
+use color_eyre::eyre::WrapErr;
+use serde::Deserialize;
+
+/// `numer / denom` in percent, `0.0` when the denominator is zero or the
+/// result isn't finite, so empty runs don't render as `NaN%`.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::utils::report_math::safe_pct;
+/// assert_eq!(safe_pct(1.0, 4.0), 25.0);
+/// assert_eq!(safe_pct(3.0, 0.0), 0.0);
+/// assert_eq!(safe_pct(f64::NAN, 2.0), 0.0);
+/// ```
+pub fn safe_pct(numer: f64, denom: f64) -> f64 {
+    if denom == 0.0 {
+        return 0.0;
+    }
+    finite_or_zero(numer / denom * 100.0)
+}
+
+/// Replace NaN and the `INFINITY`/`NEG_INFINITY` fold sentinels with `0.0`.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::utils::report_math::finite_or_zero;
+/// let best = Vec::<f64>::new().into_iter().fold(f64::NEG_INFINITY, f64::max);
+/// assert_eq!(finite_or_zero(best), 0.0);
+/// assert_eq!(finite_or_zero(-1.5), -1.5);
+/// ```
+pub fn finite_or_zero(value: f64) -> f64 {
+    if value.is_finite() { value } else { 0.0 }
+}
+
+/// `items` by descending profit, ties broken by name, so reports built
+/// from a `HashMap` list their rows in the same order on every run.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use botmarley::utils::report_math::rank_by_profit;
+/// let stats = HashMap::from([("SOLUSDC", 1.5), ("ETHUSDC", 2.0), ("ADAUSDC", 1.5), ("BTCUSDC", 3.0)]);
+/// let ranked: Vec<&str> = rank_by_profit(stats.iter(), |(pair, profit)| (**profit, **pair))
+///     .into_iter()
+///     .map(|(pair, _)| *pair)
+///     .collect();
+/// assert_eq!(ranked, ["BTCUSDC", "ETHUSDC", "ADAUSDC", "SOLUSDC"]);
+/// // the hash order of another map with the same entries doesn't matter
+/// let again: HashMap<_, _> = stats.clone().into_iter().collect();
+/// let ranked_again: Vec<&str> = rank_by_profit(again.iter(), |(pair, profit)| (**profit, **pair))
+///     .into_iter()
+///     .map(|(pair, _)| *pair)
+///     .collect();
+/// assert_eq!(ranked, ranked_again);
+/// ```
+pub fn rank_by_profit<T, F>(items: impl IntoIterator<Item = T>, key: F) -> Vec<T>
+where
+    F: Fn(&T) -> (f64, &str),
+{
+    let mut items: Vec<T> = items.into_iter().collect();
+    items.sort_by(|a, b| {
+        let ((profit_a, name_a), (profit_b, name_b)) = (key(a), key(b));
+        profit_b.total_cmp(&profit_a).then_with(|| name_a.cmp(name_b))
+    });
+    items
+}
+
+/// The parts of a `btc_test` JSON report needed for a comparison.
+/// Everything else in the file is ignored.
+#[derive(Debug, Deserialize)]
+pub struct SavedReport {
+    pub symbol: String,
+    pub model: String,
+    pub test_period_from: String,
+    pub test_period_to: String,
+    pub total_decisions: usize,
+    pub accuracy_30m: f64,
+    pub accuracy_1h: f64,
+    pub accuracy_2h: f64,
+    pub total_profit_loss_30m: f64,
+    pub total_profit_loss_1h: f64,
+    pub total_profit_loss_2h: f64,
+    pub portfolio_return_30m: f64,
+    pub portfolio_return_1h: f64,
+    pub portfolio_return_2h: f64,
+    pub fatal_buy_decisions_1h: usize,
+    pub fatal_sell_decisions_1h: usize,
+    pub results: Vec<SavedResult>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SavedResult {
+    pub profit_loss_1h: Option<f64>,
+}
+
+impl SavedReport {
+    pub fn load(path: &str) -> color_eyre::Result<Self> {
+        let json = std::fs::read_to_string(path).wrap_err_with(|| format!("Failed to read {}", path))?;
+        serde_json::from_str(&json).wrap_err_with(|| format!("{} is not a btc_test JSON report", path))
+    }
+
+    /// Largest peak-to-trough drop of the cumulative 1h decision P&L, in percent points.
+    pub fn max_drawdown_1h(&self) -> f64 {
+        max_drawdown(self.results.iter().filter_map(|r| r.profit_loss_1h))
+    }
+}
+
+/// Largest peak-to-trough drop of the running sum of `profits`, measured
+/// from the starting zero.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::utils::report_math::max_drawdown;
+/// // peaks at +3, falls to -2
+/// assert_eq!(max_drawdown([1.0, 2.0, -4.0, -1.0, 6.0]), 5.0);
+/// // losing from the start counts from zero
+/// assert_eq!(max_drawdown([-1.5, -0.5]), 2.0);
+/// assert_eq!(max_drawdown([1.0, 1.0]), 0.0);
+/// assert_eq!(max_drawdown(Vec::new()), 0.0);
+/// ```
+pub fn max_drawdown(profits: impl IntoIterator<Item = f64>) -> f64 {
+    let mut cumulative = 0.0;
+    let mut peak = 0.0_f64;
+    let mut drawdown = 0.0_f64;
+    for profit in profits {
+        cumulative += profit;
+        peak = peak.max(cumulative);
+        drawdown = drawdown.max(peak - cumulative);
+    }
+    drawdown
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Winner {
+    A,
+    B,
+    Tie,
+}
+
+/// Pick the better of two values, `higher_is_better` decides the direction.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::utils::report_math::{winner, Winner};
+/// assert_eq!(winner(60.0, 55.0, true), Winner::A);
+/// // a drawdown is better when it's smaller
+/// assert_eq!(winner(4.0, 2.5, false), Winner::B);
+/// assert_eq!(winner(1.0, 1.0 + 1e-12, true), Winner::Tie);
+/// ```
+pub fn winner(a: f64, b: f64, higher_is_better: bool) -> Winner {
+    if (a - b).abs() < 1e-9 {
+        Winner::Tie
+    } else if (a > b) == higher_is_better {
+        Winner::A
+    } else {
+        Winner::B
+    }
+}
+
+/// One row of a run comparison.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricComparison {
+    pub name: &'static str,
+    pub a: f64,
+    pub b: f64,
+    pub winner: Winner,
+}
+
+/// Side-by-side accuracy, P&L, portfolio return, drawdown and fatal
+/// decision counts of two runs, with the winner of each.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::utils::report_math::{compare_reports, SavedReport, Winner};
+/// let report = |model: &str, accuracy_1h: f64, return_1h: f64, profits: &str, fatal: usize| -> SavedReport {
+///     serde_json::from_str(&format!(r#"{{
+///         "symbol": "BTCUSDC", "model": "{model}", "test_period_from": "2024-10-01", "test_period_to": "2024-10-02",
+///         "total_decisions": 3,
+///         "accuracy_30m": 50.0, "accuracy_1h": {accuracy_1h}, "accuracy_2h": 40.0,
+///         "total_profit_loss_30m": 1.0, "total_profit_loss_1h": 2.0, "total_profit_loss_2h": 3.0,
+///         "portfolio_return_30m": 0.5, "portfolio_return_1h": {return_1h}, "portfolio_return_2h": 1.5,
+///         "fatal_buy_decisions_1h": {fatal}, "fatal_sell_decisions_1h": 0,
+///         "results": [{profits}]
+///     }}"#)).unwrap()
+/// };
+/// let a = report("model-a", 66.7, 1.2, r#"{"profit_loss_1h": 2.0}, {"profit_loss_1h": -3.0}, {"profit_loss_1h": 3.0}"#, 1);
+/// let b = report("model-b", 33.3, 2.4, r#"{"profit_loss_1h": 1.0}, {"profit_loss_1h": -1.0}, {"profit_loss_1h": 2.0}"#, 0);
+/// let winners: Vec<(&str, Winner)> = compare_reports(&a, &b).iter().map(|row| (row.name, row.winner)).collect();
+/// assert_eq!(winners, [
+///     ("Accuracy 30m (%)", Winner::Tie),
+///     ("Accuracy 1h (%)", Winner::A),
+///     ("Accuracy 2h (%)", Winner::Tie),
+///     ("Total P&L 30m (%)", Winner::Tie),
+///     ("Total P&L 1h (%)", Winner::Tie),
+///     ("Total P&L 2h (%)", Winner::Tie),
+///     ("Portfolio return 30m (%)", Winner::Tie),
+///     ("Portfolio return 1h (%)", Winner::B),
+///     ("Portfolio return 2h (%)", Winner::Tie),
+///     // 3 vs 1 point drop, lower wins
+///     ("Max drawdown 1h (%)", Winner::B),
+///     ("Fatal decisions 1h", Winner::B),
+/// ]);
+/// ```
+pub fn compare_reports(a: &SavedReport, b: &SavedReport) -> Vec<MetricComparison> {
+    let metrics = [
+        ("Accuracy 30m (%)", a.accuracy_30m, b.accuracy_30m, true),
+        ("Accuracy 1h (%)", a.accuracy_1h, b.accuracy_1h, true),
+        ("Accuracy 2h (%)", a.accuracy_2h, b.accuracy_2h, true),
+        ("Total P&L 30m (%)", a.total_profit_loss_30m, b.total_profit_loss_30m, true),
+        ("Total P&L 1h (%)", a.total_profit_loss_1h, b.total_profit_loss_1h, true),
+        ("Total P&L 2h (%)", a.total_profit_loss_2h, b.total_profit_loss_2h, true),
+        ("Portfolio return 30m (%)", a.portfolio_return_30m, b.portfolio_return_30m, true),
+        ("Portfolio return 1h (%)", a.portfolio_return_1h, b.portfolio_return_1h, true),
+        ("Portfolio return 2h (%)", a.portfolio_return_2h, b.portfolio_return_2h, true),
+        ("Max drawdown 1h (%)", a.max_drawdown_1h(), b.max_drawdown_1h(), false),
+        (
+            "Fatal decisions 1h",
+            (a.fatal_buy_decisions_1h + a.fatal_sell_decisions_1h) as f64,
+            (b.fatal_buy_decisions_1h + b.fatal_sell_decisions_1h) as f64,
+            false,
+        ),
+    ];
+    metrics
+        .into_iter()
+        .map(|(name, a, b, higher_is_better)| MetricComparison { name, a, b, winner: winner(a, b, higher_is_better) })
+        .collect()
+}
@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// Aggregate risk picture of a portfolio for the prompt.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RiskSummary {
+    pub total_value: f64,
+    pub allocated_pct: f64,
+    pub cash_pct: f64,
+    /// Pair and its share of the total value
+    pub largest_position: Option<(String, f64)>,
+    pub realized_pnl_today: f64,
+}
+
+/// Start of the UTC day containing `now_ms`.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::utils::risk::utc_day_start;
+/// let day = 24 * 60 * 60 * 1000;
+/// assert_eq!(utc_day_start(3 * day), 3 * day);
+/// assert_eq!(utc_day_start(4 * day - 1), 3 * day);
+/// ```
+pub fn utc_day_start(now_ms: i64) -> i64 {
+    now_ms - now_ms.rem_euclid(DAY_MS)
+}
+
+/// Realized P&L of the sells at or after `since_ms`, from `(timestamp ms,
+/// pair, action, amount, value)` trades in time order. Each sell is
+/// measured against the average cost of the position it closes, so buys
+/// before `since_ms` still set the cost basis.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::utils::risk::{realized_pnl_since, utc_day_start};
+/// let day = 24 * 60 * 60 * 1000;
+/// let trades = [
+///     // average cost 110 after both buys
+///     (0, "BTCUSDC", "buy", 1.0, 100.0),
+///     (1_000, "BTCUSDC", "buy", 1.0, 120.0),
+///     // yesterday: half a coin sold at 130, +10
+///     (day - 1, "BTCUSDC", "sell", 0.5, 65.0),
+///     // today: the other half at 100, -5, and half the ETH for +7
+///     (day, "BTCUSDC", "sell", 0.5, 50.0),
+///     (day + 1, "ETHUSDC", "buy", 2.0, 20.0),
+///     (day + 2, "ETHUSDC", "sell", 1.0, 17.0),
+/// ];
+/// assert!((realized_pnl_since(trades, utc_day_start(day + 5)) - 2.0).abs() < 1e-9);
+/// assert!((realized_pnl_since(trades, 0) - 12.0).abs() < 1e-9);
+/// // the sell in the last ms before the cutoff isn't counted
+/// assert!((realized_pnl_since(trades, day) - 2.0).abs() < 1e-9);
+/// assert!((realized_pnl_since(trades, day - 1) - 12.0).abs() < 1e-9);
+/// // a sell without a position realizes nothing
+/// assert_eq!(realized_pnl_since([(0, "BTCUSDC", "sell", 1.0, 100.0)], 0), 0.0);
+/// ```
+pub fn realized_pnl_since<'a>(trades: impl IntoIterator<Item = (i64, &'a str, &'a str, f64, f64)>, since_ms: i64) -> f64 {
+    // pair -> (amount held, total cost)
+    let mut positions: HashMap<&str, (f64, f64)> = HashMap::new();
+    let mut realized = 0.0;
+    for (timestamp, pair, action, traded, value) in trades {
+        let (amount, cost) = positions.entry(pair).or_insert((0.0, 0.0));
+        match action {
+            "buy" => {
+                *amount += traded;
+                *cost += value;
+            }
+            "sell" if *amount > 0.0 => {
+                let basis = *cost / *amount * traded;
+                if timestamp >= since_ms {
+                    realized += value - basis;
+                }
+                *amount -= traded;
+                *cost -= basis;
+            }
+            _ => {}
+        }
+    }
+    realized
+}
+
+/// Allocation and concentration from the cash balance and the current
+/// value of every open position.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::utils::risk::risk_summary;
+/// let positions = vec![("BTCUSDC".to_string(), 300.0), ("ETHUSDC".to_string(), 100.0)];
+/// let risk = risk_summary(600.0, positions, -4.0);
+/// assert_eq!((risk.total_value, risk.allocated_pct, risk.cash_pct), (1000.0, 40.0, 60.0));
+/// assert_eq!(risk.largest_position, Some(("BTCUSDC".to_string(), 30.0)));
+/// assert_eq!(risk.realized_pnl_today, -4.0);
+/// assert_eq!(risk_summary(0.0, Vec::new(), 0.0).cash_pct, 0.0);
+/// ```
+pub fn risk_summary(cash: f64, position_values: Vec<(String, f64)>, realized_pnl_today: f64) -> RiskSummary {
+    let allocated: f64 = position_values.iter().map(|(_, value)| value).sum();
+    let total_value = cash + allocated;
+    let pct_of_total = |value: f64| if total_value > 0.0 { value / total_value * 100.0 } else { 0.0 };
+
+    let largest_position = position_values
+        .into_iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+        .map(|(pair, value)| (pair, pct_of_total(value)));
+
+    RiskSummary {
+        total_value,
+        allocated_pct: pct_of_total(allocated),
+        cash_pct: pct_of_total(cash),
+        largest_position,
+        realized_pnl_today,
+    }
+}
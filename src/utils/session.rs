@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Main trading session by UTC hour. Overlaps are assigned to the later
+/// session: Europe from 08:00, US from 13:00, back to Asia from 21:00.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TradingSession {
+    Asia,
+    Europe,
+    Us,
+}
+
+impl std::fmt::Display for TradingSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            TradingSession::Asia => "Asia",
+            TradingSession::Europe => "Europe",
+            TradingSession::Us => "US",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Session active at a given UTC hour (0-23).
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::utils::session::{session_for_hour, TradingSession};
+/// assert_eq!(session_for_hour(3), TradingSession::Asia);
+/// assert_eq!(session_for_hour(9), TradingSession::Europe);
+/// assert_eq!(session_for_hour(15), TradingSession::Us);
+/// assert_eq!(session_for_hour(22), TradingSession::Asia);
+/// ```
+pub fn session_for_hour(hour: u32) -> TradingSession {
+    match hour % 24 {
+        8..=12 => TradingSession::Europe,
+        13..=20 => TradingSession::Us,
+        _ => TradingSession::Asia,
+    }
+}
+
+pub fn session_at(time: DateTime<Utc>) -> TradingSession {
+    session_for_hour(time.hour())
+}
+
+pub fn current_session() -> TradingSession {
+    session_at(Utc::now())
+}
+
+/// Average candle range, `(high - low) / close` in percent, of the candles
+/// opening in `session`. `None` when there are no such candles.
+pub fn session_volatility(
+    open_time_ms: &[i64],
+    high: &[f64],
+    low: &[f64],
+    close: &[f64],
+    session: TradingSession,
+) -> Option<f64> {
+    let ranges: Vec<f64> = open_time_ms
+        .iter()
+        .zip(high.iter().zip(low.iter().zip(close.iter())))
+        .filter(|(time, _)| DateTime::<Utc>::from_timestamp_millis(**time).map(session_at) == Some(session))
+        .filter(|(_, (_, (_, close)))| **close > 0.0)
+        .map(|(_, (high, (low, close)))| (high - low) / close * 100.0)
+        .collect();
+    (!ranges.is_empty()).then(|| ranges.iter().sum::<f64>() / ranges.len() as f64)
+}
+
+/// For every candle, `session_volatility` of its own session over the
+/// candles up to and including it, in a single pass. `None` while that
+/// session has no candle with a positive close yet.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::utils::session::{session_volatility, session_volatility_series, TradingSession};
+/// // hourly candles from midnight UTC: Asia, then Europe from 08:00
+/// let time: Vec<i64> = (0..12).map(|h| h * 3_600_000).collect();
+/// let high: Vec<f64> = (0..12).map(|h| 101.0 + h as f64 / 10.0).collect();
+/// let low = vec![100.0; 12];
+/// let close = vec![100.0; 12];
+/// let series = session_volatility_series(&time, &high, &low, &close);
+/// assert_eq!(series[5], session_volatility(&time[..6], &high[..6], &low[..6], &close[..6], TradingSession::Asia));
+/// assert_eq!(series[9], session_volatility(&time[..10], &high[..10], &low[..10], &close[..10], TradingSession::Europe));
+/// ```
+pub fn session_volatility_series(open_time_ms: &[i64], high: &[f64], low: &[f64], close: &[f64]) -> Vec<Option<f64>> {
+    let mut totals: HashMap<TradingSession, (f64, usize)> = HashMap::new();
+    open_time_ms
+        .iter()
+        .zip(high.iter().zip(low.iter().zip(close.iter())))
+        .map(|(time, (high, (low, close)))| {
+            let session = DateTime::<Utc>::from_timestamp_millis(*time).map(session_at)?;
+            let (sum, count) = totals.entry(session).or_insert((0.0, 0));
+            if *close > 0.0 {
+                *sum += (high - low) / close * 100.0;
+                *count += 1;
+            }
+            (*count > 0).then(|| *sum / *count as f64)
+        })
+        .collect()
+}
@@ -0,0 +1,21 @@
+/// Price a market order actually fills at once slippage is taken into
+/// account: buys fill higher, sells fill lower.
+///
+/// # Arguments
+///
+/// * `price` - The quoted price at decision time.
+/// * `is_buy` - `true` for buys, `false` for sells.
+/// * `slippage_pct` - Slippage in percent (e.g. `0.05` for 0.05%).
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::utils::slippage::fill_price;
+/// assert_eq!(fill_price(100.0, true, 1.0), 101.0);
+/// assert_eq!(fill_price(100.0, false, 1.0), 99.0);
+/// assert_eq!(fill_price(100.0, true, 0.0), 100.0);
+/// ```
+pub fn fill_price(price: f64, is_buy: bool, slippage_pct: f64) -> f64 {
+    let slippage = price * slippage_pct.max(0.0) / 100.0;
+    if is_buy { price + slippage } else { price - slippage }
+}
@@ -0,0 +1,50 @@
+use std::ops::RangeInclusive;
+
+use color_eyre::eyre::{eyre, WrapErr};
+
+use crate::utils::date_to_timestamp::date_string_to_timestamp;
+
+/// Open-time ranges, in ms, of a walk-forward run over the `(from, to)`
+/// YYYY-MM-DD dates of each sample. Kline range reads include both ends,
+/// so the in-sample range stops 1 ms before the out-of-sample one starts
+/// and no kline lands in both.
+///
+/// # Examples
+///
+/// ```
+/// use botmarley::utils::date_to_timestamp::date_string_to_timestamp;
+/// use botmarley::utils::walk_forward::sample_ranges;
+/// let (in_sample, out_sample) = sample_ranges(("2024-10-01", "2024-10-02"), ("2024-10-02", "2024-10-03")).unwrap();
+/// let day = |date| date_string_to_timestamp(date).unwrap() * 1000;
+/// assert_eq!(in_sample, day("2024-10-01")..=day("2024-10-02") - 1);
+/// assert_eq!(out_sample, day("2024-10-02")..=day("2024-10-03"));
+///
+/// // 5m klines from the first day through the last: each lands in exactly one sample, in order
+/// let open_times: Vec<i64> = (day("2024-10-01")..=day("2024-10-03")).step_by(300_000).collect();
+/// let (train, test): (Vec<i64>, Vec<i64>) = open_times.iter().partition(|t| in_sample.contains(t));
+/// assert!(test.iter().all(|t| out_sample.contains(t)));
+/// assert_eq!((train.len(), test.len()), (288, 289));
+/// assert_eq!(test[0], day("2024-10-02"));
+/// assert!(train.last() < test.first());
+///
+/// // the held-out range has to come after the in-sample one
+/// assert!(sample_ranges(("2024-10-02", "2024-10-03"), ("2024-10-01", "2024-10-02")).is_err());
+/// assert!(sample_ranges(("2024-10-01", "2024-10-03"), ("2024-10-02", "2024-10-04")).is_err());
+/// assert!(sample_ranges(("2024-10-01", "2024-13-01"), ("2024-10-02", "2024-10-03")).is_err());
+/// ```
+pub fn sample_ranges(in_sample: (&str, &str), out_sample: (&str, &str)) -> color_eyre::Result<(RangeInclusive<i64>, RangeInclusive<i64>)> {
+    let ms = |date: &str| -> color_eyre::Result<i64> {
+        Ok(date_string_to_timestamp(date).wrap_err_with(|| format!("Invalid date '{}', expected YYYY-MM-DD", date))? * 1000)
+    };
+    let (in_start, in_end) = (ms(in_sample.0)?, ms(in_sample.1)?);
+    let (out_start, out_end) = (ms(out_sample.0)?, ms(out_sample.1)?);
+
+    if in_start > out_start || in_end > out_start {
+        return Err(eyre!(
+            "Out-of-sample range {} to {} must start after the in-sample range {} to {}",
+            out_sample.0, out_sample.1, in_sample.0, in_sample.1
+        ));
+    }
+
+    Ok((in_start..=in_end.min(out_start - 1), out_start..=out_end))
+}